@@ -0,0 +1,484 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::fmt::Debug;
+
+use crate::api::{AccessPolicyError, DomainError};
+use crate::document::traits::AsDocument;
+use crate::document::{Document, DocumentBuilder, DocumentId};
+use crate::identity::PublicKey;
+use crate::operation::traits::AsOperation;
+use crate::operation::{Operation, OperationValue};
+use crate::schema::{FieldType, Schema, SchemaId};
+use crate::storage_provider::error::DocumentStorageError;
+use crate::storage_provider::traits::OperationStore;
+
+/// Decides which public keys are allowed to write to a document.
+///
+/// Policies are evaluated by `publish_with_config` for every operation after it was validated and
+/// it's document was determined.
+pub trait WriteAccessPolicy: Debug + Send + Sync {
+    /// Returns true if the public key is allowed to publish this operation.
+    ///
+    /// `document` is the state of the document at the view the UPDATE or DELETE operation refers
+    /// to with it's previous operations, it's author is the public key which published the CREATE
+    /// operation. It is `None` for CREATE operations.
+    fn is_allowed(
+        &self,
+        schema: &Schema,
+        document: Option<&Document>,
+        public_key: &PublicKey,
+        operation: &Operation,
+    ) -> bool;
+}
+
+/// Policy allowing every public key to write to every document.
+#[derive(Clone, Debug, Default)]
+pub struct OpenAccess;
+
+impl WriteAccessPolicy for OpenAccess {
+    fn is_allowed(
+        &self,
+        _schema: &Schema,
+        _document: Option<&Document>,
+        _public_key: &PublicKey,
+        _operation: &Operation,
+    ) -> bool {
+        true
+    }
+}
+
+/// Policy allowing every public key to create documents but only their author to update and
+/// delete them.
+#[derive(Clone, Debug, Default)]
+pub struct OwnerOnly;
+
+impl WriteAccessPolicy for OwnerOnly {
+    fn is_allowed(
+        &self,
+        _schema: &Schema,
+        document: Option<&Document>,
+        public_key: &PublicKey,
+        _operation: &Operation,
+    ) -> bool {
+        match document {
+            Some(document) => document.author() == public_key,
+            None => true,
+        }
+    }
+}
+
+/// Policy allowing the author of a document and all public keys listed in one of it's fields to
+/// update and delete it, every public key can create documents.
+///
+/// The field is a string field of one schema containing hex encoded public keys separated by
+/// commas or whitespace. Operations writing a value to it which contains anything else are
+/// denied. Only the author of a document can change the field, editors can't add or remove
+/// public keys. Documents of other schemas can only be written to by their author.
+#[derive(Clone, Debug)]
+pub struct AllowListField {
+    schema_id: SchemaId,
+    field: String,
+}
+
+impl AllowListField {
+    /// Returns a policy reading the allowed public keys from the passed field of the schema.
+    ///
+    /// Returns an error if the schema doesn't contain a string field with this name.
+    pub fn new(schema: &Schema, field: &str) -> Result<Self, AccessPolicyError> {
+        match schema.fields().get(field) {
+            Some(FieldType::String) => Ok(Self {
+                schema_id: schema.id().to_owned(),
+                field: field.to_owned(),
+            }),
+            _ => Err(AccessPolicyError::InvalidAllowListField(
+                schema.id().to_owned(),
+                field.to_owned(),
+            )),
+        }
+    }
+}
+
+impl WriteAccessPolicy for AllowListField {
+    fn is_allowed(
+        &self,
+        schema: &Schema,
+        document: Option<&Document>,
+        public_key: &PublicKey,
+        operation: &Operation,
+    ) -> bool {
+        if schema.id() != &self.schema_id {
+            return OwnerOnly.is_allowed(schema, document, public_key, operation);
+        }
+
+        let written_allow_list = operation
+            .fields()
+            .and_then(|fields| fields.get(&self.field).cloned());
+
+        if let Some(value) = &written_allow_list {
+            if parse_allow_list(value).is_none() {
+                return false;
+            }
+        }
+
+        let document = match document {
+            Some(document) => document,
+            None => return true,
+        };
+
+        if document.author() == public_key {
+            return true;
+        }
+
+        // Editors are not allowed to change who else can edit the document.
+        if written_allow_list.is_some() {
+            return false;
+        }
+
+        match document.get(&self.field).and_then(parse_allow_list) {
+            Some(allow_list) => allow_list.contains(public_key),
+            None => false,
+        }
+    }
+}
+
+/// Returns the public keys of an allow list or `None` if it contains invalid values.
+fn parse_allow_list(value: &OperationValue) -> Option<Vec<PublicKey>> {
+    match value {
+        OperationValue::String(allow_list) => allow_list
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<PublicKey>().ok())
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Returns an error if the policy doesn't allow the public key to publish the operation.
+///
+/// For UPDATE and DELETE operations the document is materialised from it's stored operations up
+/// to the view the operation refers to. Access is therefore decided by the state the author of
+/// the operation saw when publishing it and doesn't depend on the order in which concurrent
+/// operations arrive.
+pub(crate) async fn check_write_access<S: OperationStore>(
+    store: &S,
+    policy: &dyn WriteAccessPolicy,
+    schema: &Schema,
+    public_key: &PublicKey,
+    operation: &Operation,
+    document_id: &DocumentId,
+) -> Result<(), DomainError> {
+    let document = match operation.previous() {
+        Some(previous) => {
+            let operations = store.get_operations_by_document_id(document_id).await?;
+            let (document, _) = DocumentBuilder::from(&operations)
+                .with_schema(schema)
+                .build_to_view_id(previous)
+                .map_err(DocumentStorageError::from)?;
+            Some(document)
+        }
+        None => None,
+    };
+
+    if !policy.is_allowed(schema, document.as_ref(), public_key, operation) {
+        return Err(DomainError::WriteAccessDenied(
+            public_key.to_owned(),
+            operation.action().as_str().to_owned(),
+            document_id.to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rstest::rstest;
+
+    use crate::api::{next_args, publish_with_config, DomainError, PublishConfig};
+    use crate::document::DocumentViewId;
+    use crate::entry::encode::sign_and_encode_entry;
+    use crate::entry::traits::AsEncodedEntry;
+    use crate::entry::LogId;
+    use crate::identity::KeyPair;
+    use crate::operation::encode::encode_operation;
+    use crate::operation::traits::AsOperation;
+    use crate::operation::{OperationAction, OperationBuilder, OperationValue};
+    use crate::schema::{FieldType, Schema, SchemaId, SchemaName};
+    use crate::storage_provider::traits::EntryStore;
+    use crate::test_utils::fixtures::{random_document_view_id, schema};
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::{AccessPolicyError, AllowListField, OpenAccess, OwnerOnly, WriteAccessPolicy};
+
+    fn editable_schema() -> Schema {
+        let schema_name = SchemaName::new("editable").unwrap();
+        let schema_id = SchemaId::new_application(&schema_name, &random_document_view_id());
+        schema(
+            vec![
+                ("title".into(), FieldType::String),
+                ("editors".into(), FieldType::String),
+            ],
+            schema_id,
+            "Document with a list of editors",
+        )
+    }
+
+    /// Publish an UPDATE with the passed fields to the document view, enforcing the write access
+    /// policy. Returns the view id of the document after the UPDATE.
+    async fn publish_update(
+        store: &MemoryStore,
+        schema: &Schema,
+        key_pair: &KeyPair,
+        previous: &DocumentViewId,
+        fields: &[(&str, OperationValue)],
+        policy: Arc<dyn WriteAccessPolicy>,
+    ) -> Result<DocumentViewId, DomainError> {
+        let operation = OperationBuilder::new(schema.id())
+            .action(OperationAction::Update)
+            .previous(previous)
+            .fields(fields)
+            .build()
+            .unwrap();
+
+        let (backlink, skiplink, seq_num, log_id) =
+            next_args(store, &key_pair.public_key(), operation.previous().as_ref())
+                .await
+                .unwrap();
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = sign_and_encode_entry(
+            &log_id,
+            &seq_num,
+            skiplink.as_ref(),
+            backlink.as_ref(),
+            &encoded_operation,
+            key_pair,
+        )
+        .unwrap();
+
+        publish_with_config(
+            store,
+            &PublishConfig::new().write_access(policy),
+            schema,
+            &encoded_entry,
+            &(&operation).into(),
+            &encoded_operation,
+        )
+        .await
+        .map(|_| DocumentViewId::new(&[encoded_entry.hash().into()]))
+    }
+
+    fn title() -> Vec<(&'static str, OperationValue)> {
+        vec![("title", OperationValue::String("Edited".into()))]
+    }
+
+    /// Policy enforced when publishing an UPDATE to the test document.
+    enum Policy {
+        Open,
+        OwnerOnly,
+        AllowList(&'static str),
+    }
+
+    impl Policy {
+        fn build(self, schema: &Schema) -> Arc<dyn WriteAccessPolicy> {
+            match self {
+                Policy::Open => Arc::new(OpenAccess),
+                Policy::OwnerOnly => Arc::new(OwnerOnly),
+                Policy::AllowList(field) => Arc::new(AllowListField::new(schema, field).unwrap()),
+            }
+        }
+    }
+
+    /// Public key publishing an UPDATE to the test document.
+    enum Writer {
+        Author,
+        Editor,
+        Stranger,
+    }
+
+    /// Populate the store with a document listing an editor, returns the author and editor key
+    /// pairs and the view id of the document.
+    async fn populate_document(
+        store: &MemoryStore,
+        schema: &Schema,
+    ) -> (KeyPair, KeyPair, DocumentViewId) {
+        let editor = KeyPair::new();
+        let editors = format!("{}, {}", KeyPair::new().public_key(), editor.public_key());
+
+        let config = PopulateStoreConfig {
+            no_of_entries: 1,
+            no_of_logs: 1,
+            no_of_public_keys: 1,
+            schema: schema.clone(),
+            create_operation_fields: vec![
+                ("title", OperationValue::String("Hello".into())),
+                ("editors", OperationValue::String(editors)),
+            ],
+            ..PopulateStoreConfig::default()
+        };
+        let (mut key_pairs, documents) = populate_store(store, &config).await;
+        let view_id = DocumentViewId::new(&[documents[0].as_str().parse().unwrap()]);
+
+        (key_pairs.remove(0), editor, view_id)
+    }
+
+    #[rstest]
+    #[case::open_to_author(Policy::Open, Writer::Author, true)]
+    #[case::open_to_stranger(Policy::Open, Writer::Stranger, true)]
+    #[case::owner_only_to_author(Policy::OwnerOnly, Writer::Author, true)]
+    #[case::owner_only_to_editor(Policy::OwnerOnly, Writer::Editor, false)]
+    #[case::allow_list_to_author(Policy::AllowList("editors"), Writer::Author, true)]
+    #[case::allow_list_to_editor(Policy::AllowList("editors"), Writer::Editor, true)]
+    #[case::allow_list_to_stranger(Policy::AllowList("editors"), Writer::Stranger, false)]
+    #[case::allow_list_in_other_field(Policy::AllowList("title"), Writer::Editor, false)]
+    #[tokio::test]
+    async fn enforces_write_access(
+        #[case] policy: Policy,
+        #[case] writer: Writer,
+        #[case] is_allowed: bool,
+    ) {
+        let store = MemoryStore::default();
+        let schema = editable_schema();
+        let (author, editor, view_id) = populate_document(&store, &schema).await;
+
+        let key_pair = match writer {
+            Writer::Author => author,
+            Writer::Editor => editor,
+            Writer::Stranger => KeyPair::new(),
+        };
+
+        let policy = policy.build(&schema);
+        let result = publish_update(&store, &schema, &key_pair, &view_id, &title(), policy).await;
+
+        match is_allowed {
+            true => assert!(result.is_ok()),
+            false => assert!(matches!(result, Err(DomainError::WriteAccessDenied(..)))),
+        }
+
+        // Rejected operations are not stored.
+        let latest_entry = store
+            .get_latest_entry(&key_pair.public_key(), &LogId::default())
+            .await
+            .unwrap();
+        assert_eq!(latest_entry.is_some(), is_allowed);
+    }
+
+    #[tokio::test]
+    async fn everyone_creates_documents() {
+        let store = MemoryStore::default();
+        let schema = editable_schema();
+        let key_pair = KeyPair::new();
+
+        let operation = OperationBuilder::new(schema.id())
+            .fields(&[
+                ("title", OperationValue::String("Hello".into())),
+                ("editors", OperationValue::String("".into())),
+            ])
+            .build()
+            .unwrap();
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = sign_and_encode_entry(
+            &LogId::default(),
+            &Default::default(),
+            None,
+            None,
+            &encoded_operation,
+            &key_pair,
+        )
+        .unwrap();
+
+        assert!(publish_with_config(
+            &store,
+            &PublishConfig::new().write_access(Arc::new(OwnerOnly)),
+            &schema,
+            &encoded_entry,
+            &(&operation).into(),
+            &encoded_operation,
+        )
+        .await
+        .is_ok());
+    }
+
+    #[test]
+    fn allow_list_field_must_exist() {
+        let schema = editable_schema();
+
+        assert!(AllowListField::new(&schema, "editors").is_ok());
+        assert!(matches!(
+            AllowListField::new(&schema, "owners"),
+            Err(AccessPolicyError::InvalidAllowListField(schema_id, field))
+                if &schema_id == schema.id() && field == "owners"
+        ));
+    }
+
+    #[rstest]
+    #[case::by_author(Writer::Author, "", true)]
+    #[case::by_editor(Writer::Editor, "", false)]
+    #[case::with_invalid_key(Writer::Author, "not a public key", false)]
+    #[tokio::test]
+    async fn protects_allow_list(
+        #[case] writer: Writer,
+        #[case] allow_list: &str,
+        #[case] is_allowed: bool,
+    ) {
+        let store = MemoryStore::default();
+        let schema = editable_schema();
+        let (author, editor, view_id) = populate_document(&store, &schema).await;
+        let policy = Arc::new(AllowListField::new(&schema, "editors").unwrap());
+
+        let key_pair = match writer {
+            Writer::Author => author,
+            Writer::Editor => editor,
+            Writer::Stranger => KeyPair::new(),
+        };
+
+        let fields = [("editors", OperationValue::String(allow_list.into()))];
+        let result = publish_update(&store, &schema, &key_pair, &view_id, &fields, policy).await;
+        assert_eq!(result.is_ok(), is_allowed);
+    }
+
+    #[tokio::test]
+    async fn decides_on_previous_view() {
+        let store = MemoryStore::default();
+        let schema = editable_schema();
+        let (author, editor, view_id) = populate_document(&store, &schema).await;
+        let policy: Arc<dyn WriteAccessPolicy> =
+            Arc::new(AllowListField::new(&schema, "editors").unwrap());
+
+        // The author removes the editor from the allow list.
+        let fields = [("editors", OperationValue::String("".into()))];
+        let new_view_id =
+            publish_update(&store, &schema, &author, &view_id, &fields, policy.clone())
+                .await
+                .unwrap();
+
+        // The editor can still write to the view they were allowed to edit, for example when
+        // they didn't know about the removal yet, but not to the new one.
+        let editor_view_id =
+            publish_update(&store, &schema, &editor, &view_id, &title(), policy.clone())
+                .await
+                .unwrap();
+        assert!(matches!(
+            publish_update(
+                &store,
+                &schema,
+                &editor,
+                &new_view_id,
+                &title(),
+                policy.clone()
+            )
+            .await,
+            Err(DomainError::WriteAccessDenied(..))
+        ));
+
+        // Merging both branches doesn't allow the editor to write anymore.
+        let merged_view_id =
+            DocumentViewId::new(&[editor_view_id.graph_tips(), new_view_id.graph_tips()].concat());
+        assert!(matches!(
+            publish_update(&store, &schema, &editor, &merged_view_id, &title(), policy).await,
+            Err(DomainError::WriteAccessDenied(..))
+        ));
+    }
+}
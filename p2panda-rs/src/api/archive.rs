@@ -0,0 +1,520 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use ciborium::de::from_reader;
+use ciborium::ser::into_writer;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{publish, ArchiveError};
+use crate::document::DocumentId;
+use crate::entry::decode::decode_entry;
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{EncodedEntry, Entry, LogId};
+use crate::hash::{Hash, HashId};
+use crate::identity::PublicKey;
+use crate::operation::decode::decode_operation;
+use crate::operation::plain::PlainOperation;
+use crate::operation::traits::{Actionable, Schematic, WithPublicKey};
+use crate::operation::EncodedOperation;
+use crate::schema::{Schema, SchemaId};
+use crate::storage_provider::traits::{
+    EntryStore, EntryWithOperation, LogStore, OperationStore, SubscriptionStore, TransactionStore,
+};
+use crate::WithId;
+
+/// Version of the archive format written by this implementation.
+const ARCHIVE_VERSION: u64 = 1;
+
+/// Number of entries requested from the store at once when exporting a log.
+const EXPORT_PAGE_SIZE: usize = 100;
+
+/// Portable collection of entries and their operations.
+///
+/// Archives can be used to move data between stores which are not connected over a network. They
+/// are encoded as CBOR with `to_bytes` and contain the entries and operations exactly as they were
+/// published, importing them with `import_archive` validates them like any other published data.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Archive {
+    version: u64,
+    entries: Vec<(EncodedEntry, EncodedOperation)>,
+}
+
+impl Archive {
+    /// Returns a new archive containing the passed entries and operations.
+    ///
+    /// The entries can be in any order, they are sorted causally when importing the archive.
+    pub fn new(entries: Vec<(EncodedEntry, EncodedOperation)>) -> Self {
+        Self {
+            version: ARCHIVE_VERSION,
+            entries,
+        }
+    }
+
+    /// Returns the archived entries and their operations.
+    pub fn entries(&self) -> &[(EncodedEntry, EncodedOperation)] {
+        &self.entries
+    }
+
+    /// Returns the number of archived entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the archive doesn't contain any entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Encode the archive as CBOR.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // Unwrap as writing bytes to a vector doesn't fail.
+        into_writer(self, &mut bytes).unwrap();
+        bytes
+    }
+
+    /// Decode an archive from CBOR bytes.
+    ///
+    /// Returns an error if the bytes are not a valid archive or if it was written with an
+    /// unsupported format version. The archived entries and operations are not validated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ArchiveError> {
+        let archive: Archive =
+            from_reader(bytes).map_err(|err| ArchiveError::InvalidArchive(err.to_string()))?;
+
+        if archive.version != ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(archive.version));
+        }
+
+        Ok(archive)
+    }
+}
+
+/// Selects the logs which are exported into an archive.
+///
+/// Logs are always exported as a whole. Selecting a schema exports the logs of all documents
+/// following it, selecting a public key exports all logs of this public key.
+#[derive(Clone, Debug, Default)]
+pub struct ArchiveSelection {
+    public_keys: Vec<PublicKey>,
+    logs: Vec<(PublicKey, LogId)>,
+    schema_ids: Vec<SchemaId>,
+}
+
+impl ArchiveSelection {
+    /// Returns an empty selection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select all logs of a public key.
+    pub fn public_key(mut self, public_key: &PublicKey) -> Self {
+        self.public_keys.push(public_key.to_owned());
+        self
+    }
+
+    /// Select a single log of a public key.
+    pub fn log(mut self, public_key: &PublicKey, log_id: &LogId) -> Self {
+        self.logs.push((public_key.to_owned(), log_id.to_owned()));
+        self
+    }
+
+    /// Select the logs of all documents following a schema.
+    pub fn schema_id(mut self, schema_id: &SchemaId) -> Self {
+        self.schema_ids.push(schema_id.to_owned());
+        self
+    }
+}
+
+/// Export all selected logs from a store into an archive.
+///
+/// The entries of every log are added in the order of their sequence numbers. Entries whose
+/// payload was removed from the store can't be published again and are left out.
+///
+/// Entries depending on operations which are not part of the selection can only be imported into
+/// stores already containing these operations.
+pub async fn export_archive<S: EntryStore + LogStore + OperationStore + Sync>(
+    store: &S,
+    selection: &ArchiveSelection,
+) -> Result<Archive, ArchiveError> {
+    // Collect all selected logs, ordered by public key and log id.
+    let mut logs: BTreeMap<(String, LogId), PublicKey> = BTreeMap::new();
+
+    for public_key in &selection.public_keys {
+        if let Some(latest_log_id) = store.latest_log_id(public_key).await? {
+            for log_id in 0..=latest_log_id.as_u64() {
+                logs.insert((public_key.to_string(), LogId::new(log_id)), *public_key);
+            }
+        }
+    }
+
+    for (public_key, log_id) in &selection.logs {
+        logs.insert((public_key.to_string(), log_id.to_owned()), *public_key);
+    }
+
+    for schema_id in &selection.schema_ids {
+        let mut documents: HashSet<(PublicKey, DocumentId)> = HashSet::new();
+
+        for operation in store.get_operations_by_schema_id(schema_id).await? {
+            documents.insert((
+                operation.public_key().to_owned(),
+                WithId::<DocumentId>::id(&operation).to_owned(),
+            ));
+        }
+
+        for (public_key, document_id) in documents {
+            if let Some(log_id) = store.get_log_id(&public_key, &document_id).await? {
+                logs.insert((public_key.to_string(), log_id), public_key);
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+
+    for ((_, log_id), public_key) in logs {
+        let mut cursor = None;
+
+        loop {
+            let page = store
+                .get_paginated_entries(&public_key, &log_id, cursor.as_ref(), EXPORT_PAGE_SIZE)
+                .await?;
+
+            for entry in page.entries {
+                if let Some(payload) = entry.payload() {
+                    entries.push((
+                        EncodedEntry::from_bytes(&entry.into_bytes()),
+                        payload.clone(),
+                    ));
+                }
+            }
+
+            match page.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+    }
+
+    Ok(Archive::new(entries))
+}
+
+/// Decoded entry and operation of an archive.
+struct ArchivedEntry {
+    hash: Hash,
+    entry: Entry,
+    encoded_entry: EncodedEntry,
+    operation: PlainOperation,
+    encoded_operation: EncodedOperation,
+}
+
+/// Import all entries and operations of an archive into a store.
+///
+/// Every entry is published with `publish`, it is validated against the passed schemas and the
+/// entries already contained in the store. Entries are published in causal order, following
+/// their backlinks and the operations referred to in `previous`. Entries which are already
+/// contained in the store are skipped.
+///
+/// Returns the hashes of all newly published entries in the order they were published. Importing
+/// stops at the first entry which was rejected, entries published up to this point are kept.
+pub async fn import_archive<S>(
+    store: &S,
+    archive: &Archive,
+    schemas: &[Schema],
+) -> Result<Vec<Hash>, ArchiveError>
+where
+    S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore,
+{
+    // Decode all entries and operations, duplicates are ignored.
+    let mut archived_entries: Vec<ArchivedEntry> = Vec::new();
+    let mut index_by_hash: HashMap<Hash, usize> = HashMap::new();
+
+    for (encoded_entry, encoded_operation) in archive.entries() {
+        let hash = encoded_entry.hash();
+
+        if index_by_hash.contains_key(&hash) {
+            continue;
+        }
+
+        index_by_hash.insert(hash.clone(), archived_entries.len());
+        archived_entries.push(ArchivedEntry {
+            hash,
+            entry: decode_entry(encoded_entry)?,
+            encoded_entry: encoded_entry.to_owned(),
+            operation: decode_operation(encoded_operation)?,
+            encoded_operation: encoded_operation.to_owned(),
+        });
+    }
+
+    // Every entry depends on it's backlink and on the entries carrying the operations it's own
+    // operation refers to. Dependencies are identified by hash, so there can't be any cycles.
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); archived_entries.len()];
+    let mut no_of_dependencies: Vec<usize> = vec![0; archived_entries.len()];
+
+    for (index, archived_entry) in archived_entries.iter().enumerate() {
+        let mut dependencies: Vec<usize> = archived_entry
+            .entry
+            .backlink()
+            .into_iter()
+            .chain(
+                archived_entry
+                    .operation
+                    .previous()
+                    .into_iter()
+                    .flat_map(|previous| previous.iter().map(|id| id.as_hash())),
+            )
+            .filter_map(|hash| index_by_hash.get(hash).copied())
+            .collect();
+
+        dependencies.sort_unstable();
+        dependencies.dedup();
+
+        for dependency in dependencies {
+            dependents[dependency].push(index);
+            no_of_dependencies[index] += 1;
+        }
+    }
+
+    // Publish entries as soon as all their dependencies were published, preferring the order
+    // they have in the archive.
+    let mut ready: BTreeSet<usize> = no_of_dependencies
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count == 0)
+        .map(|(index, _)| index)
+        .collect();
+    let mut published = Vec::new();
+
+    while let Some(index) = ready.iter().next().copied() {
+        ready.remove(&index);
+
+        let archived_entry = &archived_entries[index];
+
+        if store.get_entry(&archived_entry.hash).await?.is_none() {
+            let schema_id = archived_entry.operation.schema_id();
+            let schema = schemas
+                .iter()
+                .find(|schema| schema.id() == schema_id)
+                .ok_or_else(|| {
+                    ArchiveError::UnknownSchema(schema_id.to_owned(), archived_entry.hash.clone())
+                })?;
+
+            publish(
+                store,
+                schema,
+                &archived_entry.encoded_entry,
+                &archived_entry.operation,
+                &archived_entry.encoded_operation,
+            )
+            .await
+            .map_err(|err| {
+                ArchiveError::ImportFailed(archived_entry.hash.clone(), Box::new(err))
+            })?;
+
+            published.push(archived_entry.hash.clone());
+        }
+
+        for dependent in &dependents[index] {
+            no_of_dependencies[*dependent] -= 1;
+
+            if no_of_dependencies[*dependent] == 0 {
+                ready.insert(*dependent);
+            }
+        }
+    }
+
+    Ok(published)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::api::ArchiveError;
+    use crate::document::DocumentViewId;
+    use crate::entry::traits::{AsEncodedEntry, AsEntry};
+    use crate::entry::{LogId, SeqNum};
+    use crate::identity::KeyPair;
+    use crate::operation::traits::WithPublicKey;
+    use crate::operation::{OperationAction, OperationBuilder, OperationId};
+    use crate::schema::SchemaId;
+    use crate::storage_provider::traits::{EntryStore, LogStore, OperationStore};
+    use crate::test_utils::constants::test_fields;
+    use crate::test_utils::fixtures::populate_store_config;
+    use crate::test_utils::memory_store::helpers::{
+        populate_store, send_to_store, PopulateStoreConfig,
+    };
+    use crate::test_utils::memory_store::MemoryStore;
+    use crate::WithId;
+
+    use super::{export_archive, import_archive, Archive, ArchiveSelection};
+
+    /// Returns all entries of a store sorted by their hash.
+    fn sorted_entries(store: &MemoryStore) -> Vec<String> {
+        let mut entries: Vec<String> = store
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| format!("{}:{:?}", entry.hash(), entry.payload))
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn exports_and_imports_store(
+        #[from(populate_store_config)]
+        #[with(4, 2, 2)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, document_ids) = populate_store(&store, &config).await;
+        let other_key_pair = KeyPair::new();
+
+        // Another public key updates a document, this entry depends on an entry in another log.
+        let latest_entry = store
+            .get_latest_entry(&key_pairs[0].public_key(), &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let operation = OperationBuilder::new(config.schema.id())
+            .action(OperationAction::Update)
+            .previous(&DocumentViewId::new(&[latest_entry.hash().into()]))
+            .fields(&test_fields())
+            .build()
+            .unwrap();
+        send_to_store(&store, &operation, &config.schema, &other_key_pair)
+            .await
+            .unwrap();
+
+        let selection = ArchiveSelection::new()
+            .public_key(&key_pairs[0].public_key())
+            .public_key(&key_pairs[1].public_key())
+            .public_key(&other_key_pair.public_key());
+        let archive = export_archive(&store, &selection).await.unwrap();
+        assert_eq!(archive.len(), 4 * 2 * 2 + 1);
+
+        // The archive survives encoding, replaying it in reverse order still succeeds.
+        let archive = Archive::from_bytes(&archive.to_bytes()).unwrap();
+        let mut entries = archive.entries().to_vec();
+        entries.reverse();
+        let archive = Archive::new(entries);
+
+        let target_store = MemoryStore::default();
+        let published = import_archive(
+            &target_store,
+            &archive,
+            std::slice::from_ref(&config.schema),
+        )
+        .await
+        .unwrap();
+        assert_eq!(published.len(), archive.len());
+
+        assert_eq!(sorted_entries(&store), sorted_entries(&target_store));
+        for document_id in &document_ids {
+            let operations = store
+                .get_operations_by_document_id(document_id)
+                .await
+                .unwrap();
+            let imported_operations = target_store
+                .get_operations_by_document_id(document_id)
+                .await
+                .unwrap();
+            assert_eq!(operations.len(), imported_operations.len());
+
+            for operation in operations {
+                let public_key = operation.public_key();
+                assert_eq!(
+                    store.get_log_id(public_key, document_id).await.unwrap(),
+                    target_store
+                        .get_log_id(public_key, document_id)
+                        .await
+                        .unwrap()
+                );
+                assert!(target_store
+                    .get_operation(WithId::<OperationId>::id(&operation))
+                    .await
+                    .unwrap()
+                    .is_some());
+            }
+        }
+
+        // Importing again doesn't publish anything.
+        assert!(import_archive(
+            &target_store,
+            &archive,
+            std::slice::from_ref(&config.schema)
+        )
+        .await
+        .unwrap()
+        .is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn exports_selected_logs(
+        #[from(populate_store_config)]
+        #[with(3, 2, 2)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        let selection = ArchiveSelection::new().log(&public_key, &LogId::new(1));
+        let archive = export_archive(&store, &selection).await.unwrap();
+        assert_eq!(archive.len(), 3);
+
+        for (seq_num, (encoded_entry, _)) in archive.entries().iter().enumerate() {
+            let entry = store
+                .get_entry(&encoded_entry.hash())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(entry.log_id(), &LogId::new(1));
+            assert_eq!(entry.seq_num(), &SeqNum::new(seq_num as u64 + 1).unwrap());
+        }
+
+        let selection = ArchiveSelection::new().schema_id(config.schema.id());
+        let archive = export_archive(&store, &selection).await.unwrap();
+        assert_eq!(archive.len(), 3 * 2 * 2);
+
+        let selection = ArchiveSelection::new().schema_id(&SchemaId::SchemaDefinition(1));
+        assert!(export_archive(&store, &selection).await.unwrap().is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn import_errors(
+        #[from(populate_store_config)]
+        #[with(3, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let selection = ArchiveSelection::new().public_key(&key_pairs[0].public_key());
+        let archive = export_archive(&store, &selection).await.unwrap();
+
+        // The schema of the archived operations is required for validating them.
+        let result = import_archive(&MemoryStore::default(), &archive, &[]).await;
+        assert!(matches!(result, Err(ArchiveError::UnknownSchema(_, _))));
+
+        // Entries can't be imported without their backlinks.
+        let archive = Archive::new(archive.entries()[1..].to_vec());
+        let result = import_archive(&MemoryStore::default(), &archive, &[config.schema]).await;
+        assert!(matches!(result, Err(ArchiveError::ImportFailed(_, _))));
+
+        assert!(matches!(
+            Archive::from_bytes(&[1, 2, 3]),
+            Err(ArchiveError::InvalidArchive(_))
+        ));
+
+        let mut archive = Archive::new(Vec::new());
+        archive.version = 2;
+        assert!(matches!(
+            Archive::from_bytes(&archive.to_bytes()),
+            Err(ArchiveError::UnsupportedVersion(2))
+        ));
+    }
+}
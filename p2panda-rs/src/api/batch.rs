@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::api::helpers::get_skiplink_for_entry;
+use crate::api::overlay::{PendingEntry, StoreOverlay};
+use crate::api::publish::{
+    insert_log_entry_and_operation, notify_subscribers, validate_entry, ValidatedEntry,
+};
+use crate::api::{DomainError, PublishConfig};
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{EncodedEntry, LogId, SeqNum};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::decode::decode_operation;
+use crate::operation::plain::PlainOperation;
+use crate::operation::traits::Schematic;
+use crate::operation::EncodedOperation;
+use crate::schema::Schema;
+use crate::storage_provider::traits::{
+    EntryStore, LogStore, OperationStore, SubscriptionStore, TransactionStore,
+};
+
+/// Backlink, skiplink, sequence number and log id for constructing the next entry of a log.
+type NextArgs = (Option<Hash>, Option<Hash>, SeqNum, LogId);
+
+/// Outcome of publishing a batch of entries with `publish_batch`.
+#[derive(Debug)]
+pub struct PublishBatchResult {
+    /// Result for every entry of the batch in the order they were passed, containing the hash of
+    /// the published entry or the reason it was rejected.
+    pub results: Vec<Result<Hash, DomainError>>,
+
+    /// Arguments for the next entry of every log which accepted entries of the batch, in the
+    /// order the logs first appear in the batch.
+    pub next_args: Vec<(PublicKey, NextArgs)>,
+}
+
+/// Persist an ordered batch of entries and operations, possibly for several logs, to storage.
+///
+/// Every entry is validated like in `publish_with_config`, against the store and all entries
+/// preceding it in the batch. Entries need to be ordered so that their backlinks and the
+/// operations referred to in `previous` are either stored already or part of the batch before
+/// them. The schema of every operation is looked up in the passed schemas.
+///
+/// An invalid entry doesn't stop the batch, it is reported in the per-entry results while all
+/// other entries are still published. Entries depending on a rejected entry are rejected as well.
+///
+/// The whole batch is validated before anything is written, the transaction persisting all
+/// accepted entries together is only started afterwards. Subscribers are notified about them once
+/// it was committed. Storage errors abort the batch: if reading from the store fails during
+/// validation or a write fails, for example because another writer stored a conflicting entry in
+/// the meantime, nothing is persisted and the error is returned.
+pub async fn publish_batch<S>(
+    store: &S,
+    config: &PublishConfig,
+    schemas: &[Schema],
+    batch: &[(EncodedEntry, EncodedOperation)],
+) -> Result<PublishBatchResult, DomainError>
+where
+    S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore + Sync,
+{
+    // Following entries of the batch are validated against the accepted ones, the overlay lets
+    // them appear as stored without writing them yet.
+    let mut overlay = StoreOverlay::new(store);
+    let mut results = Vec::with_capacity(batch.len());
+
+    for (encoded_entry, encoded_operation) in batch {
+        match validate_batch_entry(&overlay, config, schemas, encoded_entry, encoded_operation)
+            .await
+        {
+            Ok(validated) => {
+                results.push(Ok(encoded_entry.hash()));
+                overlay.push(PendingEntry {
+                    validated,
+                    encoded_entry,
+                    encoded_operation,
+                });
+            }
+            Err(err) if err.is_storage_error() => return Err(err),
+            Err(err) => results.push(Err(err)),
+        }
+    }
+
+    let published = overlay.into_pending();
+    let transaction = store.begin_transaction().await?;
+
+    for pending in &published {
+        let result = insert_log_entry_and_operation(
+            &transaction,
+            &pending.validated.entry,
+            pending.encoded_entry,
+            pending.encoded_operation,
+            &pending.validated.operation,
+            &pending.validated.operation_id,
+            &pending.validated.document_id,
+        )
+        .await;
+
+        if let Err(err) = result {
+            store.rollback_transaction(transaction).await?;
+            return Err(err);
+        }
+    }
+
+    store.commit_transaction(transaction).await?;
+
+    for pending in &published {
+        notify_subscribers(store, &pending.validated, pending.encoded_entry);
+    }
+
+    // Only the last published entry of every log is relevant for it's next args.
+    let mut last_entries: Vec<(PublicKey, LogId, Hash, SeqNum)> = Vec::new();
+
+    for pending in &published {
+        let public_key = pending.validated.entry.public_key();
+        let log_id = pending.validated.entry.log_id();
+        let last_entry = (
+            public_key.to_owned(),
+            log_id.to_owned(),
+            pending.encoded_entry.hash(),
+            pending.validated.next_seq_num,
+        );
+
+        match last_entries
+            .iter_mut()
+            .find(|(other_public_key, other_log_id, _, _)| {
+                other_public_key == public_key && other_log_id == log_id
+            }) {
+            Some(existing) => *existing = last_entry,
+            None => last_entries.push(last_entry),
+        }
+    }
+
+    let mut next_args = Vec::with_capacity(last_entries.len());
+
+    for (public_key, log_id, backlink, next_seq_num) in last_entries {
+        let skiplink = get_skiplink_for_entry(store, &next_seq_num, &log_id, &public_key).await?;
+        next_args.push((public_key, (Some(backlink), skiplink, next_seq_num, log_id)));
+    }
+
+    Ok(PublishBatchResult { results, next_args })
+}
+
+/// Decode the operation of a batch entry, look up it's schema and validate it.
+async fn validate_batch_entry<S: EntryStore + OperationStore + LogStore>(
+    store: &S,
+    config: &PublishConfig,
+    schemas: &[Schema],
+    encoded_entry: &EncodedEntry,
+    encoded_operation: &EncodedOperation,
+) -> Result<ValidatedEntry, DomainError> {
+    let plain_operation: PlainOperation = decode_operation(encoded_operation)?;
+
+    let schema = schemas
+        .iter()
+        .find(|schema| schema.id() == plain_operation.schema_id())
+        .ok_or_else(|| DomainError::UnknownSchema(plain_operation.schema_id().to_owned()))?;
+
+    validate_entry(
+        store,
+        config,
+        schema,
+        encoded_entry,
+        &plain_operation,
+        encoded_operation,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+    use futures::StreamExt;
+    use rstest::rstest;
+
+    use crate::api::{next_args, DomainError, PublishConfig};
+    use crate::document::DocumentViewId;
+    use crate::entry::decode::decode_entry;
+    use crate::entry::traits::{AsEncodedEntry, AsEntry};
+    use crate::entry::{LogId, SeqNum};
+    use crate::hash::Hash;
+    use crate::identity::KeyPair;
+    use crate::schema::{FieldType, Schema, SchemaId, SchemaName};
+    use crate::storage_provider::events::{EventFilter, StoreEvent};
+    use crate::storage_provider::traits::{EntryStore, SubscriptionStore};
+    use crate::test_utils::fixtures::{
+        log_entries, log_entry, random_document_view_id, random_hash, schema,
+    };
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::publish_batch;
+
+    #[rstest]
+    #[tokio::test]
+    async fn publishes_entries_of_several_logs(schema: Schema) {
+        let store = MemoryStore::default();
+        let panda = KeyPair::new();
+        let penguin = KeyPair::new();
+
+        let panda_log = log_entries(&schema, &panda, 3);
+        let penguin_log = log_entries(&schema, &penguin, 2);
+
+        let batch = vec![
+            panda_log[0].clone(),
+            penguin_log[0].clone(),
+            panda_log[1].clone(),
+            penguin_log[1].clone(),
+            panda_log[2].clone(),
+        ];
+        let result = publish_batch(&store, &PublishConfig::new(), &[schema], &batch)
+            .await
+            .unwrap();
+
+        let hashes: Vec<Hash> = result
+            .results
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect();
+        let expected_hashes: Vec<Hash> = batch.iter().map(|(entry, _)| entry.hash()).collect();
+        assert_eq!(hashes, expected_hashes);
+
+        // Next args match the ones computed from the store after publishing.
+        assert_eq!(result.next_args.len(), 2);
+        let (public_key, args) = &result.next_args[0];
+        assert_eq!(public_key, &panda.public_key());
+        let view_id = DocumentViewId::from(batch[4].0.hash());
+        assert_eq!(
+            args,
+            &next_args(&store, &panda.public_key(), Some(&view_id))
+                .await
+                .unwrap()
+        );
+        assert_eq!(args.0, Some(batch[4].0.hash()));
+        assert_eq!(args.2, SeqNum::new(4).unwrap());
+
+        let (public_key, args) = &result.next_args[1];
+        assert_eq!(public_key, &penguin.public_key());
+        let view_id = DocumentViewId::from(batch[3].0.hash());
+        assert_eq!(
+            args,
+            &next_args(&store, &penguin.public_key(), Some(&view_id))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn publishes_entries_with_skiplinks(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+
+        // Entries from sequence number 4 on point at an earlier entry with their skiplink.
+        let batch = log_entries(&schema, &key_pair, 10);
+        let result = publish_batch(&store, &PublishConfig::new(), &[schema], &batch)
+            .await
+            .unwrap();
+        assert!(result.results.iter().all(|result| result.is_ok()));
+
+        let latest_entry = store
+            .get_latest_entry(&key_pair.public_key(), &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest_entry.hash(), batch[9].0.hash());
+        assert_eq!(latest_entry.seq_num(), &SeqNum::new(10).unwrap());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn rejects_invalid_entries_and_their_dependents(schema: Schema) {
+        let store = MemoryStore::default();
+        let panda = KeyPair::new();
+        let penguin = KeyPair::new();
+
+        let panda_log = log_entries(&schema, &panda, 2);
+
+        // Penguin updates a document which doesn't exist and then builds on top of it.
+        let unknown_previous = random_hash();
+        let penguin_invalid = log_entry(&schema, &penguin, &[], Some(&unknown_previous));
+        let penguin_dependent = log_entry(
+            &schema,
+            &penguin,
+            &[penguin_invalid.0.hash()],
+            Some(&penguin_invalid.0.hash()),
+        );
+
+        let batch = vec![
+            panda_log[0].clone(),
+            penguin_invalid,
+            penguin_dependent,
+            panda_log[1].clone(),
+        ];
+        let result = publish_batch(&store, &PublishConfig::new(), &[schema], &batch)
+            .await
+            .unwrap();
+
+        assert!(result.results[0].is_ok());
+        assert!(result.results[1].is_err());
+        assert!(result.results[2].is_err());
+        assert!(result.results[3].is_ok());
+
+        // Only the logs of accepted entries are returned and stored.
+        assert_eq!(result.next_args.len(), 1);
+        assert_eq!(result.next_args[0].0, panda.public_key());
+        assert!(store
+            .get_latest_entry(&penguin.public_key(), &LogId::default())
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            store
+                .get_latest_entry(&panda.public_key(), &LogId::default())
+                .await
+                .unwrap()
+                .unwrap()
+                .hash(),
+            batch[3].0.hash()
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn rejects_unknown_schemas(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+
+        let schema_name = SchemaName::new("unknown").unwrap();
+        let unknown_schema = Schema::new(
+            &SchemaId::new_application(&schema_name, &random_document_view_id()),
+            "Schema which is not passed to publish_batch",
+            &[("name", FieldType::String)],
+        )
+        .unwrap();
+        let batch = vec![log_entry(&unknown_schema, &key_pair, &[], None)];
+
+        let result = publish_batch(&store, &PublishConfig::new(), &[schema], &batch)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            &result.results[0],
+            Err(DomainError::UnknownSchema(schema_id)) if schema_id == unknown_schema.id()
+        ));
+        assert!(result.next_args.is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn notifies_subscribers_about_accepted_entries(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+        let public_key = key_pair.public_key();
+
+        let create = log_entry(&schema, &key_pair, &[], None);
+        let invalid = log_entry(&schema, &key_pair, &[], None);
+        let batch = vec![create, invalid];
+
+        let mut events = store.subscribe(EventFilter::new().public_key(&public_key));
+
+        let result = publish_batch(&store, &PublishConfig::new(), &[schema], &batch)
+            .await
+            .unwrap();
+        assert!(result.results[1].is_err());
+
+        assert!(matches!(
+            events.next().await,
+            Some(StoreEvent::EntryInserted { hash, .. }) if hash == batch[0].0.hash()
+        ));
+        assert!(matches!(
+            events.next().await,
+            Some(StoreEvent::OperationInserted { .. })
+        ));
+        assert!(events.next().now_or_never().is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn aborts_batch_on_storage_errors(schema: Schema) {
+        let store = MemoryStore::default();
+        let panda = KeyPair::new();
+        let penguin = KeyPair::new();
+
+        let panda_create = log_entry(&schema, &panda, &[], None);
+        let penguin_create = log_entry(&schema, &penguin, &[], None);
+        let batch = vec![panda_create, penguin_create.clone()];
+
+        // Block committing transactions to write to the store after the batch was validated.
+        let commit_lock = store.commit_lock.clone();
+        let guard = commit_lock.lock().await;
+        let config = PublishConfig::new();
+        let schemas = [schema];
+        let mut publishing = Box::pin(publish_batch(&store, &config, &schemas, &batch));
+        assert!((&mut publishing).now_or_never().is_none());
+
+        // Another writer stores a conflicting entry in the meantime.
+        let (encoded_entry, encoded_operation) = penguin_create;
+        store
+            .insert_entry(
+                &decode_entry(&encoded_entry).unwrap(),
+                &encoded_entry,
+                Some(&encoded_operation),
+            )
+            .await
+            .unwrap();
+        drop(guard);
+
+        assert!(matches!(
+            publishing.await,
+            Err(DomainError::TransactionStoreError(_))
+        ));
+
+        // None of the entries of the batch were persisted.
+        assert!(store
+            .get_latest_entry(&panda.public_key(), &LogId::default())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
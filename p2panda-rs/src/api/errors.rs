@@ -1,12 +1,17 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::api::QuotaLimit;
 use crate::document::DocumentId;
 use crate::entry::error::DecodeEntryError;
+use crate::hash::Hash;
 use crate::identity::PublicKey;
-use crate::operation::error::ValidateOperationError;
+use crate::operation::error::{DecodeOperationError, ValidateOperationError};
 use crate::operation::OperationId;
 use crate::schema::SchemaId;
-use crate::storage_provider::error::{EntryStorageError, LogStorageError, OperationStorageError};
+use crate::storage_provider::error::{
+    DocumentStorageError, EntryStorageError, LogStorageError, OperationStorageError,
+    TransactionError,
+};
 
 /// Error type used in the validation module.
 #[derive(thiserror::Error, Debug)]
@@ -19,6 +24,12 @@ pub enum ValidationError {
     #[error("Expected skiplink entry not found in store: public key {0}, log id {1}, seq num {2}")]
     ExpectedSkiplinkNotFound(String, u64, u64),
 
+    /// The expected skiplink entry was removed from the store when pruning the log.
+    #[error(
+        "Expected skiplink entry was pruned from store: public key {0}, log id {1}, seq num {2}"
+    )]
+    ExpectedSkiplinkPruned(String, u64, u64),
+
     /// The claimed log id didn't match the expected for a given public key and document id.
     #[error(
         "Entry's claimed log id of {0} does not match existing log id of {1} for given public key and document id"
@@ -89,6 +100,28 @@ pub enum DomainError {
     #[error("Expected log id {0} not found when calculating next args")]
     ExpectedLogIdNotFound(u64),
 
+    /// Publishing the entry would exceed a limit of the quota configured for the store, contains
+    /// the configured limit and the value publishing the entry would have resulted in.
+    #[error("Quota exceeded: {0} are limited to {1} but would be {2}")]
+    QuotaExceeded(QuotaLimit, u64, u64),
+
+    /// The write access policy of the store doesn't allow the public key to publish the
+    /// operation.
+    #[error("Public key {0} is not allowed to {1} document {2}")]
+    WriteAccessDenied(PublicKey, String, DocumentId),
+
+    /// The pending queue of `IngestQueue` can't hold the entry waiting for it's dependencies.
+    #[error("Pending queue is full, can not hold entry {0}")]
+    PendingQueueFull(Hash),
+
+    /// The same document was requested several times from `next_args_many`.
+    #[error("Document {0} was requested more than once")]
+    DuplicateDocument(DocumentId),
+
+    /// The schema of an operation was not passed to `publish_batch`.
+    #[error("Schema {0} of operation is not known")]
+    UnknownSchema(SchemaId),
+
     /// Validation errors.
     #[error(transparent)]
     ValidationError(#[from] ValidationError),
@@ -105,11 +138,86 @@ pub enum DomainError {
     #[error(transparent)]
     OperationStoreError(#[from] OperationStorageError),
 
+    /// Error coming from the document store.
+    #[error(transparent)]
+    DocumentStoreError(#[from] DocumentStorageError),
+
+    /// Error coming from the transaction store.
+    #[error(transparent)]
+    TransactionStoreError(#[from] TransactionError),
+
     /// Error occurring when decoding entries.
     #[error(transparent)]
     DecodeEntryError(#[from] DecodeEntryError),
 
+    /// Error occurring when decoding operations.
+    #[error(transparent)]
+    DecodeOperationError(#[from] DecodeOperationError),
+
     /// Error occurring when validating operations.
     #[error(transparent)]
     ValidateOperationError(#[from] ValidateOperationError),
 }
+
+impl DomainError {
+    /// Returns true if the error was caused by reading from or writing to the store instead of an
+    /// invalid entry or operation.
+    pub(crate) fn is_storage_error(&self) -> bool {
+        matches!(
+            self,
+            DomainError::LogStoreError(_)
+                | DomainError::EntryStoreError(_)
+                | DomainError::OperationStoreError(_)
+                | DomainError::DocumentStoreError(_)
+                | DomainError::TransactionStoreError(_)
+        )
+    }
+}
+
+/// Error type used when constructing write access policies.
+#[derive(thiserror::Error, Debug)]
+pub enum AccessPolicyError {
+    /// The schema passed to `AllowListField` has no string field with this name.
+    #[error("Schema {0} has no string field {1} to read an allow list from")]
+    InvalidAllowListField(SchemaId, String),
+}
+
+/// Error type used when exporting and importing archives.
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    /// The archive bytes could not be decoded.
+    #[error("Could not decode archive: {0}")]
+    InvalidArchive(String),
+
+    /// The archive was written in a format version this implementation doesn't support.
+    #[error("Unsupported archive version {0}")]
+    UnsupportedVersion(u64),
+
+    /// The schema of an archived operation was not passed to the importer.
+    #[error("Schema {0} of operation in entry {1} is not known")]
+    UnknownSchema(SchemaId, Hash),
+
+    /// An archived entry was rejected when publishing it to the target store.
+    #[error("Could not import entry {0}: {1}")]
+    ImportFailed(Hash, Box<DomainError>),
+
+    /// Error occurring when decoding archived entries.
+    #[error(transparent)]
+    DecodeEntryError(#[from] DecodeEntryError),
+
+    /// Error occurring when decoding archived operations.
+    #[error(transparent)]
+    DecodeOperationError(#[from] DecodeOperationError),
+
+    /// Error coming from the log store.
+    #[error(transparent)]
+    LogStoreError(#[from] LogStorageError),
+
+    /// Error coming from the entry store.
+    #[error(transparent)]
+    EntryStoreError(#[from] EntryStorageError),
+
+    /// Error coming from the operation store.
+    #[error(transparent)]
+    OperationStoreError(#[from] OperationStorageError),
+}
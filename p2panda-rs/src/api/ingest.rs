@@ -0,0 +1,781 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use crate::api::{publish_with_config, DomainError, PublishConfig};
+use crate::entry::decode::decode_entry;
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{EncodedEntry, LogId, SeqNum};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::decode::decode_operation;
+use crate::operation::plain::PlainOperation;
+use crate::operation::traits::Actionable;
+use crate::operation::{EncodedOperation, OperationId};
+use crate::schema::Schema;
+use crate::storage_provider::traits::{
+    EntryStore, LogStore, OperationStore, SubscriptionStore, TransactionStore,
+};
+
+/// Default maximum number of entries held in the pending queue.
+const DEFAULT_MAX_PENDING_ENTRIES: usize = 1000;
+
+/// Default maximum size of all entries and operations held in the pending queue, in bytes.
+const DEFAULT_MAX_PENDING_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Default time after which pending entries are dropped.
+const DEFAULT_MAX_PENDING_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Limits of the pending queue and checks performed when publishing ingested entries.
+#[derive(Clone, Debug)]
+pub struct IngestConfig {
+    max_pending_entries: usize,
+    max_pending_bytes: u64,
+    max_pending_age: Duration,
+    publish: PublishConfig,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            max_pending_entries: DEFAULT_MAX_PENDING_ENTRIES,
+            max_pending_bytes: DEFAULT_MAX_PENDING_BYTES,
+            max_pending_age: DEFAULT_MAX_PENDING_AGE,
+            publish: PublishConfig::default(),
+        }
+    }
+}
+
+impl IngestConfig {
+    /// Returns a config with the default limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of entries waiting for their dependencies.
+    pub fn max_pending_entries(mut self, max: usize) -> Self {
+        self.max_pending_entries = max;
+        self
+    }
+
+    /// Limit the total size of all entries and operations waiting for their dependencies, in
+    /// bytes.
+    pub fn max_pending_bytes(mut self, max: u64) -> Self {
+        self.max_pending_bytes = max;
+        self
+    }
+
+    /// Drop entries which are still waiting for their dependencies after this duration.
+    pub fn max_pending_age(mut self, max: Duration) -> Self {
+        self.max_pending_age = max;
+        self
+    }
+
+    /// Publish entries with the checks enabled in the passed config.
+    pub fn publish_config(mut self, config: PublishConfig) -> Self {
+        self.publish = config;
+        self
+    }
+}
+
+/// Data an ingested entry is waiting for before it can be published.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Dependency {
+    /// The backlink of the entry, identified by it's public key, log id and sequence number.
+    Entry(PublicKey, LogId, SeqNum),
+
+    /// An operation referred to in the `previous` field of the entry's operation.
+    Operation(OperationId),
+}
+
+/// Entries published from the pending queue of `IngestQueue` after their dependencies were
+/// stored.
+#[derive(Debug, Default)]
+pub struct PublishedEntries {
+    /// Hashes of all published entries, in the order they were published.
+    pub published: Vec<Hash>,
+
+    /// Hashes of pending entries which failed validation once their dependencies were stored,
+    /// together with the reason. These entries were removed from the pending queue.
+    pub rejected: Vec<(Hash, DomainError)>,
+}
+
+/// Outcome of ingesting an entry with `IngestQueue`.
+#[derive(Debug)]
+pub enum IngestOutcome {
+    /// The entry was published, followed by all pending entries which could be published after
+    /// it. The ingested entry is the first published entry.
+    Published(PublishedEntries),
+
+    /// The entry is waiting in the pending queue for a missing dependency.
+    Pending(Dependency),
+}
+
+/// Entry and operation waiting in the pending queue.
+#[derive(Debug)]
+struct PendingEntry {
+    schema: Schema,
+    encoded_entry: EncodedEntry,
+    encoded_operation: EncodedOperation,
+    dependency: Dependency,
+    received_at: Instant,
+}
+
+impl PendingEntry {
+    /// Size of the entry and operation in bytes.
+    fn size(&self) -> u64 {
+        self.encoded_entry.size() + self.encoded_operation.size()
+    }
+}
+
+/// Ingests entries which might arrive out of order, for example during replication.
+///
+/// `publish` rejects entries whose backlink or whose `previous` operations are not stored yet.
+/// Instead of rejecting them, the queue parks such entries until the missing data was ingested
+/// and then publishes them automatically. All other validation errors are returned right away.
+///
+/// Pending entries are retried automatically when their dependency is ingested through the same
+/// queue. Entries published to the store in other ways, for example with `publish` or
+/// `publish_batch`, don't trigger a retry, call `retry_pending` afterwards or whenever a
+/// subscription of the store reports inserted entries.
+///
+/// The pending queue is bounded by the number and total size of the entries it holds, entries
+/// exceeding these limits are rejected. Entries which are still pending after the configured age
+/// are dropped.
+#[derive(Debug, Default)]
+pub struct IngestQueue {
+    config: IngestConfig,
+    pending: VecDeque<PendingEntry>,
+    pending_bytes: u64,
+}
+
+impl IngestQueue {
+    /// Returns an empty queue with the passed config.
+    pub fn new(config: IngestConfig) -> Self {
+        Self {
+            config,
+            pending: VecDeque::new(),
+            pending_bytes: 0,
+        }
+    }
+
+    /// Publish an entry and operation or park them in the pending queue if their dependencies
+    /// are not stored yet.
+    ///
+    /// When the entry was published, all pending entries which were waiting for it are published
+    /// as well. Pending entries which fail validation at that point are dropped and returned in
+    /// the outcome. If the store fails while publishing them, the error is returned and they stay
+    /// in the pending queue, even though the ingested entry itself was published.
+    pub async fn ingest<S>(
+        &mut self,
+        store: &S,
+        schema: &Schema,
+        encoded_entry: &EncodedEntry,
+        encoded_operation: &EncodedOperation,
+    ) -> Result<IngestOutcome, DomainError>
+    where
+        S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore,
+    {
+        self.expire();
+
+        if let Some(pending) = self
+            .pending
+            .iter()
+            .find(|pending| pending.encoded_entry.hash() == encoded_entry.hash())
+        {
+            return Ok(IngestOutcome::Pending(pending.dependency.clone()));
+        }
+
+        let missing = self
+            .try_publish(store, schema, encoded_entry, encoded_operation)
+            .await?;
+
+        match missing {
+            None => {
+                let mut outcome = PublishedEntries {
+                    published: vec![encoded_entry.hash()],
+                    rejected: Vec::new(),
+                };
+
+                let entry = decode_entry(encoded_entry)?;
+                let dependents = self.take_dependents(&entry, &encoded_entry.hash());
+                self.publish_pending(store, dependents, &mut outcome)
+                    .await?;
+
+                Ok(IngestOutcome::Published(outcome))
+            }
+            Some(dependency) => {
+                let pending = PendingEntry {
+                    schema: schema.to_owned(),
+                    encoded_entry: encoded_entry.to_owned(),
+                    encoded_operation: encoded_operation.to_owned(),
+                    dependency: dependency.clone(),
+                    received_at: Instant::now(),
+                };
+
+                if self.pending.len() >= self.config.max_pending_entries
+                    || self.pending_bytes + pending.size() > self.config.max_pending_bytes
+                {
+                    return Err(DomainError::PendingQueueFull(encoded_entry.hash()));
+                }
+
+                self.push_pending(pending);
+                Ok(IngestOutcome::Pending(dependency))
+            }
+        }
+    }
+
+    /// Drop all pending entries which are waiting longer than the configured age, returns their
+    /// hashes.
+    ///
+    /// Expired entries are also dropped on every call to `ingest`.
+    pub fn expire(&mut self) -> Vec<Hash> {
+        let max_age = self.config.max_pending_age;
+        let (expired, pending): (VecDeque<PendingEntry>, VecDeque<PendingEntry>) = self
+            .pending
+            .drain(..)
+            .partition(|pending| pending.received_at.elapsed() > max_age);
+
+        self.pending = pending;
+        self.pending_bytes = self.pending.iter().map(PendingEntry::size).sum();
+
+        expired
+            .iter()
+            .map(|pending| {
+                debug!(
+                    "Dropping expired entry {} waiting for {:?}",
+                    pending.encoded_entry.hash(),
+                    pending.dependency
+                );
+                pending.encoded_entry.hash()
+            })
+            .collect()
+    }
+
+    /// Publish all pending entries whose dependency was stored in the meantime, followed by all
+    /// entries waiting for them in turn.
+    ///
+    /// Use this after entries were published to the store without going through the queue.
+    /// Pending entries which fail validation are dropped and returned in the outcome.
+    pub async fn retry_pending<S>(&mut self, store: &S) -> Result<PublishedEntries, DomainError>
+    where
+        S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore,
+    {
+        self.expire();
+
+        let mut ready_hashes = Vec::new();
+        for pending in &self.pending {
+            if is_stored(store, &pending.dependency).await? {
+                ready_hashes.push(pending.encoded_entry.hash());
+            }
+        }
+
+        let ready =
+            self.take_pending(|pending| ready_hashes.contains(&pending.encoded_entry.hash()));
+        let mut outcome = PublishedEntries::default();
+        self.publish_pending(store, ready, &mut outcome).await?;
+
+        Ok(outcome)
+    }
+
+    /// Returns true if the entry is waiting in the pending queue.
+    pub fn is_pending(&self, hash: &Hash) -> bool {
+        self.pending
+            .iter()
+            .any(|pending| &pending.encoded_entry.hash() == hash)
+    }
+
+    /// Returns the dependencies pending entries are waiting for, in the order they were parked.
+    pub fn missing_dependencies(&self) -> Vec<Dependency> {
+        let mut dependencies = Vec::new();
+
+        for pending in &self.pending {
+            if !dependencies.contains(&pending.dependency) {
+                dependencies.push(pending.dependency.clone());
+            }
+        }
+
+        dependencies
+    }
+
+    /// Returns the number of pending entries.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns true if no entries are pending.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    fn push_pending(&mut self, pending: PendingEntry) {
+        self.pending_bytes += pending.size();
+        self.pending.push_back(pending);
+    }
+
+    /// Remove and return all pending entries waiting for the passed entry or it's operation.
+    fn take_dependents(&mut self, entry: &impl AsEntry, hash: &Hash) -> Vec<PendingEntry> {
+        let entry_dependency = Dependency::Entry(
+            entry.public_key().to_owned(),
+            entry.log_id().to_owned(),
+            entry.seq_num().to_owned(),
+        );
+        let operation_dependency = Dependency::Operation(hash.to_owned().into());
+
+        self.take_pending(|pending| {
+            pending.dependency == entry_dependency || pending.dependency == operation_dependency
+        })
+    }
+
+    /// Remove and return all pending entries matching the filter.
+    fn take_pending(&mut self, filter: impl Fn(&PendingEntry) -> bool) -> Vec<PendingEntry> {
+        let (taken, pending): (VecDeque<PendingEntry>, VecDeque<PendingEntry>) =
+            self.pending.drain(..).partition(|pending| filter(pending));
+
+        self.pending = pending;
+        self.pending_bytes = self.pending.iter().map(PendingEntry::size).sum();
+        taken.into()
+    }
+
+    /// Publish the passed pending entries and all entries which were waiting for those in turn.
+    ///
+    /// Entries which are still missing a dependency are parked again, entries failing validation
+    /// are added to the rejected entries of the outcome. Storage errors stop publishing, the
+    /// remaining entries are parked again before the error is returned.
+    async fn publish_pending<S>(
+        &mut self,
+        store: &S,
+        ready: Vec<PendingEntry>,
+        outcome: &mut PublishedEntries,
+    ) -> Result<(), DomainError>
+    where
+        S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore,
+    {
+        let mut ready: VecDeque<PendingEntry> = ready.into();
+
+        while let Some(mut pending) = ready.pop_front() {
+            let missing = self
+                .try_publish(
+                    store,
+                    &pending.schema,
+                    &pending.encoded_entry,
+                    &pending.encoded_operation,
+                )
+                .await;
+
+            match missing {
+                Ok(None) => {
+                    let hash = pending.encoded_entry.hash();
+                    let entry = decode_entry(&pending.encoded_entry)?;
+                    ready.extend(self.take_dependents(&entry, &hash));
+                    outcome.published.push(hash);
+                }
+                // The entry was waiting for more than one dependency, park it again.
+                Ok(Some(dependency)) => {
+                    pending.dependency = dependency;
+                    self.push_pending(pending);
+                }
+                Err(err) if err.is_storage_error() => {
+                    self.push_pending(pending);
+                    for pending in ready {
+                        self.push_pending(pending);
+                    }
+
+                    return Err(err);
+                }
+                Err(err) => {
+                    debug!(
+                        "Dropping pending entry {}: {}",
+                        pending.encoded_entry.hash(),
+                        err
+                    );
+                    outcome.rejected.push((pending.encoded_entry.hash(), err));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Publish the entry if all of it's dependencies are stored, otherwise returns the first
+    /// missing dependency without publishing it.
+    async fn try_publish<S>(
+        &self,
+        store: &S,
+        schema: &Schema,
+        encoded_entry: &EncodedEntry,
+        encoded_operation: &EncodedOperation,
+    ) -> Result<Option<Dependency>, DomainError>
+    where
+        S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore,
+    {
+        let entry = decode_entry(encoded_entry)?;
+        let plain_operation = decode_operation(encoded_operation)?;
+
+        if let Some(dependency) = missing_dependency(store, &entry, &plain_operation).await? {
+            return Ok(Some(dependency));
+        }
+
+        publish_with_config(
+            store,
+            &self.config.publish,
+            schema,
+            encoded_entry,
+            &plain_operation,
+            encoded_operation,
+        )
+        .await?;
+
+        Ok(None)
+    }
+}
+
+/// Returns true if the entry or operation of the dependency is stored.
+async fn is_stored<S: EntryStore + OperationStore>(
+    store: &S,
+    dependency: &Dependency,
+) -> Result<bool, DomainError> {
+    let is_stored = match dependency {
+        Dependency::Entry(public_key, log_id, seq_num) => store
+            .get_entry_at_seq_num(public_key, log_id, seq_num)
+            .await?
+            .is_some(),
+        Dependency::Operation(operation_id) => store.get_operation(operation_id).await?.is_some(),
+    };
+
+    Ok(is_stored)
+}
+
+/// Returns the first dependency of the entry and operation which is not stored yet.
+///
+/// The backlink is missing when the log doesn't reach up to the sequence number before the
+/// entry's. Entries at or below the latest stored sequence number have no missing dependencies,
+/// publishing them fails with a validation error.
+async fn missing_dependency<S: EntryStore + OperationStore>(
+    store: &S,
+    entry: &impl AsEntry,
+    plain_operation: &PlainOperation,
+) -> Result<Option<Dependency>, DomainError> {
+    let seq_num = entry.seq_num().as_u64();
+
+    if seq_num > 1 {
+        let latest_seq_num = store
+            .get_latest_entry(entry.public_key(), entry.log_id())
+            .await?
+            .map(|latest_entry| latest_entry.seq_num().as_u64())
+            .unwrap_or(0);
+
+        if latest_seq_num + 1 < seq_num {
+            // Safe as the sequence number is larger than 1.
+            let backlink_seq_num = SeqNum::new(seq_num - 1).unwrap();
+
+            return Ok(Some(Dependency::Entry(
+                entry.public_key().to_owned(),
+                entry.log_id().to_owned(),
+                backlink_seq_num,
+            )));
+        }
+    }
+
+    if let Some(previous) = plain_operation.previous() {
+        for operation_id in previous.iter() {
+            if store.get_operation(operation_id).await?.is_none() {
+                return Ok(Some(Dependency::Operation(operation_id.to_owned())));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use crate::api::{publish, DomainError};
+    use crate::entry::traits::{AsEncodedEntry, AsEntry};
+    use crate::entry::{LogId, SeqNum};
+    use crate::hash::Hash;
+    use crate::identity::KeyPair;
+    use crate::operation::decode::decode_operation;
+    use crate::schema::Schema;
+    use crate::storage_provider::traits::EntryStore;
+    use crate::test_utils::fixtures::{log_entries, log_entry, random_hash, schema};
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::{Dependency, IngestConfig, IngestOutcome, IngestQueue};
+
+    /// Returns the dependency the ingested entry is waiting for.
+    fn pending(outcome: IngestOutcome) -> Dependency {
+        match outcome {
+            IngestOutcome::Pending(dependency) => dependency,
+            IngestOutcome::Published(entries) => panic!("Entries were published: {:?}", entries),
+        }
+    }
+
+    /// Returns the hashes of all published entries, expecting that no entry was rejected.
+    fn published(outcome: IngestOutcome) -> Vec<Hash> {
+        match outcome {
+            IngestOutcome::Published(entries) => {
+                assert!(entries.rejected.is_empty());
+                entries.published
+            }
+            IngestOutcome::Pending(dependency) => panic!("Entry is waiting for {:?}", dependency),
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn publishes_entries_arriving_out_of_order(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+        let public_key = key_pair.public_key();
+        let entries = log_entries(&schema, &key_pair, 3);
+        let mut queue = IngestQueue::new(IngestConfig::new());
+
+        let outcome = queue
+            .ingest(&store, &schema, &entries[2].0, &entries[2].1)
+            .await
+            .unwrap();
+        assert_eq!(
+            pending(outcome),
+            Dependency::Entry(public_key, LogId::default(), SeqNum::new(2).unwrap())
+        );
+
+        let outcome = queue
+            .ingest(&store, &schema, &entries[1].0, &entries[1].1)
+            .await
+            .unwrap();
+        assert_eq!(
+            pending(outcome),
+            Dependency::Entry(public_key, LogId::default(), SeqNum::default())
+        );
+        assert_eq!(queue.len(), 2);
+        assert!(queue.is_pending(&entries[2].0.hash()));
+
+        // Ingesting the first entry publishes the whole log.
+        let outcome = queue
+            .ingest(&store, &schema, &entries[0].0, &entries[0].1)
+            .await
+            .unwrap();
+        assert_eq!(
+            published(outcome),
+            entries
+                .iter()
+                .map(|(entry, _)| entry.hash())
+                .collect::<Vec<Hash>>()
+        );
+        assert!(queue.is_empty());
+
+        let latest_entry = store
+            .get_latest_entry(&public_key, &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest_entry.seq_num(), &SeqNum::new(3).unwrap());
+
+        // Invalid entries are still rejected right away.
+        assert!(queue
+            .ingest(&store, &schema, &entries[0].0, &entries[0].1)
+            .await
+            .is_err());
+        assert!(queue.is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn publishes_entries_with_skiplinks_arriving_out_of_order(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+        let entries = log_entries(&schema, &key_pair, 10);
+        let mut queue = IngestQueue::new(IngestConfig::new());
+
+        for (encoded_entry, encoded_operation) in entries.iter().skip(1).rev() {
+            pending(
+                queue
+                    .ingest(&store, &schema, encoded_entry, encoded_operation)
+                    .await
+                    .unwrap(),
+            );
+        }
+        assert_eq!(queue.len(), 9);
+
+        let outcome = queue
+            .ingest(&store, &schema, &entries[0].0, &entries[0].1)
+            .await
+            .unwrap();
+        assert_eq!(published(outcome).len(), 10);
+        assert!(queue.is_empty());
+
+        let latest_entry = store
+            .get_latest_entry(&key_pair.public_key(), &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest_entry.seq_num(), &SeqNum::new(10).unwrap());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn waits_for_previous_operations(schema: Schema) {
+        let store = MemoryStore::default();
+        let panda = KeyPair::new();
+        let penguin = KeyPair::new();
+        let mut queue = IngestQueue::new(IngestConfig::new());
+
+        let create = log_entry(&schema, &panda, &[], None);
+        let update = log_entry(&schema, &penguin, &[], Some(&create.0.hash()));
+
+        let outcome = queue
+            .ingest(&store, &schema, &update.0, &update.1)
+            .await
+            .unwrap();
+        assert_eq!(
+            pending(outcome),
+            Dependency::Operation(create.0.hash().into())
+        );
+        assert_eq!(
+            queue.missing_dependencies(),
+            vec![Dependency::Operation(create.0.hash().into())]
+        );
+
+        let outcome = queue
+            .ingest(&store, &schema, &create.0, &create.1)
+            .await
+            .unwrap();
+        assert_eq!(published(outcome), vec![create.0.hash(), update.0.hash()]);
+        assert!(store
+            .get_latest_entry(&penguin.public_key(), &LogId::default())
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[rstest]
+    #[case::too_many_entries(IngestConfig::new().max_pending_entries(1))]
+    #[case::too_many_bytes(IngestConfig::new().max_pending_bytes(1))]
+    #[tokio::test]
+    async fn bounds_pending_queue(#[case] config: IngestConfig, schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+        let entries = log_entries(&schema, &key_pair, 3);
+        let mut queue = IngestQueue::new(config);
+
+        let mut results = Vec::new();
+        for (encoded_entry, encoded_operation) in entries.iter().skip(1) {
+            results.push(
+                queue
+                    .ingest(&store, &schema, encoded_entry, encoded_operation)
+                    .await,
+            );
+        }
+
+        // At most one entry fits into the queue, the other one is rejected.
+        assert!(queue.len() <= 1);
+        assert!(results
+            .iter()
+            .any(|result| matches!(result, Err(DomainError::PendingQueueFull(_)))));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn expires_pending_entries(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+        let entries = log_entries(&schema, &key_pair, 3);
+        let mut queue = IngestQueue::new(IngestConfig::new().max_pending_age(Duration::ZERO));
+
+        queue
+            .ingest(&store, &schema, &entries[1].0, &entries[1].1)
+            .await
+            .unwrap();
+        assert_eq!(queue.len(), 1);
+
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(queue.expire(), vec![entries[1].0.hash()]);
+        assert!(queue.is_empty());
+
+        // The expired entry isn't published when it's dependency arrives.
+        let outcome = queue
+            .ingest(&store, &schema, &entries[0].0, &entries[0].1)
+            .await
+            .unwrap();
+        assert_eq!(published(outcome), vec![entries[0].0.hash()]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn reports_rejected_dependents(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+        let mut queue = IngestQueue::new(IngestConfig::new());
+
+        // The second entry claims a backlink which isn't the first entry.
+        let first = log_entry(&schema, &key_pair, &[], None);
+        let second = log_entry(&schema, &key_pair, &[random_hash()], Some(&first.0.hash()));
+
+        queue
+            .ingest(&store, &schema, &second.0, &second.1)
+            .await
+            .unwrap();
+
+        let outcome = queue
+            .ingest(&store, &schema, &first.0, &first.1)
+            .await
+            .unwrap();
+        match outcome {
+            IngestOutcome::Published(entries) => {
+                assert_eq!(entries.published, vec![first.0.hash()]);
+                assert_eq!(entries.rejected.len(), 1);
+                assert_eq!(entries.rejected[0].0, second.0.hash());
+            }
+            IngestOutcome::Pending(_) => panic!("Expected first entry to be published"),
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn retries_after_publishing_directly(schema: Schema) {
+        let store = MemoryStore::default();
+        let key_pair = KeyPair::new();
+        let entries = log_entries(&schema, &key_pair, 3);
+        let mut queue = IngestQueue::new(IngestConfig::new());
+
+        for (encoded_entry, encoded_operation) in entries.iter().skip(1) {
+            queue
+                .ingest(&store, &schema, encoded_entry, encoded_operation)
+                .await
+                .unwrap();
+        }
+
+        // Nothing can be published while the first entry is missing.
+        let outcome = queue.retry_pending(&store).await.unwrap();
+        assert!(outcome.published.is_empty());
+        assert_eq!(queue.len(), 2);
+
+        let (encoded_entry, encoded_operation) = &entries[0];
+        publish(
+            &store,
+            &schema,
+            encoded_entry,
+            &decode_operation(encoded_operation).unwrap(),
+            encoded_operation,
+        )
+        .await
+        .unwrap();
+
+        let outcome = queue.retry_pending(&store).await.unwrap();
+        assert_eq!(
+            outcome.published,
+            vec![entries[1].0.hash(), entries[2].0.hash()]
+        );
+        assert!(outcome.rejected.is_empty());
+        assert!(queue.is_empty());
+    }
+}
@@ -1,12 +1,29 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 //! Common validation and API methods following the p2panda specification.
+mod access;
+mod archive;
+mod batch;
 mod errors;
 pub mod helpers;
+mod ingest;
 mod next_args;
+mod overlay;
+mod prune;
 mod publish;
+mod purge;
+mod quota;
 pub mod validation;
 
-pub use errors::{DomainError, ValidationError};
-pub use next_args::next_args;
-pub use publish::publish;
+pub use access::{AllowListField, OpenAccess, OwnerOnly, WriteAccessPolicy};
+pub use archive::{export_archive, import_archive, Archive, ArchiveSelection};
+pub use batch::{publish_batch, PublishBatchResult};
+pub use errors::{AccessPolicyError, ArchiveError, DomainError, ValidationError};
+pub use ingest::{Dependency, IngestConfig, IngestOutcome, IngestQueue, PublishedEntries};
+pub use next_args::{next_args, next_args_many};
+pub use prune::prune_log;
+pub use publish::{
+    publish, publish_with_config, validate_publish, validate_publish_with_config, PublishConfig,
+};
+pub use purge::purge_deleted_documents;
+pub use quota::{Quota, QuotaLimit};
@@ -2,11 +2,11 @@
 
 use crate::api::helpers::get_skiplink_for_entry;
 use crate::api::validation::{
-    ensure_document_not_deleted, get_checked_document_id_for_view_id, increment_seq_num,
-    next_log_id,
+    ensure_document_not_deleted, get_checked_document_id_for_view_id, increment_log_id,
+    increment_seq_num, next_log_id,
 };
 use crate::api::DomainError;
-use crate::document::DocumentViewId;
+use crate::document::{DocumentId, DocumentViewId};
 use crate::entry::traits::{AsEncodedEntry, AsEntry};
 use crate::entry::{LogId, SeqNum};
 use crate::hash::Hash;
@@ -88,6 +88,65 @@ pub async fn next_args<S: EntryStore + OperationStore + LogStore>(
     }
 }
 
+/// Retrieve arguments required for constructing the next entry for several documents of one public
+/// key at once.
+///
+/// Every item is handled like the `document_view_id` argument of `next_args`: `None` requests
+/// arguments for a new document, a document view id arguments for updating the document it
+/// belongs to. Arguments are returned in the order of the passed items.
+///
+/// New logs are allocated consistently across all items: every new document gets it's own log
+/// id, counting up from the next free log id of the public key. The returned arguments are only
+/// valid as long as no other entries are published to the logs of this public key.
+///
+/// Every document can only be requested once, arguments for a following entry in the same log
+/// depend on the hash of the entry constructed with the first arguments.
+///
+/// Fails if the arguments can't be determined for any of the items, for example when a document
+/// view id can't be found or the document was deleted, or when several items point at the same
+/// document.
+pub async fn next_args_many<S: EntryStore + OperationStore + LogStore>(
+    store: &S,
+    public_key: &PublicKey,
+    document_view_ids: &[Option<DocumentViewId>],
+) -> Result<Vec<(Option<Backlink>, Option<Skiplink>, SeqNum, LogId)>, DomainError> {
+    let mut next_args = Vec::with_capacity(document_view_ids.len());
+
+    // Documents requested so far, the next free log id for new documents is determined from the
+    // store when it's needed for the first time.
+    let mut document_ids: Vec<DocumentId> = Vec::new();
+    let mut next_free_log_id: Option<LogId> = None;
+
+    for document_view_id in document_view_ids {
+        if let Some(document_view_id) = document_view_id {
+            let document_id = get_checked_document_id_for_view_id(store, document_view_id).await?;
+
+            if document_ids.contains(&document_id) {
+                return Err(DomainError::DuplicateDocument(document_id));
+            }
+
+            ensure_document_not_deleted(store, &document_id).await?;
+            let log_id = store.get_log_id(public_key, &document_id).await?;
+            document_ids.push(document_id);
+
+            if let Some(log_id) = log_id {
+                next_args.push(calculate_next_args_existing_log(store, &log_id, public_key).await?);
+                continue;
+            }
+        }
+
+        let log_id = match next_free_log_id {
+            Some(mut log_id) => increment_log_id(&mut log_id)?,
+            None => next_log_id(store, public_key).await?,
+        };
+        next_free_log_id = Some(log_id);
+
+        next_args.push((None, None, SeqNum::default(), log_id));
+    }
+
+    Ok(next_args)
+}
+
 /// Calculate the next args for a new log for the given public key.
 async fn calculate_next_args_new_log<S: LogStore>(
     store: &S,
@@ -138,8 +197,8 @@ async fn calculate_next_args_existing_log<S: EntryStore>(
 mod tests {
     use rstest::rstest;
 
-    use crate::api::next_args;
     use crate::api::next_args::{calculate_next_args_existing_log, calculate_next_args_new_log};
+    use crate::api::{next_args, next_args_many, DomainError};
     use crate::document::DocumentViewId;
     use crate::entry::encode::sign_and_encode_entry;
     use crate::entry::traits::{AsEncodedEntry, AsEntry};
@@ -526,4 +585,128 @@ mod tests {
 
         result.map_err(|err| err.to_string()).unwrap();
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn calculates_next_args_for_many_documents(
+        #[from(populate_store_config)]
+        #[with(2, 2, 2)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        // The first public key has logs for the first two documents, the other two were created
+        // by the second public key.
+        let view_ids: Vec<DocumentViewId> = documents
+            .iter()
+            .map(|document_id| document_id.as_str().parse().unwrap())
+            .collect();
+
+        let requests = vec![
+            None,
+            Some(view_ids[1].clone()),
+            Some(view_ids[2].clone()),
+            None,
+            Some(view_ids[3].clone()),
+        ];
+        let many_next_args = next_args_many(&store, &public_key, &requests)
+            .await
+            .unwrap();
+
+        let log_ids: Vec<LogId> = many_next_args
+            .iter()
+            .map(|(_, _, _, log_id)| *log_id)
+            .collect();
+        assert_eq!(
+            log_ids,
+            vec![
+                LogId::new(2),
+                LogId::new(1),
+                LogId::new(3),
+                LogId::new(4),
+                LogId::new(5)
+            ]
+        );
+
+        // Existing logs get the same args as from `next_args`.
+        assert_eq!(
+            many_next_args[1],
+            next_args(&store, &public_key, Some(&view_ids[1]))
+                .await
+                .unwrap()
+        );
+        assert_eq!(many_next_args[1].2, SeqNum::new(3).unwrap());
+
+        // New logs start without backlink and skiplink.
+        for index in [0, 2, 3, 4] {
+            assert_eq!(
+                many_next_args[index],
+                (None, None, SeqNum::default(), log_ids[index])
+            );
+        }
+
+        // Without any requests there are no args.
+        assert!(next_args_many(&store, &public_key, &[])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn many_next_args_fail_for_unknown_document(
+        #[from(populate_store_config)]
+        #[with(2, 1, 1)]
+        config: PopulateStoreConfig,
+        #[from(random_document_view_id)] unknown_view_id: DocumentViewId,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+
+        let requests = vec![
+            Some(documents[0].as_str().parse().unwrap()),
+            Some(unknown_view_id),
+        ];
+        assert!(
+            next_args_many(&store, &key_pairs[0].public_key(), &requests)
+                .await
+                .is_err()
+        );
+    }
+
+    #[rstest]
+    #[case::same_view_id(false)]
+    #[case::different_view_ids(true)]
+    #[tokio::test]
+    async fn many_next_args_fail_for_duplicate_document(
+        #[case] latest_view: bool,
+        #[from(populate_store_config)]
+        #[with(2, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        // Both requests point at the same document, the second one possibly at a later view.
+        let view_id: DocumentViewId = documents[0].as_str().parse().unwrap();
+        let other_view_id = match latest_view {
+            true => store
+                .get_latest_entry(&public_key, &LogId::default())
+                .await
+                .unwrap()
+                .unwrap()
+                .hash()
+                .into(),
+            false => view_id.clone(),
+        };
+
+        let requests = vec![Some(view_id), None, Some(other_view_id)];
+        assert!(matches!(
+            next_args_many(&store, &public_key, &requests).await,
+            Err(DomainError::DuplicateDocument(document_id)) if document_id == documents[0]
+        ));
+    }
 }
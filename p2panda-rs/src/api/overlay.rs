@@ -0,0 +1,590 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_trait::async_trait;
+
+use crate::api::publish::ValidatedEntry;
+use crate::document::{DocumentId, DocumentViewId};
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{EncodedEntry, Entry, LogId, SeqNum, Signature};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::traits::{AsOperation, WithPublicKey};
+use crate::operation::{
+    EncodedOperation, Operation, OperationAction, OperationFields, OperationId, OperationVersion,
+};
+use crate::schema::SchemaId;
+use crate::storage_provider::error::{EntryStorageError, LogStorageError, OperationStorageError};
+use crate::storage_provider::traits::{EntryStore, EntryWithOperation, LogStore, OperationStore};
+use crate::storage_provider::utils::{referenced_document_views, referenced_documents};
+use crate::WithId;
+
+/// Error message returned by all write methods of `StoreOverlay`.
+const READ_ONLY: &str = "Store overlay is read-only";
+
+/// Entry of a batch which was validated but not written to the store yet.
+#[derive(Debug)]
+pub(super) struct PendingEntry<'a> {
+    /// The validated entry and operation.
+    pub(super) validated: ValidatedEntry,
+
+    /// The entry in it's encoded form.
+    pub(super) encoded_entry: &'a EncodedEntry,
+
+    /// The operation in it's encoded form.
+    pub(super) encoded_operation: &'a EncodedOperation,
+}
+
+impl<'a> PendingEntry<'a> {
+    fn is_in_log(&self, public_key: &PublicKey, log_id: &LogId) -> bool {
+        self.validated.entry.public_key() == public_key && self.validated.entry.log_id() == log_id
+    }
+
+    fn to_entry<E>(&self) -> OverlayEntry<E> {
+        OverlayEntry::Pending(
+            Box::new(self.validated.entry.to_owned()),
+            self.encoded_entry.to_owned(),
+            self.encoded_operation.to_owned(),
+        )
+    }
+
+    fn to_operation<O>(&self) -> OverlayOperation<O> {
+        OverlayOperation::Pending(Box::new(PendingOperation {
+            id: self.validated.operation_id.to_owned(),
+            operation: self.validated.operation.to_owned(),
+            public_key: self.validated.entry.public_key().to_owned(),
+            document_id: self.validated.document_id.to_owned(),
+        }))
+    }
+}
+
+/// Read-only view on a store which contains the pending entries of a batch as if they were
+/// stored already.
+///
+/// Entries of a batch can depend on entries preceding them in the same batch. Validating them
+/// against this overlay allows validating the whole batch before anything is written to the store.
+/// All write methods return an error.
+#[derive(Debug)]
+pub(super) struct StoreOverlay<'a, S> {
+    store: &'a S,
+    pending: Vec<PendingEntry<'a>>,
+}
+
+impl<'a, S> StoreOverlay<'a, S> {
+    /// Returns an overlay without pending entries on top of the passed store.
+    pub(super) fn new(store: &'a S) -> Self {
+        Self {
+            store,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add a validated entry, following reads see it as stored.
+    pub(super) fn push(&mut self, pending: PendingEntry<'a>) {
+        self.pending.push(pending);
+    }
+
+    /// Returns all pending entries in the order they were added.
+    pub(super) fn into_pending(self) -> Vec<PendingEntry<'a>> {
+        self.pending
+    }
+}
+
+/// Entry read through a `StoreOverlay`.
+#[derive(Debug, Clone)]
+pub(super) enum OverlayEntry<E> {
+    /// Entry read from the store.
+    Stored(E),
+
+    /// Pending entry together with it's encoded form and payload.
+    Pending(Box<Entry>, EncodedEntry, EncodedOperation),
+}
+
+impl<E: AsEntry> AsEntry for OverlayEntry<E> {
+    fn public_key(&self) -> &PublicKey {
+        match self {
+            OverlayEntry::Stored(entry) => entry.public_key(),
+            OverlayEntry::Pending(entry, _, _) => entry.public_key(),
+        }
+    }
+
+    fn log_id(&self) -> &LogId {
+        match self {
+            OverlayEntry::Stored(entry) => entry.log_id(),
+            OverlayEntry::Pending(entry, _, _) => entry.log_id(),
+        }
+    }
+
+    fn seq_num(&self) -> &SeqNum {
+        match self {
+            OverlayEntry::Stored(entry) => entry.seq_num(),
+            OverlayEntry::Pending(entry, _, _) => entry.seq_num(),
+        }
+    }
+
+    fn skiplink(&self) -> Option<&Hash> {
+        match self {
+            OverlayEntry::Stored(entry) => entry.skiplink(),
+            OverlayEntry::Pending(entry, _, _) => entry.skiplink(),
+        }
+    }
+
+    fn backlink(&self) -> Option<&Hash> {
+        match self {
+            OverlayEntry::Stored(entry) => entry.backlink(),
+            OverlayEntry::Pending(entry, _, _) => entry.backlink(),
+        }
+    }
+
+    fn payload_size(&self) -> u64 {
+        match self {
+            OverlayEntry::Stored(entry) => entry.payload_size(),
+            OverlayEntry::Pending(entry, _, _) => entry.payload_size(),
+        }
+    }
+
+    fn payload_hash(&self) -> &Hash {
+        match self {
+            OverlayEntry::Stored(entry) => entry.payload_hash(),
+            OverlayEntry::Pending(entry, _, _) => entry.payload_hash(),
+        }
+    }
+
+    fn signature(&self) -> &Signature {
+        match self {
+            OverlayEntry::Stored(entry) => entry.signature(),
+            OverlayEntry::Pending(entry, _, _) => entry.signature(),
+        }
+    }
+}
+
+impl<E: AsEncodedEntry> AsEncodedEntry for OverlayEntry<E> {
+    fn hash(&self) -> Hash {
+        match self {
+            OverlayEntry::Stored(entry) => entry.hash(),
+            OverlayEntry::Pending(_, encoded_entry, _) => encoded_entry.hash(),
+        }
+    }
+
+    fn into_bytes(&self) -> Vec<u8> {
+        match self {
+            OverlayEntry::Stored(entry) => entry.into_bytes(),
+            OverlayEntry::Pending(_, encoded_entry, _) => encoded_entry.into_bytes(),
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            OverlayEntry::Stored(entry) => entry.size(),
+            OverlayEntry::Pending(_, encoded_entry, _) => encoded_entry.size(),
+        }
+    }
+}
+
+impl<E: EntryWithOperation> EntryWithOperation for OverlayEntry<E> {
+    fn payload(&self) -> Option<&EncodedOperation> {
+        match self {
+            OverlayEntry::Stored(entry) => entry.payload(),
+            OverlayEntry::Pending(_, _, payload) => Some(payload),
+        }
+    }
+}
+
+/// Operation read through a `StoreOverlay`.
+#[derive(Debug)]
+pub(super) enum OverlayOperation<O> {
+    /// Operation read from the store.
+    Stored(O),
+
+    /// Operation of a pending entry.
+    Pending(Box<PendingOperation>),
+}
+
+/// Operation of a pending entry with it's id, author and document id.
+#[derive(Debug)]
+pub(super) struct PendingOperation {
+    id: OperationId,
+    operation: Operation,
+    public_key: PublicKey,
+    document_id: DocumentId,
+}
+
+impl<O: WithPublicKey> WithPublicKey for OverlayOperation<O> {
+    fn public_key(&self) -> &PublicKey {
+        match self {
+            OverlayOperation::Stored(operation) => operation.public_key(),
+            OverlayOperation::Pending(pending) => &pending.public_key,
+        }
+    }
+}
+
+impl<O: WithId<OperationId>> WithId<OperationId> for OverlayOperation<O> {
+    fn id(&self) -> &OperationId {
+        match self {
+            OverlayOperation::Stored(operation) => operation.id(),
+            OverlayOperation::Pending(pending) => &pending.id,
+        }
+    }
+}
+
+impl<O: WithId<DocumentId>> WithId<DocumentId> for OverlayOperation<O> {
+    fn id(&self) -> &DocumentId {
+        match self {
+            OverlayOperation::Stored(operation) => operation.id(),
+            OverlayOperation::Pending(pending) => &pending.document_id,
+        }
+    }
+}
+
+impl<O: AsOperation> AsOperation for OverlayOperation<O> {
+    fn action(&self) -> OperationAction {
+        match self {
+            OverlayOperation::Stored(operation) => operation.action(),
+            OverlayOperation::Pending(pending) => pending.operation.action(),
+        }
+    }
+
+    fn schema_id(&self) -> SchemaId {
+        match self {
+            OverlayOperation::Stored(operation) => operation.schema_id(),
+            OverlayOperation::Pending(pending) => pending.operation.schema_id(),
+        }
+    }
+
+    fn version(&self) -> OperationVersion {
+        match self {
+            OverlayOperation::Stored(operation) => operation.version(),
+            OverlayOperation::Pending(pending) => pending.operation.version(),
+        }
+    }
+
+    fn fields(&self) -> Option<OperationFields> {
+        match self {
+            OverlayOperation::Stored(operation) => operation.fields(),
+            OverlayOperation::Pending(pending) => pending.operation.fields(),
+        }
+    }
+
+    fn previous(&self) -> Option<DocumentViewId> {
+        match self {
+            OverlayOperation::Stored(operation) => operation.previous(),
+            OverlayOperation::Pending(pending) => pending.operation.previous(),
+        }
+    }
+}
+
+#[async_trait]
+impl<'a, S: LogStore + Sync> LogStore for StoreOverlay<'a, S> {
+    async fn insert_log(
+        &self,
+        _log_id: &LogId,
+        _public_key: &PublicKey,
+        _schema: &SchemaId,
+        _document: &DocumentId,
+    ) -> Result<bool, LogStorageError> {
+        Err(LogStorageError::Custom(READ_ONLY.into()))
+    }
+
+    async fn get_log_id(
+        &self,
+        public_key: &PublicKey,
+        document_id: &DocumentId,
+    ) -> Result<Option<LogId>, LogStorageError> {
+        if let Some(log_id) = self.store.get_log_id(public_key, document_id).await? {
+            return Ok(Some(log_id));
+        }
+
+        Ok(self
+            .pending
+            .iter()
+            .find(|pending| {
+                pending.validated.entry.public_key() == public_key
+                    && &pending.validated.document_id == document_id
+            })
+            .map(|pending| pending.validated.entry.log_id().to_owned()))
+    }
+
+    async fn latest_log_id(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<LogId>, LogStorageError> {
+        let pending_log_ids = self
+            .pending
+            .iter()
+            .filter(|pending| pending.validated.entry.public_key() == public_key)
+            .map(|pending| pending.validated.entry.log_id().to_owned());
+
+        Ok(self
+            .store
+            .latest_log_id(public_key)
+            .await?
+            .into_iter()
+            .chain(pending_log_ids)
+            .max_by_key(LogId::as_u64))
+    }
+}
+
+#[async_trait]
+impl<'a, S: EntryStore + Sync> EntryStore for StoreOverlay<'a, S> {
+    type Entry = OverlayEntry<S::Entry>;
+
+    async fn insert_entry(
+        &self,
+        _entry: &Entry,
+        _encoded_entry: &EncodedEntry,
+        _encoded_operation: Option<&EncodedOperation>,
+    ) -> Result<(), EntryStorageError> {
+        Err(EntryStorageError::Custom(READ_ONLY.into()))
+    }
+
+    async fn remove_payload(&self, _hash: &Hash) -> Result<(), EntryStorageError> {
+        Err(EntryStorageError::Custom(READ_ONLY.into()))
+    }
+
+    async fn remove_entry(&self, _hash: &Hash) -> Result<(), EntryStorageError> {
+        Err(EntryStorageError::Custom(READ_ONLY.into()))
+    }
+
+    async fn insert_pruned_seq_num(
+        &self,
+        _public_key: &PublicKey,
+        _log_id: &LogId,
+        _seq_num: &SeqNum,
+    ) -> Result<(), EntryStorageError> {
+        Err(EntryStorageError::Custom(READ_ONLY.into()))
+    }
+
+    async fn get_pruned_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+    ) -> Result<Option<SeqNum>, EntryStorageError> {
+        self.store.get_pruned_seq_num(public_key, log_id).await
+    }
+
+    async fn get_entry_at_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        seq_num: &SeqNum,
+    ) -> Result<Option<Self::Entry>, EntryStorageError> {
+        let pending = self.pending.iter().find(|pending| {
+            pending.is_in_log(public_key, log_id) && pending.validated.entry.seq_num() == seq_num
+        });
+
+        match pending {
+            Some(pending) => Ok(Some(pending.to_entry())),
+            None => Ok(self
+                .store
+                .get_entry_at_seq_num(public_key, log_id, seq_num)
+                .await?
+                .map(OverlayEntry::Stored)),
+        }
+    }
+
+    async fn get_payload_size_by_public_key(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<u64, EntryStorageError> {
+        let pending_size: u64 = self
+            .pending
+            .iter()
+            .filter(|pending| pending.validated.entry.public_key() == public_key)
+            .map(|pending| pending.encoded_operation.size())
+            .sum();
+
+        Ok(self
+            .store
+            .get_payload_size_by_public_key(public_key)
+            .await?
+            .saturating_add(pending_size))
+    }
+
+    async fn get_entry(&self, hash: &Hash) -> Result<Option<Self::Entry>, EntryStorageError> {
+        match self
+            .pending
+            .iter()
+            .find(|pending| &pending.encoded_entry.hash() == hash)
+        {
+            Some(pending) => Ok(Some(pending.to_entry())),
+            None => Ok(self.store.get_entry(hash).await?.map(OverlayEntry::Stored)),
+        }
+    }
+
+    async fn get_latest_entry(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+    ) -> Result<Option<Self::Entry>, EntryStorageError> {
+        // Pending entries always follow the stored entries of their log.
+        match self
+            .pending
+            .iter()
+            .rev()
+            .find(|pending| pending.is_in_log(public_key, log_id))
+        {
+            Some(pending) => Ok(Some(pending.to_entry())),
+            None => Ok(self
+                .store
+                .get_latest_entry(public_key, log_id)
+                .await?
+                .map(OverlayEntry::Stored)),
+        }
+    }
+
+    async fn get_entries_from(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        from: &SeqNum,
+        limit: usize,
+    ) -> Result<Vec<Self::Entry>, EntryStorageError> {
+        let mut entries: Vec<Self::Entry> = self
+            .store
+            .get_entries_from(public_key, log_id, from, limit)
+            .await?
+            .into_iter()
+            .map(OverlayEntry::Stored)
+            .collect();
+
+        entries.extend(
+            self.pending
+                .iter()
+                .filter(|pending| {
+                    pending.is_in_log(public_key, log_id)
+                        && pending.validated.entry.seq_num().as_u64() >= from.as_u64()
+                })
+                .map(PendingEntry::to_entry),
+        );
+        entries.truncate(limit);
+
+        Ok(entries)
+    }
+}
+
+#[async_trait]
+impl<'a, S: OperationStore + Sync> OperationStore for StoreOverlay<'a, S> {
+    type Operation = OverlayOperation<S::Operation>;
+
+    async fn insert_operation(
+        &self,
+        _id: &OperationId,
+        _public_key: &PublicKey,
+        _operation: &Operation,
+        _document_id: &DocumentId,
+    ) -> Result<(), OperationStorageError> {
+        Err(OperationStorageError::Custom(READ_ONLY.into()))
+    }
+
+    async fn remove_operations_by_document_id(
+        &self,
+        _id: &DocumentId,
+    ) -> Result<(), OperationStorageError> {
+        Err(OperationStorageError::Custom(READ_ONLY.into()))
+    }
+
+    async fn get_operation(
+        &self,
+        id: &OperationId,
+    ) -> Result<Option<Self::Operation>, OperationStorageError> {
+        match self
+            .pending
+            .iter()
+            .find(|pending| &pending.validated.operation_id == id)
+        {
+            Some(pending) => Ok(Some(pending.to_operation())),
+            None => Ok(self
+                .store
+                .get_operation(id)
+                .await?
+                .map(OverlayOperation::Stored)),
+        }
+    }
+
+    async fn get_document_id_by_operation_id(
+        &self,
+        id: &OperationId,
+    ) -> Result<Option<DocumentId>, OperationStorageError> {
+        match self
+            .pending
+            .iter()
+            .find(|pending| &pending.validated.operation_id == id)
+        {
+            Some(pending) => Ok(Some(pending.validated.document_id.to_owned())),
+            None => self.store.get_document_id_by_operation_id(id).await,
+        }
+    }
+
+    async fn get_operations_by_document_id(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<Self::Operation>, OperationStorageError> {
+        let stored = self.store.get_operations_by_document_id(id).await?;
+        Ok(self.with_pending_operations(stored, |pending| &pending.validated.document_id == id))
+    }
+
+    async fn get_operations_by_schema_id(
+        &self,
+        id: &SchemaId,
+    ) -> Result<Vec<Self::Operation>, OperationStorageError> {
+        let stored = self.store.get_operations_by_schema_id(id).await?;
+        Ok(self.with_pending_operations(stored, |pending| {
+            &pending.validated.operation.schema_id() == id
+        }))
+    }
+
+    async fn count_documents_by_schema_id(
+        &self,
+        id: &SchemaId,
+    ) -> Result<usize, OperationStorageError> {
+        let pending_count = self
+            .pending
+            .iter()
+            .filter(|pending| {
+                pending.validated.operation.is_create()
+                    && &pending.validated.operation.schema_id() == id
+            })
+            .count();
+
+        Ok(self.store.count_documents_by_schema_id(id).await? + pending_count)
+    }
+
+    async fn get_operations_referencing_document(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<Self::Operation>, OperationStorageError> {
+        let stored = self.store.get_operations_referencing_document(id).await?;
+        Ok(self.with_pending_operations(stored, |pending| {
+            referenced_documents(&pending.validated.operation).contains(id)
+        }))
+    }
+
+    async fn get_operations_referencing_view(
+        &self,
+        id: &DocumentViewId,
+    ) -> Result<Vec<Self::Operation>, OperationStorageError> {
+        let stored = self.store.get_operations_referencing_view(id).await?;
+        Ok(self.with_pending_operations(stored, |pending| {
+            referenced_document_views(&pending.validated.operation).contains(id)
+        }))
+    }
+}
+
+impl<'a, S: OperationStore> StoreOverlay<'a, S> {
+    /// Returns the stored operations followed by the operations of all matching pending entries.
+    fn with_pending_operations(
+        &self,
+        stored: Vec<S::Operation>,
+        filter: impl Fn(&PendingEntry) -> bool,
+    ) -> Vec<OverlayOperation<S::Operation>> {
+        stored
+            .into_iter()
+            .map(OverlayOperation::Stored)
+            .chain(
+                self.pending
+                    .iter()
+                    .filter(|pending| filter(pending))
+                    .map(PendingEntry::to_operation),
+            )
+            .collect()
+    }
+}
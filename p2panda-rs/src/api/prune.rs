@@ -0,0 +1,371 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::BTreeSet;
+
+use crate::api::DomainError;
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{LogId, SeqNum};
+use crate::identity::PublicKey;
+use crate::storage_provider::traits::{EntryStore, TransactionStore};
+
+/// Returns the sequence numbers of all entries below `seq_num` which need to be retained to
+/// verify and extend a log pruned below this sequence number.
+///
+/// These are all skiplink targets of entries at `seq_num` or later which lie below `seq_num`,
+/// together with the skiplink targets along their own certificate paths down to the first entry.
+fn retained_seq_nums(seq_num: &SeqNum) -> BTreeSet<u64> {
+    let mut retained = BTreeSet::new();
+
+    // A skiplink never points further back than a third of the linking entry's sequence number,
+    // entries after this bound only link to entries which are not pruned.
+    let bound = seq_num.as_u64().saturating_mul(3).saturating_add(1);
+
+    for linking_seq_num in seq_num.as_u64()..=bound {
+        let mut current = SeqNum::new(linking_seq_num).unwrap();
+
+        // Follow the skiplinks until we reach the first entry or an already retained one.
+        while !current.is_first() {
+            // Unwrap because method always returns `Some` for seq num > 1
+            let skiplink_seq_num = current.skiplink_seq_num().unwrap();
+
+            if skiplink_seq_num.as_u64() >= seq_num.as_u64() {
+                current = skiplink_seq_num;
+                continue;
+            }
+
+            if !retained.insert(skiplink_seq_num.as_u64()) {
+                break;
+            }
+
+            current = skiplink_seq_num;
+        }
+    }
+
+    retained
+}
+
+/// Prune a public key's log below the passed sequence number.
+///
+/// Removes the payloads of all entries below `seq_num`, the operations they contained are not
+/// affected. If `remove_entries` is set, entries below `seq_num` are removed completely, apart
+/// from the skiplink targets required to verify the remaining log via it's certificate pool and to
+/// validate entries published to it in the future.
+///
+/// The latest entry of a log is never pruned, passing a higher sequence number prunes up to it.
+/// The pruned sequence number is recorded in the store, `publish` rejects new entries whose
+/// skiplink target was pruned with a distinct error.
+///
+/// All changes are written inside one transaction.
+pub async fn prune_log<S: EntryStore + TransactionStore>(
+    store: &S,
+    public_key: &PublicKey,
+    log_id: &LogId,
+    seq_num: &SeqNum,
+    remove_entries: bool,
+) -> Result<(), DomainError> {
+    let latest_entry = match store.get_latest_entry(public_key, log_id).await? {
+        Some(entry) => entry,
+        None => return Ok(()),
+    };
+
+    let prune_below = match seq_num.as_u64() > latest_entry.seq_num().as_u64() {
+        true => latest_entry.seq_num().to_owned(),
+        false => seq_num.to_owned(),
+    };
+
+    let transaction = store.begin_transaction().await?;
+
+    let result = prune_entries(
+        &transaction,
+        public_key,
+        log_id,
+        &prune_below,
+        remove_entries,
+    )
+    .await;
+
+    // Only commit when all removals succeeded, otherwise roll them back and return the error.
+    match result {
+        Ok(_) => store.commit_transaction(transaction).await?,
+        Err(err) => {
+            store.rollback_transaction(transaction).await?;
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the payloads and entries of a log below the passed sequence number.
+///
+/// This is expected to be called on a transaction, an error returned part way through must be
+/// followed by a rollback.
+async fn prune_entries<S: EntryStore>(
+    store: &S,
+    public_key: &PublicKey,
+    log_id: &LogId,
+    seq_num: &SeqNum,
+    remove_entries: bool,
+) -> Result<(), DomainError> {
+    let retained = retained_seq_nums(seq_num);
+
+    // Never move the recorded pruned sequence number back.
+    let pruned_seq_num = store.get_pruned_seq_num(public_key, log_id).await?;
+    if !matches!(pruned_seq_num, Some(pruned) if pruned.as_u64() >= seq_num.as_u64()) {
+        store
+            .insert_pruned_seq_num(public_key, log_id, seq_num)
+            .await?;
+    }
+
+    let limit = (seq_num.as_u64() - 1) as usize;
+    let entries = store
+        .get_entries_from(public_key, log_id, &SeqNum::default(), limit)
+        .await?;
+
+    for entry in entries {
+        if remove_entries && !retained.contains(&entry.seq_num().as_u64()) {
+            store.remove_entry(&entry.hash()).await?;
+        } else {
+            store.remove_payload(&entry.hash()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::api::validation::get_expected_skiplink;
+    use crate::api::{next_args, publish, DomainError, ValidationError};
+    use crate::document::DocumentViewId;
+    use crate::entry::encode::{encode_entry, sign_entry};
+    use crate::entry::traits::{AsEncodedEntry, AsEntry};
+    use crate::entry::validate::validate_certificate_pool;
+    use crate::entry::{LogId, SeqNum};
+    use crate::operation::encode::encode_operation;
+    use crate::operation::traits::AsOperation;
+    use crate::operation::{OperationAction, OperationBuilder};
+    use crate::storage_provider::traits::EntryStore;
+    use crate::test_utils::constants::test_fields;
+    use crate::test_utils::fixtures::populate_store_config;
+    use crate::test_utils::memory_store::helpers::{
+        populate_store, remove_entries, send_to_store, PopulateStoreConfig,
+    };
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::{prune_log, retained_seq_nums};
+
+    #[rstest]
+    #[case(1, vec![])]
+    #[case(2, vec![1])]
+    #[case(5, vec![1, 4])]
+    #[case(10, vec![1, 4, 8, 9])]
+    #[case(14, vec![1, 4, 13])]
+    fn retains_skiplink_targets(#[case] seq_num: u64, #[case] expected: Vec<u64>) {
+        let retained = retained_seq_nums(&SeqNum::new(seq_num).unwrap());
+        assert_eq!(retained.into_iter().collect::<Vec<u64>>(), expected);
+    }
+
+    #[test]
+    fn retained_entries_cover_all_future_skiplinks() {
+        for seq_num in 1..300 {
+            let retained = retained_seq_nums(&SeqNum::new(seq_num).unwrap());
+
+            // Every entry at or after the pruned sequence number finds it's skiplink.
+            for linking_seq_num in (seq_num + 1).max(2)..3000 {
+                let skiplink_seq_num = SeqNum::new(linking_seq_num)
+                    .unwrap()
+                    .skiplink_seq_num()
+                    .unwrap()
+                    .as_u64();
+
+                assert!(skiplink_seq_num >= seq_num || retained.contains(&skiplink_seq_num));
+            }
+        }
+    }
+
+    #[rstest]
+    #[case::payloads_only(false, 20)]
+    #[case::entries(true, 10)]
+    #[tokio::test]
+    async fn prunes_log(
+        #[case] remove_entries: bool,
+        #[case] expected_no_of_entries: usize,
+        #[from(populate_store_config)]
+        #[with(20, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let key_pair = &key_pairs[0];
+        let public_key = key_pair.public_key();
+        let log_id = LogId::default();
+
+        prune_log(
+            &store,
+            &public_key,
+            &log_id,
+            &SeqNum::new(15).unwrap(),
+            remove_entries,
+        )
+        .await
+        .unwrap();
+
+        let entries = store
+            .get_entries_from(&public_key, &log_id, &SeqNum::default(), 100)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), expected_no_of_entries);
+        assert_eq!(
+            store
+                .get_pruned_seq_num(&public_key, &log_id)
+                .await
+                .unwrap(),
+            Some(SeqNum::new(15).unwrap())
+        );
+
+        // Only entries from the pruned sequence number on still have their payloads.
+        for entry in &entries {
+            assert_eq!(entry.payload.is_some(), entry.seq_num().as_u64() >= 15);
+        }
+
+        // The latest entry can still be verified with it's certificate pool.
+        let latest_entry = store
+            .get_latest_entry(&public_key, &log_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let certificate_pool = store
+            .get_certificate_pool(&public_key, &log_id, latest_entry.seq_num())
+            .await
+            .unwrap();
+        assert!(validate_certificate_pool(&latest_entry, &latest_entry, &certificate_pool).is_ok());
+
+        // The log can be extended with new entries.
+        let mut view_id: DocumentViewId = latest_entry.hash().into();
+        for _ in 0..20 {
+            let operation = OperationBuilder::new(config.schema.id())
+                .action(OperationAction::Update)
+                .previous(&view_id)
+                .fields(&test_fields())
+                .build()
+                .unwrap();
+            let (encoded_entry, _) = send_to_store(&store, &operation, &config.schema, key_pair)
+                .await
+                .unwrap();
+            view_id = encoded_entry.hash().into();
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn rejects_entry_with_pruned_skiplink(
+        #[from(populate_store_config)]
+        #[with(12, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let key_pair = &key_pairs[0];
+        let public_key = key_pair.public_key();
+        let log_id = LogId::default();
+
+        prune_log(
+            &store,
+            &public_key,
+            &log_id,
+            &SeqNum::new(12).unwrap(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        // Prepare the next entry which has seq num 13 as it's skiplink.
+        let latest_entry = store
+            .get_latest_entry(&public_key, &log_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let operation = OperationBuilder::new(config.schema.id())
+            .action(OperationAction::Update)
+            .previous(&latest_entry.hash().into())
+            .fields(&test_fields())
+            .build()
+            .unwrap();
+        let (backlink, skiplink, seq_num, _) =
+            next_args(&store, &public_key, operation.previous().as_ref())
+                .await
+                .unwrap();
+        assert_eq!(seq_num, SeqNum::new(13).unwrap());
+        assert!(skiplink.is_some());
+
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = encode_entry(
+            &sign_entry(
+                &log_id,
+                &seq_num,
+                skiplink.as_ref(),
+                backlink.as_ref(),
+                &encoded_operation,
+                key_pair,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        // Remove the skiplink target, it lies below the pruned seq num.
+        remove_entries(&store, &public_key, &[(0, 4)]);
+        assert!(matches!(
+            get_expected_skiplink(&store, &public_key, &log_id, &seq_num).await,
+            Err(ValidationError::ExpectedSkiplinkPruned(..))
+        ));
+
+        // The entry can't be validated without it's skiplink target and is rejected.
+        assert!(matches!(
+            publish(
+                &store,
+                &config.schema,
+                &encoded_entry,
+                &(&operation).into(),
+                &encoded_operation,
+            )
+            .await,
+            Err(DomainError::ValidationError(
+                ValidationError::ExpectedSkiplinkPruned(..)
+            ))
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn never_prunes_latest_entry(
+        #[from(populate_store_config)]
+        #[with(5, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+        let log_id = LogId::default();
+
+        prune_log(
+            &store,
+            &public_key,
+            &log_id,
+            &SeqNum::new(100).unwrap(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        let latest_entry = store
+            .get_latest_entry(&public_key, &log_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest_entry.seq_num(), &SeqNum::new(5).unwrap());
+        assert!(latest_entry.payload.is_some());
+    }
+}
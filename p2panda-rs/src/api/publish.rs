@@ -1,6 +1,10 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::sync::Arc;
+
+use crate::api::access::{check_write_access, WriteAccessPolicy};
 use crate::api::helpers::get_skiplink_for_entry;
+use crate::api::quota::{check_quota, Quota};
 use crate::api::validation::{
     ensure_document_not_deleted, get_checked_document_id_for_view_id, get_expected_skiplink,
     increment_seq_num, is_next_seq_num, validate_claimed_schema_id, verify_log_id,
@@ -9,14 +13,47 @@ use crate::api::DomainError;
 use crate::document::DocumentId;
 use crate::entry::decode::decode_entry;
 use crate::entry::traits::{AsEncodedEntry, AsEntry};
-use crate::entry::{EncodedEntry, LogId, SeqNum};
+use crate::entry::{EncodedEntry, Entry, LogId, SeqNum};
 use crate::hash::Hash;
 use crate::operation::plain::PlainOperation;
 use crate::operation::traits::AsOperation;
 use crate::operation::validate::validate_operation_with_entry;
 use crate::operation::{EncodedOperation, Operation, OperationAction, OperationId};
 use crate::schema::Schema;
-use crate::storage_provider::traits::{EntryStore, LogStore, OperationStore};
+use crate::storage_provider::events::StoreEvent;
+use crate::storage_provider::traits::{
+    EntryStore, LogStore, OperationStore, SubscriptionStore, TransactionStore,
+};
+
+/// Optional checks performed by `publish_with_config` in addition to the validation steps of
+/// `publish`.
+///
+/// The default config doesn't enable any further checks.
+#[derive(Clone, Debug, Default)]
+pub struct PublishConfig {
+    quota: Option<Quota>,
+    write_access: Option<Arc<dyn WriteAccessPolicy>>,
+}
+
+impl PublishConfig {
+    /// Returns a config without any further checks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject entries which would exceed the passed quota.
+    pub fn quota(mut self, quota: Quota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Reject operations from public keys which the passed policy doesn't allow to write to the
+    /// document.
+    pub fn write_access(mut self, policy: Arc<dyn WriteAccessPolicy>) -> Self {
+        self.write_access = Some(policy);
+        self
+    }
+}
 
 /// An entries' backlink returned by next_args.
 type Backlink = Hash;
@@ -64,21 +101,234 @@ type Skiplink = Hash;
 ///
 /// ## Persist data
 ///
+/// All of the following writes happen inside one transaction, if any of them fails the store is
+/// rolled back and none of them are persisted.
+///
 /// - If this is a new document:
 ///   - Store the new log.
 /// - Store the entry.
 /// - Store the operation.
 ///
+/// ## Notify subscribers
+///
+/// - After the transaction was committed, send events about the inserted entry and operation to
+///   all matching subscribers of the store.
+///
 /// ## Compute and return next entry arguments
 ///
 /// - Done!
-pub async fn publish<S: EntryStore + OperationStore + LogStore>(
+pub async fn publish<
+    S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore,
+>(
     store: &S,
     schema: &Schema,
     encoded_entry: &EncodedEntry,
     plain_operation: &PlainOperation,
     encoded_operation: &EncodedOperation,
 ) -> Result<(Option<Backlink>, Option<Skiplink>, SeqNum, LogId), DomainError> {
+    publish_with_config(
+        store,
+        &PublishConfig::default(),
+        schema,
+        encoded_entry,
+        plain_operation,
+        encoded_operation,
+    )
+    .await
+}
+
+/// Persist an entry and operation to storage like `publish`, additionally performing the checks
+/// enabled in the passed config.
+///
+/// ## Enforce write access
+///
+/// - If a write access policy is set, reject operations from public keys which are not allowed
+///   to write to the document. See `WriteAccessPolicy` for the provided policies.
+///
+/// ## Enforce quota
+///
+/// - If a quota is set, reject entries which would exceed it after the entry and operation were
+///   validated and their document id was determined. See `Quota` for the enforced limits.
+pub async fn publish_with_config<
+    S: EntryStore + OperationStore + LogStore + TransactionStore + SubscriptionStore,
+>(
+    store: &S,
+    config: &PublishConfig,
+    schema: &Schema,
+    encoded_entry: &EncodedEntry,
+    plain_operation: &PlainOperation,
+    encoded_operation: &EncodedOperation,
+) -> Result<(Option<Backlink>, Option<Skiplink>, SeqNum, LogId), DomainError> {
+    let validated = validate_entry(
+        store,
+        config,
+        schema,
+        encoded_entry,
+        plain_operation,
+        encoded_operation,
+    )
+    .await?;
+    let entry = &validated.entry;
+
+    // Get the skiplink for the following entry to be used in next args
+    let skiplink = get_skiplink_for_entry(
+        store,
+        &validated.next_seq_num,
+        entry.log_id(),
+        entry.public_key(),
+    )
+    .await?;
+
+    ////////////////////////////////////
+    // STORE LOG, ENTRY AND OPERATION //
+    ////////////////////////////////////
+
+    let transaction = store.begin_transaction().await?;
+
+    let result = insert_log_entry_and_operation(
+        &transaction,
+        entry,
+        encoded_entry,
+        encoded_operation,
+        &validated.operation,
+        &validated.operation_id,
+        &validated.document_id,
+    )
+    .await;
+
+    // Only commit when all writes succeeded, otherwise roll them back and return the error.
+    match result {
+        Ok(_) => store.commit_transaction(transaction).await?,
+        Err(err) => {
+            store.rollback_transaction(transaction).await?;
+            return Err(err);
+        }
+    }
+
+    // Let subscribers know about the new data only once it was committed.
+    notify_subscribers(store, &validated, encoded_entry);
+
+    // Construct and return next args.
+    Ok((
+        Some(encoded_entry.hash()),
+        skiplink,
+        validated.next_seq_num,
+        entry.log_id().to_owned(),
+    ))
+}
+
+/// Perform all validation steps of `publish` without persisting the entry and operation.
+///
+/// Returns the id of the document the operation belongs to and the arguments for constructing
+/// the entry following this one, as `publish` would after storing it. Nothing is written to the
+/// store and no subscribers are notified, an entry which passed can still be rejected by
+/// `publish` if the store changed in the meantime.
+///
+/// This can be used to check entries before publishing them, for example in clients.
+pub async fn validate_publish<S: EntryStore + OperationStore + LogStore>(
+    store: &S,
+    schema: &Schema,
+    encoded_entry: &EncodedEntry,
+    plain_operation: &PlainOperation,
+    encoded_operation: &EncodedOperation,
+) -> Result<
+    (
+        DocumentId,
+        (Option<Backlink>, Option<Skiplink>, SeqNum, LogId),
+    ),
+    DomainError,
+> {
+    validate_publish_with_config(
+        store,
+        &PublishConfig::default(),
+        schema,
+        encoded_entry,
+        plain_operation,
+        encoded_operation,
+    )
+    .await
+}
+
+/// Perform all validation steps of `publish_with_config` without persisting the entry and
+/// operation, like `validate_publish` additionally performing the checks enabled in the passed
+/// config.
+///
+/// Use this to check entries which are going to be published with the same config, otherwise
+/// entries rejected by the write access policy or quota pass validation.
+pub async fn validate_publish_with_config<S: EntryStore + OperationStore + LogStore>(
+    store: &S,
+    config: &PublishConfig,
+    schema: &Schema,
+    encoded_entry: &EncodedEntry,
+    plain_operation: &PlainOperation,
+    encoded_operation: &EncodedOperation,
+) -> Result<
+    (
+        DocumentId,
+        (Option<Backlink>, Option<Skiplink>, SeqNum, LogId),
+    ),
+    DomainError,
+> {
+    let validated = validate_entry(
+        store,
+        config,
+        schema,
+        encoded_entry,
+        plain_operation,
+        encoded_operation,
+    )
+    .await?;
+    let entry = &validated.entry;
+
+    // Get the skiplink for the following entry, it is never the entry itself so it's already
+    // stored if the log is valid.
+    let skiplink = get_skiplink_for_entry(
+        store,
+        &validated.next_seq_num,
+        entry.log_id(),
+        entry.public_key(),
+    )
+    .await?;
+
+    Ok((
+        validated.document_id,
+        (
+            Some(encoded_entry.hash()),
+            skiplink,
+            validated.next_seq_num,
+            entry.log_id().to_owned(),
+        ),
+    ))
+}
+
+/// Entry and operation which passed all validation steps of `publish_with_config`.
+#[derive(Debug)]
+pub(super) struct ValidatedEntry {
+    /// The decoded entry.
+    pub(super) entry: Entry,
+
+    /// The operation contained in the entry.
+    pub(super) operation: Operation,
+
+    /// Id of the operation, derived from the entry hash.
+    pub(super) operation_id: OperationId,
+
+    /// Id of the document the operation belongs to.
+    pub(super) document_id: DocumentId,
+
+    /// Sequence number of the entry following this one in it's log.
+    pub(super) next_seq_num: SeqNum,
+}
+
+/// Perform all validation steps of `publish_with_config` without writing to the store.
+pub(super) async fn validate_entry<S: EntryStore + OperationStore + LogStore>(
+    store: &S,
+    config: &PublishConfig,
+    schema: &Schema,
+    encoded_entry: &EncodedEntry,
+    plain_operation: &PlainOperation,
+    encoded_operation: &EncodedOperation,
+) -> Result<ValidatedEntry, DomainError> {
     // Decode the entry.
     let entry = decode_entry(encoded_entry)?;
 
@@ -106,20 +356,76 @@ pub async fn publish<S: EntryStore + OperationStore + LogStore>(
     )
     .await?;
 
+    // Reject the operation if the public key isn't allowed to write to the document.
+    if let Some(policy) = &config.write_access {
+        check_write_access(
+            store,
+            policy.as_ref(),
+            schema,
+            entry.public_key(),
+            &operation,
+            &document_id,
+        )
+        .await?;
+    }
+
+    // Reject the entry if it would exceed the configured quota.
+    if let Some(quota) = &config.quota {
+        check_quota(store, quota, &entry, &operation, encoded_operation).await?;
+    }
+
     // If we have reached MAX_SEQ_NUM here for the next args then we will error and _not_ store
     // the entry which is being processed in this request.
     let next_seq_num = increment_seq_num(&mut entry.seq_num().clone()).map_err(|_| {
         DomainError::MaxSeqNumReached(entry.public_key().to_string(), entry.log_id().as_u64())
     })?;
 
-    // Get the skiplink for the following entry to be used in next args
-    let skiplink =
-        get_skiplink_for_entry(store, &next_seq_num, entry.log_id(), entry.public_key()).await?;
+    Ok(ValidatedEntry {
+        entry,
+        operation,
+        operation_id,
+        document_id,
+        next_seq_num,
+    })
+}
 
-    ///////////////
-    // STORE LOG //
-    ///////////////
+/// Send events about an inserted entry and operation to all matching subscribers of the store.
+pub(super) fn notify_subscribers<S: SubscriptionStore>(
+    store: &S,
+    validated: &ValidatedEntry,
+    encoded_entry: &EncodedEntry,
+) {
+    let entry = &validated.entry;
+
+    store.notify(StoreEvent::EntryInserted {
+        hash: encoded_entry.hash(),
+        public_key: entry.public_key().to_owned(),
+        log_id: entry.log_id().to_owned(),
+        seq_num: entry.seq_num().to_owned(),
+        schema_id: validated.operation.schema_id(),
+        document_id: validated.document_id.clone(),
+    });
+    store.notify(StoreEvent::OperationInserted {
+        operation_id: validated.operation_id.clone(),
+        public_key: entry.public_key().to_owned(),
+        schema_id: validated.operation.schema_id(),
+        document_id: validated.document_id.clone(),
+    });
+}
 
+/// Insert the log (if this is the first entry), entry and operation into the store.
+///
+/// This is expected to be called inside a transaction, an error returned part way through must be
+/// followed by a rollback.
+pub(super) async fn insert_log_entry_and_operation<S: EntryStore + OperationStore + LogStore>(
+    store: &S,
+    entry: &Entry,
+    encoded_entry: &EncodedEntry,
+    encoded_operation: &EncodedOperation,
+    operation: &Operation,
+    operation_id: &OperationId,
+    document_id: &DocumentId,
+) -> Result<(), DomainError> {
     // If the entries' seq num is 1 we insert a new log here.
     if entry.seq_num().is_first() {
         store
@@ -127,32 +433,22 @@ pub async fn publish<S: EntryStore + OperationStore + LogStore>(
                 entry.log_id(),
                 entry.public_key(),
                 &operation.schema_id(),
-                &document_id,
+                document_id,
             )
             .await?;
     }
 
-    ///////////////////////////////
-    // STORE ENTRY AND OPERATION //
-    ///////////////////////////////
-
     // Insert the entry into the store.
     store
-        .insert_entry(&entry, encoded_entry, Some(encoded_operation))
+        .insert_entry(entry, encoded_entry, Some(encoded_operation))
         .await?;
 
     // Insert the operation into the store.
     store
-        .insert_operation(&operation_id, entry.public_key(), &operation, &document_id)
+        .insert_operation(operation_id, entry.public_key(), operation, document_id)
         .await?;
 
-    // Construct and return next args.
-    Ok((
-        Some(encoded_entry.hash()),
-        skiplink,
-        next_seq_num,
-        entry.log_id().to_owned(),
-    ))
+    Ok(())
 }
 
 /// Wrapper for `operation::validate::validate_operation_with_entry` which makes use of methods
@@ -174,7 +470,9 @@ async fn validate_entry_and_operation<S: EntryStore + OperationStore + LogStore>
     let latest_seq_num = latest_entry.as_ref().map(|entry| entry.seq_num());
     is_next_seq_num(latest_seq_num, entry.seq_num())?;
 
-    // If a skiplink is claimed, get the expected skiplink from the database, errors if it can't be found.
+    // If a skiplink is claimed, get the expected skiplink from the database, errors if it can't be
+    // found. Pruning a log keeps all skiplink targets of future entries, an entry whose skiplink
+    // target was pruned is rejected.
     let skiplink = match entry.skiplink() {
         Some(_) => Some(
             get_expected_skiplink(store, entry.public_key(), entry.log_id(), entry.seq_num())
@@ -249,9 +547,14 @@ async fn determine_document_id<S: OperationStore>(
 
 #[cfg(test)]
 mod tests {
+    use futures::{FutureExt, StreamExt};
     use rstest::rstest;
 
-    use crate::api::{next_args, publish};
+    use crate::api::helpers::get_skiplink_for_entry;
+    use crate::api::{
+        next_args, publish, publish_with_config, validate_publish, validate_publish_with_config,
+        DomainError, PublishConfig, Quota, QuotaLimit,
+    };
     use crate::document::{DocumentId, DocumentViewId};
     use crate::entry::encode::sign_and_encode_entry;
     use crate::entry::traits::{AsEncodedEntry, AsEntry};
@@ -264,7 +567,11 @@ mod tests {
         Operation, OperationAction, OperationBuilder, OperationId, OperationValue,
     };
     use crate::schema::{FieldType, Schema, SchemaId, SchemaName};
-    use crate::storage_provider::traits::{EntryStore, LogStore, OperationStore};
+    use crate::storage_provider::error::OperationStorageError;
+    use crate::storage_provider::events::{EventFilter, StoreEvent};
+    use crate::storage_provider::traits::{
+        EntryStore, LogStore, OperationStore, SubscriptionStore,
+    };
     use crate::test_utils::constants::{test_fields, PRIVATE_KEY};
     use crate::test_utils::fixtures::{
         create_operation, delete_operation, key_pair, operation, populate_store_config,
@@ -1051,4 +1358,334 @@ mod tests {
 
         result.map_err(|err| err.to_string()).unwrap();
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn rolls_back_when_insertion_fails(
+        schema: Schema,
+        key_pair: KeyPair,
+        operation: Operation,
+    ) {
+        let store = MemoryStore::default();
+        let public_key = key_pair.public_key();
+
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = sign_and_encode_entry(
+            &LogId::default(),
+            &SeqNum::default(),
+            None,
+            None,
+            &encoded_operation,
+            &key_pair,
+        )
+        .unwrap();
+
+        // Insert an operation with the id the published operation will have. This causes the
+        // last write in `publish` to fail after the log and entry were already inserted.
+        let operation_id: OperationId = encoded_entry.hash().into();
+        let document_id: DocumentId = encoded_entry.hash().into();
+        store
+            .insert_operation(&operation_id, &public_key, &operation, &document_id)
+            .await
+            .unwrap();
+
+        let result = publish(
+            &store,
+            &schema,
+            &encoded_entry,
+            &decode_operation(&encoded_operation).unwrap(),
+            &encoded_operation,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(DomainError::OperationStoreError(
+                OperationStorageError::InsertionError(_)
+            ))
+        ));
+
+        // Neither the log nor the entry were persisted.
+        assert!(store
+            .get_log_id(&public_key, &document_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_none());
+
+        // The transaction was closed, after removing the conflicting operation the same entry
+        // can be published successfully.
+        store.operations.lock().unwrap().clear();
+
+        publish(
+            &store,
+            &schema,
+            &encoded_entry,
+            &decode_operation(&encoded_operation).unwrap(),
+            &encoded_operation,
+        )
+        .await
+        .unwrap();
+
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn notifies_subscribers_after_commit(
+        schema: Schema,
+        key_pair: KeyPair,
+        operation: Operation,
+    ) {
+        let store = MemoryStore::default();
+        let public_key = key_pair.public_key();
+
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = sign_and_encode_entry(
+            &LogId::default(),
+            &SeqNum::default(),
+            None,
+            None,
+            &encoded_operation,
+            &key_pair,
+        )
+        .unwrap();
+        let document_id: DocumentId = encoded_entry.hash().into();
+
+        let mut events = store.subscribe(EventFilter::new().public_key(&public_key));
+        let mut other_schema_events =
+            store.subscribe(EventFilter::new().schema_id(&SchemaId::SchemaDefinition(1)));
+
+        publish(
+            &store,
+            &schema,
+            &encoded_entry,
+            &decode_operation(&encoded_operation).unwrap(),
+            &encoded_operation,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            events.next().await,
+            Some(StoreEvent::EntryInserted {
+                hash: encoded_entry.hash(),
+                public_key,
+                log_id: LogId::default(),
+                seq_num: SeqNum::default(),
+                schema_id: schema.id().to_owned(),
+                document_id: document_id.clone(),
+            })
+        );
+        assert_eq!(
+            events.next().await,
+            Some(StoreEvent::OperationInserted {
+                operation_id: encoded_entry.hash().into(),
+                public_key,
+                schema_id: schema.id().to_owned(),
+                document_id,
+            })
+        );
+        assert!(other_schema_events.next().now_or_never().is_none());
+
+        // Publishing the same entry again fails and doesn't notify anyone.
+        assert!(publish(
+            &store,
+            &schema,
+            &encoded_entry,
+            &decode_operation(&encoded_operation).unwrap(),
+            &encoded_operation,
+        )
+        .await
+        .is_err());
+        assert!(events.next().now_or_never().is_none());
+    }
+
+    #[rstest]
+    #[case::valid(0, false, true)]
+    #[case::seq_num_occupied(-1, false, false)]
+    #[case::seq_num_skipped(1, false, false)]
+    #[case::document_deleted(0, true, false)]
+    #[tokio::test]
+    async fn validates_without_persisting(
+        schema: Schema,
+        #[case] seq_num_offset: i64,
+        #[case] with_delete: bool,
+        #[case] is_valid: bool,
+    ) {
+        let store = MemoryStore::default();
+        let config = PopulateStoreConfig {
+            no_of_entries: 3,
+            no_of_logs: 1,
+            no_of_public_keys: 1,
+            schema: schema.clone(),
+            with_delete,
+            ..PopulateStoreConfig::default()
+        };
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let key_pair = &key_pairs[0];
+        let public_key = key_pair.public_key();
+
+        let latest_entry = store
+            .get_latest_entry(&public_key, &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let operation = update_operation(
+            test_fields(),
+            latest_entry.hash().into(),
+            schema.id().to_owned(),
+        );
+        let seq_num =
+            SeqNum::new((latest_entry.seq_num().as_u64() as i64 + 1 + seq_num_offset) as u64)
+                .unwrap();
+        let skiplink = get_skiplink_for_entry(&store, &seq_num, &LogId::default(), &public_key)
+            .await
+            .unwrap_or(None);
+
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = sign_and_encode_entry(
+            &LogId::default(),
+            &seq_num,
+            skiplink.as_ref(),
+            Some(&latest_entry.hash()),
+            &encoded_operation,
+            key_pair,
+        )
+        .unwrap();
+        let plain_operation = decode_operation(&encoded_operation).unwrap();
+
+        let mut events = store.subscribe(EventFilter::new().public_key(&public_key));
+
+        let result = validate_publish(
+            &store,
+            &schema,
+            &encoded_entry,
+            &plain_operation,
+            &encoded_operation,
+        )
+        .await;
+        assert_eq!(result.is_ok(), is_valid);
+
+        // Nothing was written to the store or sent to subscribers.
+        assert_eq!(
+            store
+                .get_latest_entry(&public_key, &LogId::default())
+                .await
+                .unwrap()
+                .unwrap()
+                .hash(),
+            latest_entry.hash()
+        );
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(events.next().now_or_never().is_none());
+
+        // Publishing returns the same next args and agrees about the entry being valid.
+        let published = publish(
+            &store,
+            &schema,
+            &encoded_entry,
+            &plain_operation,
+            &encoded_operation,
+        )
+        .await;
+        assert_eq!(published.is_ok(), is_valid);
+
+        if let (Ok((document_id, next_args)), Ok(published_next_args)) = (result, published) {
+            assert_eq!(document_id, documents[0]);
+            assert_eq!(next_args, published_next_args);
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn validates_with_config(
+        #[from(populate_store_config)]
+        #[with(2, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let key_pair = &key_pairs[0];
+        let schema = &config.schema;
+        let public_key = key_pair.public_key();
+
+        let latest_entry = store
+            .get_latest_entry(&public_key, &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let operation = update_operation(
+            test_fields(),
+            latest_entry.hash().into(),
+            schema.id().to_owned(),
+        );
+        let (backlink, skiplink, seq_num, log_id) =
+            next_args(&store, &public_key, Some(&latest_entry.hash().into()))
+                .await
+                .unwrap();
+
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = sign_and_encode_entry(
+            &log_id,
+            &seq_num,
+            skiplink.as_ref(),
+            backlink.as_ref(),
+            &encoded_operation,
+            key_pair,
+        )
+        .unwrap();
+        let plain_operation = decode_operation(&encoded_operation).unwrap();
+
+        // The entry is valid without a config but exceeds the quota.
+        let publish_config = PublishConfig::new().quota(Quota::new().max_entries_per_log(2));
+
+        assert!(validate_publish(
+            &store,
+            schema,
+            &encoded_entry,
+            &plain_operation,
+            &encoded_operation,
+        )
+        .await
+        .is_ok());
+        assert!(matches!(
+            validate_publish_with_config(
+                &store,
+                &publish_config,
+                schema,
+                &encoded_entry,
+                &plain_operation,
+                &encoded_operation,
+            )
+            .await,
+            Err(DomainError::QuotaExceeded(QuotaLimit::EntriesPerLog, 2, 3))
+        ));
+
+        // Publishing with the same config agrees about the entry being rejected.
+        assert!(matches!(
+            publish_with_config(
+                &store,
+                &publish_config,
+                schema,
+                &encoded_entry,
+                &plain_operation,
+                &encoded_operation,
+            )
+            .await,
+            Err(DomainError::QuotaExceeded(QuotaLimit::EntriesPerLog, 2, 3))
+        ));
+    }
 }
@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::BTreeMap;
+
+use crate::api::DomainError;
+use crate::document::DocumentId;
+use crate::hash::HashId;
+use crate::operation::traits::AsOperation;
+use crate::operation::OperationId;
+use crate::schema::SchemaId;
+use crate::storage_provider::traits::{
+    DocumentStore, EntryStore, OperationStore, TransactionStore,
+};
+use crate::WithId;
+
+/// Purge the data of all deleted documents of a schema from the store.
+///
+/// Once a document received a DELETE operation it can't be changed anymore, it's operations are
+/// only kept to reproduce the document's history. This method removes the operations of all
+/// deleted documents following the passed schema, the payloads of the entries they arrived on and
+/// any stored document views.
+///
+/// The entries themselves are kept, they still contain the hash and size of their payload and
+/// make sure that the logs of all public keys stay verifiable. Other peers will also not be able
+/// to publish the purged entries again as their sequence numbers are already taken.
+///
+/// All data of one document is removed inside one transaction. Returns the ids of all purged
+/// documents.
+pub async fn purge_deleted_documents<S>(
+    store: &S,
+    schema_id: &SchemaId,
+) -> Result<Vec<DocumentId>, DomainError>
+where
+    S: EntryStore + OperationStore + DocumentStore + TransactionStore,
+{
+    // Collect the operation ids of all documents which contain a DELETE operation.
+    let mut operations_by_document: BTreeMap<DocumentId, Vec<OperationId>> = BTreeMap::new();
+    let mut deleted_documents: Vec<DocumentId> = Vec::new();
+
+    for operation in store.get_operations_by_schema_id(schema_id).await? {
+        let document_id = WithId::<DocumentId>::id(&operation).to_owned();
+
+        if operation.is_delete() {
+            deleted_documents.push(document_id.clone());
+        }
+
+        operations_by_document
+            .entry(document_id)
+            .or_default()
+            .push(WithId::<OperationId>::id(&operation).to_owned());
+    }
+
+    deleted_documents.sort();
+    deleted_documents.dedup();
+
+    for document_id in &deleted_documents {
+        // Unwrap as we collected operations for every deleted document above.
+        let operation_ids = operations_by_document.get(document_id).unwrap();
+
+        let transaction = store.begin_transaction().await?;
+
+        let result = purge_document(&transaction, document_id, operation_ids).await;
+
+        // Only commit when all removals succeeded, otherwise roll them back and return the error.
+        match result {
+            Ok(_) => store.commit_transaction(transaction).await?,
+            Err(err) => {
+                store.rollback_transaction(transaction).await?;
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(deleted_documents)
+}
+
+/// Remove the entry payloads, operations and stored views of one document.
+///
+/// This is expected to be called on a transaction, an error returned part way through must be
+/// followed by a rollback.
+async fn purge_document<S: EntryStore + OperationStore + DocumentStore>(
+    store: &S,
+    document_id: &DocumentId,
+    operation_ids: &[OperationId],
+) -> Result<(), DomainError> {
+    // The id of an operation is the hash of the entry it arrived on.
+    for operation_id in operation_ids {
+        store.remove_payload(operation_id.as_hash()).await?;
+    }
+
+    store.remove_operations_by_document_id(document_id).await?;
+    store.remove_document(document_id).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::document::traits::AsDocument;
+    use crate::document::DocumentViewId;
+    use crate::entry::traits::{AsEncodedEntry, AsEntry};
+    use crate::entry::LogId;
+    use crate::operation::traits::AsOperation;
+    use crate::operation::{OperationAction, OperationBuilder, OperationId};
+    use crate::storage_provider::traits::{DocumentStore, EntryStore, OperationStore};
+    use crate::test_utils::constants::test_fields;
+    use crate::test_utils::fixtures::populate_store_config;
+    use crate::test_utils::memory_store::helpers::{
+        populate_store, send_to_store, PopulateStoreConfig,
+    };
+    use crate::test_utils::memory_store::MemoryStore;
+    use crate::WithId;
+
+    use super::purge_deleted_documents;
+
+    #[rstest]
+    #[tokio::test]
+    async fn purges_deleted_documents(
+        #[from(populate_store_config)]
+        #[with(3, 2, 1, true)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, deleted_documents) = populate_store(&store, &config).await;
+        let key_pair = &key_pairs[0];
+        let public_key = key_pair.public_key();
+
+        // Store the materialised view of a deleted document before it was deleted.
+        let operations = store
+            .get_operations_by_document_id(&deleted_documents[0])
+            .await
+            .unwrap();
+        let create_operation = operations
+            .iter()
+            .find(|operation| operation.is_create())
+            .unwrap();
+        let view_id =
+            DocumentViewId::new(&[WithId::<OperationId>::id(create_operation).to_owned()]);
+        let document = store
+            .get_document_by_view_id(&view_id)
+            .await
+            .unwrap()
+            .unwrap();
+        store.insert_document(&document).await.unwrap();
+
+        // Publish another document which is not deleted.
+        let create_operation = OperationBuilder::new(config.schema.id())
+            .fields(&test_fields())
+            .build()
+            .unwrap();
+        let (encoded_entry, _) = send_to_store(&store, &create_operation, &config.schema, key_pair)
+            .await
+            .unwrap();
+
+        let purged_documents = purge_deleted_documents(&store, config.schema.id())
+            .await
+            .unwrap();
+        assert_eq!(purged_documents.len(), 2);
+        assert!(deleted_documents
+            .iter()
+            .all(|document_id| purged_documents.contains(document_id)));
+
+        for document_id in &deleted_documents {
+            assert!(store
+                .get_operations_by_document_id(document_id)
+                .await
+                .unwrap()
+                .is_empty());
+            assert!(store.get_document(document_id).await.unwrap().is_none());
+            assert!(store
+                .get_document_views(document_id)
+                .await
+                .unwrap()
+                .is_empty());
+        }
+
+        // All entries of the purged documents are still there, only their payloads are gone.
+        for log_id in [LogId::new(0), LogId::new(1)] {
+            let entries = store
+                .get_entries_from(&public_key, &log_id, &Default::default(), 10)
+                .await
+                .unwrap();
+            assert_eq!(entries.len(), 3);
+            assert!(entries.iter().all(|entry| entry.payload.is_none()));
+        }
+
+        // The document which wasn't deleted is untouched and can still be updated.
+        let entry = store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(entry.payload.is_some());
+        assert_eq!(entry.log_id(), &LogId::new(2));
+
+        let document = store
+            .get_document(&encoded_entry.hash().into())
+            .await
+            .unwrap()
+            .unwrap();
+        let update_operation = OperationBuilder::new(config.schema.id())
+            .action(OperationAction::Update)
+            .previous(document.view_id())
+            .fields(&test_fields())
+            .build()
+            .unwrap();
+        assert!(
+            send_to_store(&store, &update_operation, &config.schema, key_pair)
+                .await
+                .is_ok()
+        );
+
+        // Running it again doesn't find anything to purge.
+        assert!(purge_deleted_documents(&store, config.schema.id())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
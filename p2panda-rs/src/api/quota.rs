@@ -0,0 +1,391 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::fmt;
+
+use crate::api::DomainError;
+use crate::entry::traits::AsEntry;
+use crate::operation::traits::AsOperation;
+use crate::operation::EncodedOperation;
+use crate::storage_provider::traits::{EntryStore, OperationStore};
+
+/// Limits on the data a store accepts from `publish_with_config`.
+///
+/// Every limit is optional, a new quota doesn't limit anything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Quota {
+    max_entries_per_log: Option<u64>,
+    max_operation_bytes_per_public_key: Option<u64>,
+    max_documents_per_schema: Option<usize>,
+}
+
+impl Quota {
+    /// Returns a quota without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of entries in every log.
+    ///
+    /// Entries removed by pruning still count towards this limit as it's checked against the
+    /// sequence number of new entries.
+    pub fn max_entries_per_log(mut self, max: u64) -> Self {
+        self.max_entries_per_log = Some(max);
+        self
+    }
+
+    /// Limit the total size of all stored operations of a public key, in bytes.
+    ///
+    /// Only payloads which are still stored count towards this limit, pruned or purged payloads
+    /// don't.
+    pub fn max_operation_bytes_per_public_key(mut self, max: u64) -> Self {
+        self.max_operation_bytes_per_public_key = Some(max);
+        self
+    }
+
+    /// Limit the number of documents created with a schema.
+    ///
+    /// Only CREATE operations are affected by this limit, existing documents can still be updated
+    /// and deleted when it is reached.
+    pub fn max_documents_per_schema(mut self, max: usize) -> Self {
+        self.max_documents_per_schema = Some(max);
+        self
+    }
+}
+
+/// Kind of limit of a `Quota`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotaLimit {
+    /// Number of entries in a log.
+    EntriesPerLog,
+
+    /// Total size of all stored operations of a public key, in bytes.
+    OperationBytesPerPublicKey,
+
+    /// Number of documents created with a schema.
+    DocumentsPerSchema,
+}
+
+impl fmt::Display for QuotaLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit = match self {
+            QuotaLimit::EntriesPerLog => "entries per log",
+            QuotaLimit::OperationBytesPerPublicKey => "operation bytes per public key",
+            QuotaLimit::DocumentsPerSchema => "documents per schema",
+        };
+
+        write!(f, "{}", limit)
+    }
+}
+
+/// Returns an error if storing the passed entry and operation would exceed the quota.
+///
+/// Stored sizes and counts are read from the running totals of the store, the cost of this check
+/// doesn't grow with the amount of stored data.
+pub(crate) async fn check_quota<S: EntryStore + OperationStore>(
+    store: &S,
+    quota: &Quota,
+    entry: &impl AsEntry,
+    operation: &impl AsOperation,
+    encoded_operation: &EncodedOperation,
+) -> Result<(), DomainError> {
+    if let Some(max) = quota.max_entries_per_log {
+        // Logs start at sequence number 1, the sequence number of the new entry is the number of
+        // entries in the log after storing it.
+        let entries = entry.seq_num().as_u64();
+
+        if entries > max {
+            return Err(DomainError::QuotaExceeded(
+                QuotaLimit::EntriesPerLog,
+                max,
+                entries,
+            ));
+        }
+    }
+
+    if let Some(max) = quota.max_operation_bytes_per_public_key {
+        let bytes = store
+            .get_payload_size_by_public_key(entry.public_key())
+            .await?
+            .saturating_add(encoded_operation.size());
+
+        if bytes > max {
+            return Err(DomainError::QuotaExceeded(
+                QuotaLimit::OperationBytesPerPublicKey,
+                max,
+                bytes,
+            ));
+        }
+    }
+
+    if let Some(max) = quota.max_documents_per_schema {
+        if operation.is_create() {
+            let documents = store
+                .count_documents_by_schema_id(&operation.schema_id())
+                .await?
+                .saturating_add(1);
+
+            if documents > max {
+                return Err(DomainError::QuotaExceeded(
+                    QuotaLimit::DocumentsPerSchema,
+                    max as u64,
+                    documents as u64,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::api::{next_args, publish_with_config, DomainError, PublishConfig};
+    use crate::entry::encode::sign_and_encode_entry;
+    use crate::entry::traits::{AsEncodedEntry, AsEntry};
+    use crate::entry::{LogId, SeqNum};
+    use crate::identity::KeyPair;
+    use crate::operation::encode::encode_operation;
+    use crate::operation::traits::AsOperation;
+    use crate::operation::{OperationAction, OperationBuilder};
+    use crate::schema::Schema;
+    use crate::storage_provider::traits::EntryStore;
+    use crate::test_utils::constants::test_fields;
+    use crate::test_utils::fixtures::populate_store_config;
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::{Quota, QuotaLimit};
+
+    /// Publish an UPDATE to the latest entry of the first log or a new document if `create` is
+    /// set, enforcing the passed quota.
+    async fn publish_with_quota(
+        store: &MemoryStore,
+        schema: &Schema,
+        key_pair: &KeyPair,
+        create: bool,
+        quota: Quota,
+    ) -> Result<(), DomainError> {
+        let operation = match create {
+            true => OperationBuilder::new(schema.id())
+                .fields(&test_fields())
+                .build()
+                .unwrap(),
+            false => {
+                let latest_entry = store
+                    .get_latest_entry(&key_pair.public_key(), &LogId::default())
+                    .await
+                    .unwrap()
+                    .unwrap();
+
+                OperationBuilder::new(schema.id())
+                    .action(OperationAction::Update)
+                    .previous(&latest_entry.hash().into())
+                    .fields(&test_fields())
+                    .build()
+                    .unwrap()
+            }
+        };
+
+        let (backlink, skiplink, seq_num, log_id) =
+            next_args(store, &key_pair.public_key(), operation.previous().as_ref())
+                .await
+                .unwrap();
+        let encoded_operation = encode_operation(&operation).unwrap();
+        let encoded_entry = sign_and_encode_entry(
+            &log_id,
+            &seq_num,
+            skiplink.as_ref(),
+            backlink.as_ref(),
+            &encoded_operation,
+            key_pair,
+        )
+        .unwrap();
+
+        publish_with_config(
+            store,
+            &PublishConfig::new().quota(quota),
+            schema,
+            &encoded_entry,
+            &(&operation).into(),
+            &encoded_operation,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    #[rstest]
+    #[case::below_limit(Quota::new().max_entries_per_log(4), true)]
+    #[case::at_limit(Quota::new().max_entries_per_log(3), false)]
+    #[case::no_limit(Quota::new(), true)]
+    #[tokio::test]
+    async fn limits_entries_per_log(
+        #[case] quota: Quota,
+        #[case] is_accepted: bool,
+        #[from(populate_store_config)]
+        #[with(3, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        let result = publish_with_quota(&store, &config.schema, &key_pairs[0], false, quota).await;
+        assert_eq!(result.is_ok(), is_accepted);
+
+        // Rejected entries are not stored.
+        let expected_seq_num = if is_accepted { 4 } else { 3 };
+        let latest_entry = store
+            .get_latest_entry(&public_key, &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            latest_entry.seq_num(),
+            &SeqNum::new(expected_seq_num).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case::at_limit(0, true)]
+    #[case::above_limit(1, false)]
+    #[tokio::test]
+    async fn limits_operation_bytes_per_public_key(
+        #[case] bytes_below_limit: u64,
+        #[case] is_accepted: bool,
+        #[from(populate_store_config)]
+        #[with(2, 2, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        // The next UPDATE operation has the same size as the latest one.
+        let stored_bytes = store
+            .get_payload_size_by_public_key(&public_key)
+            .await
+            .unwrap();
+        let operation_size = store
+            .get_latest_entry(&public_key, &LogId::default())
+            .await
+            .unwrap()
+            .unwrap()
+            .payload_size();
+
+        let quota = Quota::new()
+            .max_operation_bytes_per_public_key(stored_bytes + operation_size - bytes_below_limit);
+        let result = publish_with_quota(&store, &config.schema, &key_pairs[0], false, quota).await;
+        assert_eq!(result.is_ok(), is_accepted);
+        if !is_accepted {
+            assert!(matches!(
+                result,
+                Err(DomainError::QuotaExceeded(
+                    QuotaLimit::OperationBytesPerPublicKey,
+                    max,
+                    bytes
+                )) if bytes == max + 1
+            ));
+        }
+
+        // Other public keys are not affected.
+        let quota = Quota::new().max_operation_bytes_per_public_key(stored_bytes);
+        assert!(
+            publish_with_quota(&store, &config.schema, &KeyPair::new(), true, quota)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn limits_documents_per_schema(
+        #[from(populate_store_config)]
+        #[with(2, 2, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let key_pair = &key_pairs[0];
+
+        let quota = Quota::new().max_documents_per_schema(2);
+        let result =
+            publish_with_quota(&store, &config.schema, key_pair, true, quota.clone()).await;
+        assert!(matches!(
+            result,
+            Err(DomainError::QuotaExceeded(
+                QuotaLimit::DocumentsPerSchema,
+                2,
+                3
+            ))
+        ));
+
+        // Existing documents can still be updated.
+        assert!(
+            publish_with_quota(&store, &config.schema, key_pair, false, quota)
+                .await
+                .is_ok()
+        );
+
+        let quota = Quota::new().max_documents_per_schema(3);
+        assert!(
+            publish_with_quota(&store, &config.schema, key_pair, true, quota)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn counts_only_stored_payloads(
+        #[from(populate_store_config)]
+        #[with(3, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        let entries = store
+            .get_entries_from(&public_key, &LogId::default(), &SeqNum::default(), 10)
+            .await
+            .unwrap();
+        let total_size: u64 = entries.iter().map(|entry| entry.payload_size()).sum();
+        assert_eq!(
+            store
+                .get_payload_size_by_public_key(&public_key)
+                .await
+                .unwrap(),
+            total_size
+        );
+
+        // Removed payloads don't count towards the stored bytes anymore.
+        store.remove_payload(&entries[0].hash()).await.unwrap();
+        assert_eq!(
+            store
+                .get_payload_size_by_public_key(&public_key)
+                .await
+                .unwrap(),
+            total_size - entries[0].payload_size()
+        );
+
+        // Removed entries neither, removing an entry without payload changes nothing.
+        store.remove_entry(&entries[0].hash()).await.unwrap();
+        store.remove_entry(&entries[1].hash()).await.unwrap();
+        assert_eq!(
+            store
+                .get_payload_size_by_public_key(&public_key)
+                .await
+                .unwrap(),
+            entries[2].payload_size()
+        );
+        assert_eq!(
+            store
+                .get_payload_size_by_public_key(&KeyPair::new().public_key())
+                .await
+                .unwrap(),
+            0
+        );
+    }
+}
@@ -98,7 +98,8 @@ pub async fn verify_log_id<S: LogStore + EntryStore>(
 ///
 /// An error is returned if:
 /// - seq num 1 was passed in, which can not have a skiplink
-/// - the expected skiplink target could not be found in the database.
+/// - the expected skiplink target could not be found in the database, a distinct error is
+///   returned if it was removed when pruning the log.
 pub async fn get_expected_skiplink<S: EntryStore>(
     store: &S,
     public_key: &PublicKey,
@@ -116,9 +117,21 @@ pub async fn get_expected_skiplink<S: EntryStore>(
         .get_entry_at_seq_num(public_key, log_id, &skiplink_seq_num)
         .await?;
 
-    match skiplink_entry {
-        Some(entry) => Ok(entry),
-        None => Err(ValidationError::ExpectedSkiplinkNotFound(
+    if let Some(entry) = skiplink_entry {
+        return Ok(entry);
+    }
+
+    let pruned_seq_num = store.get_pruned_seq_num(public_key, log_id).await?;
+
+    match pruned_seq_num {
+        Some(pruned_seq_num) if skiplink_seq_num.as_u64() < pruned_seq_num.as_u64() => {
+            Err(ValidationError::ExpectedSkiplinkPruned(
+                public_key.to_string(),
+                log_id.as_u64(),
+                skiplink_seq_num.as_u64(),
+            ))
+        }
+        _ => Err(ValidationError::ExpectedSkiplinkNotFound(
             public_key.to_string(),
             log_id.as_u64(),
             skiplink_seq_num.as_u64(),
@@ -1,17 +1,22 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt::{Debug, Display};
 
 use crate::document::error::{DocumentBuilderError, DocumentReducerError};
-use crate::document::traits::AsDocument;
-use crate::document::{DocumentId, DocumentViewFields, DocumentViewId};
+use crate::document::merge::{has_text, FieldMerger};
+use crate::document::traits::{commit_last_write_wins, AsDocument};
+use crate::document::{
+    ConflictReport, DocumentDiff, DocumentId, DocumentViewFields, DocumentViewId,
+};
+use crate::graph::error::GraphError;
 use crate::graph::{Graph, Reducer};
 use crate::hash::HashId;
 use crate::identity::PublicKey;
 use crate::operation::traits::{AsOperation, WithPublicKey};
 use crate::operation::{Operation, OperationId};
-use crate::schema::SchemaId;
+use crate::schema::{Schema, SchemaId};
 use crate::{Human, WithId};
 
 use super::error::DocumentError;
@@ -34,6 +39,9 @@ use super::error::DocumentError;
 /// method. Any operations committed in this way must refer to the documents current view id in
 /// their `previous` field.
 ///
+/// Operations which don't refer to the current view id, for example from concurrent branches, can
+/// be added to an already constructed document with `DocumentBuilder::build_incremental`.
+///
 /// See module docs for example uses.
 #[derive(Debug, Clone)]
 pub struct Document {
@@ -51,6 +59,55 @@ pub struct Document {
 
     /// The public key of the author who created this document.
     author: PublicKey,
+
+    /// Merges the values written to collaborative text fields and fields with merge strategies
+    /// when operations are committed.
+    merger: FieldMerger,
+}
+
+impl Document {
+    /// Returns a document from it's already materialised state, for example when it was read
+    /// from a store.
+    #[cfg(any(feature = "test-utils", feature = "sqlite-store", test))]
+    pub(crate) fn new(
+        id: &DocumentId,
+        fields: Option<&DocumentViewFields>,
+        schema_id: &SchemaId,
+        view_id: &DocumentViewId,
+        author: &PublicKey,
+    ) -> Self {
+        Self {
+            id: id.to_owned(),
+            fields: fields.cloned(),
+            schema_id: schema_id.to_owned(),
+            view_id: view_id.to_owned(),
+            author: author.to_owned(),
+            merger: FieldMerger::default(),
+        }
+    }
+
+    /// Returns mutable access to the fields of this document, `None` if it was deleted.
+    pub(crate) fn fields_mut(&mut self) -> Option<&mut DocumentViewFields> {
+        self.fields.as_mut()
+    }
+
+    /// Returns true if fields of this document are merged with other strategies than
+    /// last-write-wins.
+    pub(crate) fn has_merge_strategies(&self) -> bool {
+        self.merger.has_strategies()
+    }
+
+    /// Merge the values written by an operation into the fields of this document.
+    fn merge<T: AsOperation>(
+        &mut self,
+        operation_id: &OperationId,
+        operation: &T,
+        previous_fields: Option<&DocumentViewFields>,
+    ) {
+        let mut merger = std::mem::take(&mut self.merger);
+        merger.merge(operation_id, operation, previous_fields, self);
+        self.merger = merger;
+    }
 }
 
 impl AsDocument for Document {
@@ -84,6 +141,22 @@ impl AsDocument for Document {
         self.view_id = id.to_owned();
         self.fields = view.cloned();
     }
+
+    /// Commit a new operation to the document without performing any validation.
+    ///
+    /// Fields are written with last-write-wins semantics, values of collaborative text fields
+    /// and fields with merge strategies are merged afterwards. Only documents built with
+    /// `DocumentBuilder::with_schema` know about the merge strategies of their schema.
+    fn commit_unchecked<T: AsOperation>(&mut self, operation_id: &OperationId, operation: &T) {
+        // Keep the fields before the commit around, merge strategies need to know them.
+        let previous_fields = match self.merger.has_strategies() {
+            true => self.fields.clone(),
+            false => None,
+        };
+
+        commit_last_write_wins(self, operation_id, operation);
+        self.merge(operation_id, operation, previous_fields.as_ref());
+    }
 }
 
 impl Display for Document {
@@ -129,6 +202,9 @@ where
 #[derive(Debug, Default)]
 struct DocumentReducer {
     document: Option<Document>,
+
+    /// Merger for the document, it is handed to the document when it's created.
+    merger: FieldMerger,
 }
 
 /// Implementation of the `Reduce` trait for collections of authored operations.
@@ -141,7 +217,7 @@ impl Reducer<(OperationId, Operation, PublicKey)> for DocumentReducer {
         let (operation_id, operation, public_key) = value;
 
         // Get the current document.
-        let document = self.document.clone();
+        let document = self.document.take();
 
         match document {
             // If it has already been instantiated perform the commit.
@@ -165,6 +241,7 @@ impl Reducer<(OperationId, Operation, PublicKey)> for DocumentReducer {
                         err => Err(err),
                     },
                 }?;
+
                 // Set the updated document.
                 self.document = Some(document);
                 Ok(())
@@ -183,14 +260,17 @@ impl Reducer<(OperationId, Operation, PublicKey)> for DocumentReducer {
                 );
 
                 // Construct the document.
-                let document = Document {
+                let mut document = Document {
                     id: operation_id.as_hash().clone().into(),
                     fields: Some(document_fields),
                     schema_id: operation.schema_id(),
                     view_id: DocumentViewId::new(&[operation_id.to_owned()]),
                     author: public_key.to_owned(),
+                    merger: std::mem::take(&mut self.merger),
                 };
 
+                document.merge(operation_id, operation, None);
+
                 // Set the newly instantiated document.
                 self.document = Some(document);
                 Ok(())
@@ -202,19 +282,58 @@ impl Reducer<(OperationId, Operation, PublicKey)> for DocumentReducer {
 type PublishedOperation = (OperationId, Operation, PublicKey);
 type OperationGraph = Graph<OperationId, PublishedOperation>;
 
+/// Returns the ids of all operations no other operation refers to in it's `previous` field.
+fn graph_tips(operations: &[PublishedOperation]) -> Vec<OperationId> {
+    let previous: HashSet<OperationId> = operations
+        .iter()
+        .filter_map(|(_, operation, _)| operation.previous())
+        .flat_map(|previous| previous.graph_tips().to_vec())
+        .collect();
+
+    operations
+        .iter()
+        .map(|(id, _, _)| id)
+        .filter(|id| !previous.contains(id))
+        .cloned()
+        .collect()
+}
+
 /// A struct for building [documents][`Document`] from a collection of operations.
+///
+/// All fields are resolved with last-write-wins semantics, unless a schema with
+/// [merge strategies][`crate::schema::MergeStrategy`] is set with `with_schema`.
 #[derive(Debug, Clone)]
-pub struct DocumentBuilder(Vec<(OperationId, Operation, PublicKey)>);
+pub struct DocumentBuilder {
+    /// Unsorted operations of the document.
+    operations: Vec<(OperationId, Operation, PublicKey)>,
+
+    /// Schema of the document, used to look up the merge strategies of it's fields.
+    schema: Option<Schema>,
+}
 
 impl DocumentBuilder {
     /// Instantiate a new `DocumentBuilder` from a collection of operations.
     pub fn new(operations: Vec<(OperationId, Operation, PublicKey)>) -> Self {
-        Self(operations)
+        Self {
+            operations,
+            schema: None,
+        }
+    }
+
+    /// Set the schema of the document, fields are merged following it's merge strategies.
+    ///
+    /// Building fails if an operation doesn't follow this schema. Merge strategies are not part
+    /// of published schemas, documents built without this schema, for example by the document
+    /// stores, resolve all fields with last-write-wins. Operations committed to the built document
+    /// are merged following the same strategies.
+    pub fn with_schema(mut self, schema: &Schema) -> Self {
+        self.schema = Some(schema.to_owned());
+        self
     }
 
     /// Get all unsorted operations for this document.
     pub fn operations(&self) -> &Vec<PublishedOperation> {
-        &self.0
+        &self.operations
     }
 
     /// Validates all contained operations and builds the document.
@@ -231,6 +350,22 @@ impl DocumentBuilder {
         self.reduce_document(&mut graph)
     }
 
+    /// Validates all contained operations and builds the document, together with a report of
+    /// all fields with concurrent writes.
+    ///
+    /// Fields written on different branches of the document graph are resolved by picking the
+    /// value of the last operation in the sorted order, the report contains all competing values
+    /// and the chosen winner of every such field. Writes which were overwritten by a later
+    /// operation knowing about them are not in conflict anymore.
+    pub fn build_with_conflicts(
+        &self,
+    ) -> Result<(Document, Vec<PublishedOperation>, ConflictReport), DocumentBuilderError> {
+        let (document, sorted) = self.build()?;
+        let schema_fields = self.schema.as_ref().map(|schema| schema.fields());
+        let conflicts = ConflictReport::new(&sorted, schema_fields);
+        Ok((document, sorted, conflicts))
+    }
+
     /// Validates all contained operations and builds the document up to the
     /// requested [`DocumentViewId`].
     ///
@@ -251,6 +386,216 @@ impl DocumentBuilder {
         self.reduce_document(&mut graph)
     }
 
+    /// Validates all contained operations and returns the ids of all views the document passed
+    /// through, in causal order.
+    ///
+    /// Operations are visited in the same sorted order as in `build`, every operation leads to a
+    /// new view identified by the graph tips of all operations visited so far. The last view id
+    /// is the one of the document returned by `build`.
+    pub fn history(&self) -> Result<Vec<DocumentViewId>, DocumentBuilderError> {
+        let (_, sorted) = self.build()?;
+
+        let mut graph_tips: Vec<OperationId> = Vec::new();
+        let view_ids = sorted
+            .iter()
+            .map(|(id, operation, _)| {
+                // Operations this one refers to are not graph tips anymore.
+                if let Some(previous) = operation.previous() {
+                    graph_tips.retain(|tip| !previous.iter().any(|id| id == tip));
+                }
+                graph_tips.push(id.to_owned());
+
+                DocumentViewId::new(&graph_tips)
+            })
+            .collect();
+
+        Ok(view_ids)
+    }
+
+    /// Validates all contained operations and returns the changes of the document fields between
+    /// two of its views.
+    ///
+    /// Each changed field contains the values of both views, which hold the id of the operation
+    /// which wrote them. Fields removed by deleting the document contain the id of the DELETE
+    /// operation.
+    pub fn diff(
+        &self,
+        from: &DocumentViewId,
+        to: &DocumentViewId,
+    ) -> Result<DocumentDiff, DocumentBuilderError> {
+        let (from_document, _) = self.build_to_view_id(from.to_owned())?;
+        let (to_document, to_operations) = self.build_to_view_id(to.to_owned())?;
+
+        let delete_id = to_operations
+            .iter()
+            .find(|(_, operation, _)| operation.is_delete())
+            .map(|(id, _, _)| id);
+
+        Ok(DocumentDiff::new(
+            (from_document.view_id(), from_document.fields()),
+            (to_document.view_id(), to_document.fields()),
+            delete_id,
+        ))
+    }
+
+    /// Builds the document from an existing document and new operations, only sorting and
+    /// reducing the operations whose order can change.
+    ///
+    /// `document` and `sorted_operations` are the result of building the document before the
+    /// contained operations arrived, for example with `build`. Contained operations which are
+    /// already part of the existing document are ignored.
+    ///
+    /// New operations only change the sorted order from the first existing operation they follow
+    /// on. The order before it is checked to be the one `build` returns, but not sorted again.
+    /// Only the operations from that position on are sorted and reduced on top of the existing
+    /// document. This is possible as every field holds the value of the last operation in the
+    /// sorted order which wrote it, all values changed after that position are written again.
+    ///
+    /// The result is the same as returned by `build` for all operations. Deleted documents don't
+    /// contain the state before their DELETE operation and are always built from all operations,
+    /// as are documents with collaborative text fields or fields using merge strategies other
+    /// than last-write-wins.
+    ///
+    /// Returns an error if the sorted operations don't lead to the view of the existing document
+    /// or are not sorted like `build` does.
+    pub fn build_incremental(
+        &self,
+        document: &Document,
+        sorted_operations: &[PublishedOperation],
+    ) -> Result<(Document, Vec<PublishedOperation>), DocumentBuilderError> {
+        // The existing operations need to lead to the view of the existing document.
+        if &DocumentViewId::new(&graph_tips(sorted_operations)) != document.view_id() {
+            return Err(DocumentBuilderError::InvalidSortedOperations(
+                document.view_id().to_owned(),
+            ));
+        }
+
+        // Position of every existing operation in the sorted order.
+        let mut positions: HashMap<&OperationId, usize> = HashMap::new();
+        for (position, (id, _, _)) in sorted_operations.iter().enumerate() {
+            positions.entry(id).or_insert(position);
+        }
+
+        let new_operations: Vec<&PublishedOperation> = self
+            .operations
+            .iter()
+            .filter(|(id, _, _)| !positions.contains_key(id))
+            .collect();
+
+        // New operations need to follow the schema of the document, which already has it's
+        // CREATE operation.
+        if let Some(schema) = &self.schema {
+            if document.schema_id() != schema.id() {
+                return Err(DocumentBuilderError::InvalidSchemaId(
+                    sorted_operations[0].0.to_owned(),
+                    schema.id().to_owned(),
+                ));
+            }
+
+            if let Some((id, _, _)) = new_operations
+                .iter()
+                .find(|(_, operation, _)| &operation.schema_id() != schema.id())
+            {
+                return Err(DocumentBuilderError::InvalidSchemaId(
+                    id.to_owned(),
+                    schema.id().to_owned(),
+                ));
+            }
+        }
+
+        if new_operations
+            .iter()
+            .any(|(_, operation, _)| operation.is_create())
+        {
+            return Err(DocumentBuilderError::MultipleCreateOperations);
+        }
+
+        let has_merge_strategies = match &self.schema {
+            Some(schema) => schema.fields().has_merge_strategies(),
+            None => false,
+        };
+
+        let has_text_fields = sorted_operations
+            .iter()
+            .chain(new_operations.iter().copied())
+            .any(|(_, operation, _)| has_text(operation));
+
+        // The merge state of the existing document is the one after all of it's operations.
+        if document.is_deleted()
+            || document.has_merge_strategies()
+            || has_merge_strategies
+            || has_text_fields
+        {
+            let operations = sorted_operations
+                .iter()
+                .chain(new_operations.iter().copied())
+                .cloned()
+                .collect();
+
+            return Self {
+                operations,
+                schema: self.schema.clone(),
+            }
+            .build();
+        }
+
+        // Find the first existing operation which is followed by new operations, the walk over
+        // the graph only differs from there on.
+        let position = new_operations
+            .iter()
+            .filter_map(|(_, operation, _)| operation.previous())
+            .flat_map(|previous| {
+                previous
+                    .iter()
+                    .filter_map(|id| positions.get(id).copied())
+                    .collect::<Vec<usize>>()
+            })
+            .min();
+
+        let position = match (position, new_operations.first()) {
+            (Some(position), _) => position,
+            // Without new operations the existing document doesn't change.
+            (None, None) => return Ok((document.to_owned(), sorted_operations.to_vec())),
+            // New operations which don't follow any existing operation can't be connected to the
+            // document.
+            (None, Some((id, _, _))) => {
+                return Err(DocumentBuilderError::InvalidOperationLink(id.to_owned()))
+            }
+        };
+
+        let (visited, remaining) = sorted_operations.split_at(position);
+
+        // Walk the existing operations up to the first operation the walk will visit differently.
+        // New operations only follow operations from there on, so the walk up to there is the
+        // same and needs to have visited the operations before in their passed order.
+        let existing_graph = Self::construct_partial_graph(&[], sorted_operations.iter())?;
+        let (existing_sorted, state) = existing_graph.walk_until(&remaining[0].0)?;
+        if existing_sorted != visited {
+            return Err(GraphError::InvalidSortedNodes.into());
+        }
+
+        // Sort and reduce all operations from that position on.
+        let graph = Self::construct_partial_graph(
+            visited,
+            remaining.iter().chain(new_operations.iter().copied()),
+        )?;
+
+        let mut document_reducer = DocumentReducer {
+            document: match position {
+                0 => None,
+                _ => Some(document.to_owned()),
+            },
+            merger: FieldMerger::default(),
+        };
+        let graph_data = graph.walk_from_state(state, &mut document_reducer)?;
+
+        let sorted: Vec<PublishedOperation> =
+            visited.iter().cloned().chain(graph_data.sorted()).collect();
+        let graph_tips = graph_tips(&sorted);
+
+        Ok(Self::finish_document(document_reducer, &graph_tips, sorted))
+    }
+
     /// Construct the document graph.
     fn construct_graph(&self) -> Result<OperationGraph, DocumentBuilderError> {
         // Instantiate the graph.
@@ -259,7 +604,17 @@ impl DocumentBuilder {
         let mut create_seen = false;
 
         // Add all operations to the graph.
-        for (id, operation, public_key) in &self.0 {
+        for (id, operation, public_key) in &self.operations {
+            // All operations need to follow the schema if one was set.
+            if let Some(schema) = &self.schema {
+                if &operation.schema_id() != schema.id() {
+                    return Err(DocumentBuilderError::InvalidSchemaId(
+                        id.to_owned(),
+                        schema.id().to_owned(),
+                    ));
+                }
+            }
+
             // Check if this is a create operation and we already saw one, this should trigger an error.
             if operation.is_create() && create_seen {
                 return Err(DocumentBuilderError::MultipleCreateOperations);
@@ -274,7 +629,7 @@ impl DocumentBuilder {
         }
 
         // Add links between operations in the graph.
-        for (id, operation, _public_key) in &self.0 {
+        for (id, operation, _public_key) in &self.operations {
             if let Some(previous) = operation.previous() {
                 for previous in previous.iter() {
                     let success = graph.add_link(previous, id);
@@ -288,6 +643,50 @@ impl DocumentBuilder {
         Ok(graph)
     }
 
+    /// Construct the graph of operations following the already visited operations.
+    ///
+    /// Visited operations are only added to the graph when they are followed by one of the
+    /// passed operations, to link it to them.
+    fn construct_partial_graph<'a>(
+        visited: &[PublishedOperation],
+        operations: impl Iterator<Item = &'a PublishedOperation>,
+    ) -> Result<OperationGraph, DocumentBuilderError> {
+        let mut graph = Graph::new();
+
+        let mut operation_ids: HashSet<&OperationId> = HashSet::new();
+        let operations: Vec<&PublishedOperation> = operations
+            .filter(|(id, _, _)| operation_ids.insert(id))
+            .collect();
+
+        for operation in &operations {
+            graph.add_node(&operation.0, (*operation).clone());
+        }
+
+        let visited: HashMap<&OperationId, &PublishedOperation> = visited
+            .iter()
+            .map(|operation| (&operation.0, operation))
+            .collect();
+
+        for (id, operation, _public_key) in &operations {
+            if let Some(previous) = operation.previous() {
+                for previous in previous.iter() {
+                    if graph.get_node(previous).is_none() {
+                        if let Some(visited_operation) = visited.get(previous) {
+                            graph.add_node(previous, (*visited_operation).clone());
+                        }
+                    }
+
+                    let success = graph.add_link(previous, id);
+                    if !success {
+                        return Err(DocumentBuilderError::InvalidOperationLink(id.to_owned()));
+                    }
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+
     /// Traverse the graph, visiting operations in their topologically sorted order and reduce
     /// them into a single document.
     fn reduce_document(
@@ -298,23 +697,43 @@ impl DocumentBuilder {
         //
         // We pass in a DocumentReducer which will construct the document as nodes (which contain
         // operations) are visited.
-        let mut document_reducer = DocumentReducer::default();
+        let mut document_reducer = DocumentReducer {
+            document: None,
+            merger: match &self.schema {
+                Some(schema) => FieldMerger::new(schema.fields()),
+                None => FieldMerger::default(),
+            },
+        };
         let graph_data = graph.reduce(&mut document_reducer)?;
+
         let graph_tips: Vec<OperationId> = graph_data
             .current_graph_tips()
             .iter()
             .map(|(id, _, _)| id.to_owned())
             .collect();
 
+        Ok(Self::finish_document(
+            document_reducer,
+            &graph_tips,
+            graph_data.sorted(),
+        ))
+    }
+
+    /// Take the reduced document and set it's view id to the tips of the sorted graph.
+    fn finish_document(
+        document_reducer: DocumentReducer,
+        graph_tips: &[OperationId],
+        sorted: Vec<PublishedOperation>,
+    ) -> (Document, Vec<PublishedOperation>) {
         // Unwrap the document as if no error occurred it should be there.
         let mut document = document_reducer.document.unwrap();
 
         // One remaining task is to set the current document view id of the document. This is
         // required as the document reducer only knows about the operations it visits in their
         // already sorted order. It doesn't know about the state of the graphs tips.
-        document.view_id = DocumentViewId::new(&graph_tips);
+        document.view_id = DocumentViewId::new(graph_tips);
 
-        Ok((document, graph_data.sorted()))
+        (document, sorted)
     }
 }
 
@@ -334,7 +753,7 @@ where
             })
             .collect();
 
-        Self(operations)
+        Self::new(operations)
     }
 }
 
@@ -354,7 +773,7 @@ where
             })
             .collect();
 
-        Self(operations)
+        Self::new(operations)
     }
 }
 
@@ -366,22 +785,104 @@ mod tests {
 
     use crate::document::traits::AsDocument;
     use crate::document::{
-        Document, DocumentId, DocumentViewFields, DocumentViewId, DocumentViewValue,
+        Document, DocumentId, DocumentViewFields, DocumentViewId, DocumentViewValue, FieldChange,
     };
     use crate::entry::traits::AsEncodedEntry;
-    use crate::identity::KeyPair;
-    use crate::operation::{OperationAction, OperationBuilder, OperationId, OperationValue};
+    use crate::identity::{KeyPair, PublicKey};
+    use crate::operation::traits::AsOperation;
+    use crate::operation::{
+        Operation, OperationAction, OperationBuilder, OperationId, OperationValue, TextDelta,
+        TextEdit,
+    };
     use crate::schema::{FieldType, Schema, SchemaId, SchemaName};
     use crate::test_utils::constants::{self, PRIVATE_KEY};
     use crate::test_utils::fixtures::{
-        operation, operation_fields, published_operation, random_document_view_id,
-        random_operation_id, schema,
+        graph_operations, operation, operation_fields, published_operation,
+        random_document_view_id, random_operation_id, schema,
     };
     use crate::test_utils::memory_store::helpers::send_to_store;
     use crate::test_utils::memory_store::{MemoryStore, PublishedOperation};
     use crate::{Human, WithId};
 
-    use super::DocumentBuilder;
+    use crate::graph::error::GraphError;
+
+    use super::{DocumentBuilder, DocumentBuilderError};
+
+    /// Returns the operations of a document edited concurrently by two public keys, every
+    /// operation follows the operations in it's `previous` field.
+    ///
+    /// The operation ids are random, so concurrent branches are sorted differently every time.
+    fn concurrent_operations(with_delete: bool) -> Vec<(OperationId, Operation, PublicKey)> {
+        let panda = KeyPair::new().public_key();
+        let penguin = KeyPair::new().public_key();
+
+        // Index of the operations in `previous`, the public key and the written fields.
+        let graph: Vec<(Vec<usize>, PublicKey, Vec<&str>)> = vec![
+            (vec![], panda, vec!["name", "age"]),
+            (vec![0], panda, vec!["name"]),
+            (vec![0], penguin, vec!["age"]),
+            (vec![1], panda, vec!["name", "age"]),
+            (vec![2], penguin, vec!["name"]),
+            (vec![3, 4], panda, vec!["age"]),
+            (vec![5], penguin, vec!["name"]),
+            (vec![5], panda, vec!["name", "age"]),
+            (vec![6], penguin, vec!["age"]),
+        ];
+
+        let schema_id = constants::schema().id().to_owned();
+        let operation_graph = graph
+            .iter()
+            .enumerate()
+            .map(|(index, (previous, _, field_names))| {
+                let fields = field_names
+                    .iter()
+                    .map(|name| match *name {
+                        "age" => (*name, OperationValue::Integer(index as i64)),
+                        _ => (*name, OperationValue::String(format!("Panda {}", index))),
+                    })
+                    .collect();
+
+                (previous.to_owned(), fields)
+            })
+            .collect();
+
+        // Operations are published by the public keys given in the graph.
+        let mut operations = graph_operations(&schema_id, operation_graph);
+        for (operation, (_, public_key, _)) in operations.iter_mut().zip(graph) {
+            operation.2 = public_key;
+        }
+
+        if with_delete {
+            let tips: Vec<OperationId> = [7, 8]
+                .iter()
+                .map(|index| operations[*index].0.clone())
+                .collect();
+            let operation = OperationBuilder::new(&schema_id)
+                .action(OperationAction::Delete)
+                .previous(&DocumentViewId::new(&tips))
+                .build()
+                .unwrap();
+            operations.push((random_operation_id(), operation, panda));
+        }
+
+        operations
+    }
+
+    /// Assert that two built documents and their sorted operations are the same.
+    fn assert_same_build(
+        built: (Document, Vec<(OperationId, Operation, PublicKey)>),
+        expected: &(Document, Vec<(OperationId, Operation, PublicKey)>),
+    ) {
+        let (document, sorted) = built;
+        let (expected_document, expected_sorted) = expected;
+
+        assert_eq!(document.id(), expected_document.id());
+        assert_eq!(document.view_id(), expected_document.view_id());
+        assert_eq!(document.author(), expected_document.author());
+        assert_eq!(document.schema_id(), expected_document.schema_id());
+        assert_eq!(document.fields(), expected_document.fields());
+        assert_eq!(&sorted, expected_sorted);
+    }
 
     #[rstest]
     fn string_representation(#[from(published_operation)] operation: PublishedOperation) {
@@ -932,4 +1433,285 @@ mod tests {
             )
             .is_err());
     }
+
+    #[rstest]
+    #[case::without_delete(false)]
+    #[case::with_delete(true)]
+    fn incremental_build_matches_full_build(#[case] with_delete: bool) {
+        // Repeat with different operation ids and thus differently sorted branches.
+        for _ in 0..20 {
+            let operations = concurrent_operations(with_delete);
+            let builder = DocumentBuilder::new(operations.clone());
+            let expected = builder.build().unwrap();
+
+            for index in 1..operations.len() {
+                // Build the document from the operations which arrived so far.
+                let existing = DocumentBuilder::new(operations[..index].to_vec())
+                    .build()
+                    .unwrap();
+                assert_same_build(
+                    builder.build_incremental(&existing.0, &existing.1).unwrap(),
+                    &expected,
+                );
+
+                // Add one more operation to it, the builder only needs to contain new operations.
+                let next_builder = DocumentBuilder::new(vec![operations[index].clone()]);
+                assert_same_build(
+                    next_builder
+                        .build_incremental(&existing.0, &existing.1)
+                        .unwrap(),
+                    &DocumentBuilder::new(operations[..=index].to_vec())
+                        .build()
+                        .unwrap(),
+                );
+
+                // Start from an earlier view of the document.
+                let view_id = DocumentViewId::new(&[operations[index].0.clone()]);
+                let existing = builder.build_to_view_id(view_id).unwrap();
+                assert_same_build(
+                    builder.build_incremental(&existing.0, &existing.1).unwrap(),
+                    &expected,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_build_fails_like_full_build() {
+        let mut operations = concurrent_operations(true);
+        let existing = DocumentBuilder::new(operations.clone()).build().unwrap();
+
+        // Update the deleted document.
+        let delete_id = operations.last().unwrap().0.clone();
+        let operation = OperationBuilder::new(constants::schema().id())
+            .action(OperationAction::Update)
+            .previous(&DocumentViewId::new(&[delete_id]))
+            .fields(&[("name", OperationValue::String("Penguin".into()))])
+            .build()
+            .unwrap();
+        operations.push((
+            random_operation_id(),
+            operation,
+            KeyPair::new().public_key(),
+        ));
+
+        let builder = DocumentBuilder::new(operations);
+        assert!(builder.build().is_err());
+        assert!(builder.build_incremental(&existing.0, &existing.1).is_err());
+    }
+
+    #[test]
+    fn incremental_build_checks_existing_operations() {
+        let operations = concurrent_operations(false);
+        let (document, sorted) = DocumentBuilder::new(operations[..8].to_vec())
+            .build()
+            .unwrap();
+        let builder = DocumentBuilder::new(operations[8..].to_vec());
+
+        // The sorted operations are missing the last operation of the existing document.
+        assert!(matches!(
+            builder.build_incremental(&document, &sorted[..sorted.len() - 1]),
+            Err(DocumentBuilderError::InvalidSortedOperations(_))
+        ));
+
+        // The sorted operations are not in the order they were sorted in.
+        let mut unsorted = sorted.clone();
+        unsorted.swap(1, 2);
+        assert!(matches!(
+            builder.build_incremental(&document, &unsorted),
+            Err(DocumentBuilderError::GraphSortingError(
+                GraphError::InvalidSortedNodes
+            ))
+        ));
+
+        // New operations need to be connected to the existing ones, the last one follows an
+        // operation which is missing.
+        let (document, sorted) = DocumentBuilder::new(operations[..6].to_vec())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            DocumentBuilder::new(operations[8..].to_vec()).build_incremental(&document, &sorted),
+            Err(DocumentBuilderError::InvalidOperationLink(_))
+        ));
+        assert!(DocumentBuilder::new(operations[6..].to_vec())
+            .build_incremental(&document, &sorted)
+            .is_ok());
+    }
+
+    #[rstest]
+    #[case::without_delete(false)]
+    #[case::with_delete(true)]
+    fn history(#[case] with_delete: bool) {
+        let builder = DocumentBuilder::new(concurrent_operations(with_delete));
+        let (document, sorted) = builder.build().unwrap();
+        let history = builder.history().unwrap();
+
+        assert_eq!(history.len(), sorted.len());
+        assert_eq!(history[0], DocumentViewId::new(&[sorted[0].0.clone()]));
+        assert_eq!(history.last(), Some(document.view_id()));
+
+        // Every view only contains operations visited before.
+        for (index, view_id) in history.iter().enumerate() {
+            assert!(view_id.iter().all(|id| sorted[..=index]
+                .iter()
+                .any(|(sorted_id, _, _)| sorted_id == id)));
+        }
+    }
+
+    #[test]
+    fn diff_between_views() {
+        let builder = DocumentBuilder::new(concurrent_operations(true));
+        let (_, sorted) = builder.build().unwrap();
+        let history = builder.history().unwrap();
+
+        // All changes between two consecutive views are caused by the visited operation.
+        for (index, views) in history.windows(2).enumerate() {
+            let diff = builder.diff(&views[0], &views[1]).unwrap();
+            let (operation_id, operation, _) = &sorted[index + 1];
+
+            assert_eq!(diff.from_view_id(), &views[0]);
+            assert_eq!(diff.to_view_id(), &views[1]);
+
+            for (name, change) in diff.iter() {
+                match operation.fields() {
+                    Some(fields) => {
+                        assert!(fields.get(name).is_some());
+                        assert!(matches!(change, FieldChange::Changed(..)));
+                    }
+                    // The last operation deletes the document.
+                    None => assert!(matches!(change, FieldChange::Removed(..))),
+                }
+
+                assert_eq!(change.operation_id(), operation_id);
+            }
+        }
+
+        // Changes between the first and the last view before the deletion refer to the
+        // operations which wrote the current values.
+        let last_view = &history[history.len() - 2];
+        let diff = builder.diff(&history[0], last_view).unwrap();
+        let (document, _) = builder.build_to_view_id(last_view.to_owned()).unwrap();
+
+        for name in ["name", "age"] {
+            assert_eq!(
+                diff.get(name).unwrap().operation_id(),
+                document.fields().unwrap().get(name).unwrap().id()
+            );
+        }
+
+        assert!(builder.diff(&history[0], &history[0]).unwrap().is_empty());
+    }
+
+    #[rstest]
+    #[case::without_delete(false)]
+    #[case::with_delete(true)]
+    fn build_with_conflicts(#[case] with_delete: bool) {
+        let operations = concurrent_operations(with_delete);
+        let builder = DocumentBuilder::new(operations.clone());
+        let (document, sorted, conflicts) = builder.build_with_conflicts().unwrap();
+        assert_same_build((document.clone(), sorted), &builder.build().unwrap());
+
+        let fields = match document.fields() {
+            Some(fields) => fields,
+            None => {
+                assert!(conflicts.is_empty());
+                return;
+            }
+        };
+
+        // Both fields were written on the branches following the last merge.
+        let expected = [("name", [6, 7]), ("age", [7, 8])];
+        assert_eq!(conflicts.len(), expected.len());
+
+        for (name, indexes) in expected {
+            let conflict = conflicts.get(name).unwrap();
+            assert_eq!(conflict.winner(), fields.get(name).unwrap());
+            assert_eq!(conflict.losers().len(), 1);
+
+            let mut operation_ids = conflict.operation_ids();
+            operation_ids.sort();
+            let mut expected_ids: Vec<&OperationId> =
+                indexes.iter().map(|index| &operations[*index].0).collect();
+            expected_ids.sort();
+            assert_eq!(operation_ids, expected_ids);
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn collaborative_text(
+        #[with(vec![("text".to_string(), FieldType::Text)])] schema: Schema,
+    ) {
+        let panda = KeyPair::new();
+        let penguin = KeyPair::new();
+        let store = MemoryStore::default();
+
+        let create_operation = OperationBuilder::new(schema.id())
+            .fields(&[("text", TextDelta::from("Hello Panda").into())])
+            .build()
+            .unwrap();
+        let (create_entry, _) = send_to_store(&store, &create_operation, &schema, &panda)
+            .await
+            .unwrap();
+        let create_id: OperationId = create_entry.hash().into();
+
+        // Panda and penguin edit the text concurrently.
+        let panda_operation = OperationBuilder::new(schema.id())
+            .action(OperationAction::Update)
+            .previous(&DocumentViewId::from(create_id.clone()))
+            .fields(&[("text", TextDelta::from(TextEdit::new(0, 5, "Hi")).into())])
+            .build()
+            .unwrap();
+        let (panda_entry, _) = send_to_store(&store, &panda_operation, &schema, &panda)
+            .await
+            .unwrap();
+
+        let penguin_operation = OperationBuilder::new(schema.id())
+            .action(OperationAction::Update)
+            .previous(&DocumentViewId::from(create_id.clone()))
+            .fields(&[("text", TextDelta::from(TextEdit::insert(11, "s")).into())])
+            .build()
+            .unwrap();
+        let (penguin_entry, _) = send_to_store(&store, &penguin_operation, &schema, &penguin)
+            .await
+            .unwrap();
+
+        // Operations referring to the current view are applied to the text directly.
+        let mut document: Document = vec![&store.operations.lock().unwrap()[&create_id]]
+            .try_into()
+            .unwrap();
+        document
+            .commit(&panda_entry.hash().into(), &panda_operation)
+            .unwrap();
+        assert_eq!(
+            document.get("text"),
+            Some(&OperationValue::String("Hi Panda".into()))
+        );
+
+        // Panda merges both edits.
+        let merge_operation = OperationBuilder::new(schema.id())
+            .action(OperationAction::Update)
+            .previous(&DocumentViewId::new(&[
+                panda_entry.hash().into(),
+                penguin_entry.hash().into(),
+            ]))
+            .fields(&[("text", TextDelta::from(TextEdit::insert(9, "!")).into())])
+            .build()
+            .unwrap();
+        let (merge_entry, _) = send_to_store(&store, &merge_operation, &schema, &panda)
+            .await
+            .unwrap();
+
+        let operations = store.operations.lock().unwrap();
+        let operations = operations.values().collect::<Vec<&PublishedOperation>>();
+        let document = Document::try_from(operations).unwrap();
+
+        assert_eq!(
+            document.fields().unwrap().get("text"),
+            Some(&DocumentViewValue::new(
+                &merge_entry.hash().into(),
+                &OperationValue::String("Hi Pandas!".into())
+            ))
+        );
+    }
 }
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::btree_map::Iter;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::document::merge::causal_past;
+use crate::document::DocumentViewValue;
+use crate::identity::PublicKey;
+use crate::operation::traits::AsOperation;
+use crate::operation::{Operation, OperationId, OperationValue};
+use crate::schema::{MergeStrategy, SchemaFields};
+
+/// Concurrent writes to a document field, one of them was chosen as the value of the field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldConflict {
+    winner: DocumentViewValue,
+    losers: Vec<DocumentViewValue>,
+}
+
+impl FieldConflict {
+    /// Returns the value which was chosen for the field, together with the operation which wrote
+    /// it.
+    pub fn winner(&self) -> &DocumentViewValue {
+        &self.winner
+    }
+
+    /// Returns the values which lost against the chosen one, in their sorted order.
+    pub fn losers(&self) -> &[DocumentViewValue] {
+        self.losers.as_slice()
+    }
+
+    /// Returns the ids of all competing operations, the winning one last.
+    pub fn operation_ids(&self) -> Vec<&OperationId> {
+        self.losers
+            .iter()
+            .chain(std::iter::once(&self.winner))
+            .map(|value| value.id())
+            .collect()
+    }
+}
+
+/// Fields of a document with concurrent writes which were resolved with last-write-wins.
+///
+/// A field is in conflict if operations on different branches of the document graph wrote to it
+/// and no later operation knowing about all of them wrote it again. The value of the last of
+/// these operations in the sorted graph wins, the others are reported as losers.
+///
+/// Collaborative text fields and fields with other merge strategies than last-write-wins combine
+/// concurrent writes and never conflict, neither do fields of deleted documents.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConflictReport {
+    conflicts: BTreeMap<String, FieldConflict>,
+}
+
+impl ConflictReport {
+    /// Returns the conflicts of a document built from the passed topologically sorted operations.
+    ///
+    /// `schema_fields` are used to look up the merge strategies of the fields, all fields use
+    /// last-write-wins if they are `None`.
+    pub(crate) fn new(
+        sorted_operations: &[(OperationId, Operation, PublicKey)],
+        schema_fields: Option<&SchemaFields>,
+    ) -> Self {
+        let is_deleted = sorted_operations
+            .iter()
+            .any(|(_, operation, _)| operation.is_delete());
+
+        if is_deleted {
+            return Self::default();
+        }
+
+        let mut ancestors = HashMap::new();
+
+        // Writes to every field which were not overwritten by an operation knowing about them.
+        let mut heads: BTreeMap<String, Vec<DocumentViewValue>> = BTreeMap::new();
+
+        for (operation_id, operation, _) in sorted_operations {
+            let operation_ancestors = causal_past(&ancestors, operation);
+
+            if let Some(fields) = operation.fields() {
+                for (name, value) in fields.iter() {
+                    if let OperationValue::Text(_) = value {
+                        continue;
+                    }
+
+                    if let Some(schema_fields) = schema_fields {
+                        if schema_fields.merge_strategy(name) != MergeStrategy::LastWriteWins {
+                            continue;
+                        }
+                    }
+
+                    let writes = heads.entry(name.to_owned()).or_default();
+                    writes.retain(|write| !operation_ancestors.contains(write.id()));
+                    writes.push(DocumentViewValue::new(operation_id, value));
+                }
+            }
+
+            ancestors.insert(operation_id.to_owned(), operation_ancestors);
+        }
+
+        let conflicts = heads
+            .into_iter()
+            .filter(|(_, writes)| writes.len() > 1)
+            .map(|(name, mut losers)| {
+                // Writes are in their sorted order, the last one wins.
+                let winner = losers.pop().unwrap();
+                (name, FieldConflict { winner, losers })
+            })
+            .collect();
+
+        Self { conflicts }
+    }
+
+    /// Returns the conflict of a field, `None` if it has no concurrent writes.
+    pub fn get(&self, name: &str) -> Option<&FieldConflict> {
+        self.conflicts.get(name)
+    }
+
+    /// Returns the number of fields in conflict.
+    pub fn len(&self) -> usize {
+        self.conflicts.len()
+    }
+
+    /// Returns true if no field is in conflict.
+    pub fn is_empty(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Returns an iterator of all fields in conflict.
+    pub fn iter(&self) -> Iter<'_, String, FieldConflict> {
+        self.conflicts.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::document::{DocumentViewId, DocumentViewValue};
+    use crate::identity::KeyPair;
+    use crate::operation::{OperationAction, OperationBuilder};
+    use crate::schema::{FieldType, MergeStrategy, Schema};
+    use crate::test_utils::fixtures::{graph_operations, random_operation_id, schema};
+
+    use super::ConflictReport;
+
+    #[rstest]
+    fn concurrent_writes(
+        #[with(vec![
+            ("name".to_string(), FieldType::String),
+            ("age".to_string(), FieldType::Integer),
+            ("visits".to_string(), FieldType::Integer),
+        ])]
+        schema: Schema,
+    ) {
+        let operations = graph_operations(
+            schema.id(),
+            vec![
+                (
+                    vec![],
+                    vec![
+                        ("name", "Panda".into()),
+                        ("age", 12.into()),
+                        ("visits", 0.into()),
+                    ],
+                ),
+                (
+                    vec![0],
+                    vec![("name", "Penguin".into()), ("visits", 1.into())],
+                ),
+                (
+                    vec![0],
+                    vec![
+                        ("name", "Polar Bear".into()),
+                        ("age", 13.into()),
+                        ("visits", 1.into()),
+                    ],
+                ),
+                (vec![1], vec![("visits", 2.into())]),
+                // Writing the age again after the merge doesn't conflict.
+                (vec![2, 3], vec![("age", 14.into())]),
+            ],
+        );
+
+        let report = ConflictReport::new(&operations, None);
+        assert_eq!(report.len(), 2);
+        assert_eq!(report.get("age"), None);
+
+        // The last concurrent write in the sorted order wins.
+        let name = report.get("name").unwrap();
+        assert_eq!(
+            name.winner(),
+            &DocumentViewValue::new(&operations[2].0, &"Polar Bear".into())
+        );
+        assert_eq!(
+            name.losers(),
+            &[DocumentViewValue::new(&operations[1].0, &"Penguin".into())]
+        );
+        assert_eq!(
+            name.operation_ids(),
+            vec![&operations[1].0, &operations[2].0]
+        );
+
+        // The write of the first branch was overwritten on it.
+        let visits = report.get("visits").unwrap();
+        assert_eq!(
+            visits.operation_ids(),
+            vec![&operations[2].0, &operations[3].0]
+        );
+
+        // Counters merge concurrent writes.
+        let schema = schema
+            .with_merge_strategy("visits", MergeStrategy::Counter)
+            .unwrap();
+        let report = ConflictReport::new(&operations, Some(schema.fields()));
+        assert_eq!(report.len(), 1);
+        assert!(report.get("visits").is_none());
+    }
+
+    #[rstest]
+    fn deleted_document(#[with(vec![("name".to_string(), FieldType::String)])] schema: Schema) {
+        let mut operations = graph_operations(
+            schema.id(),
+            vec![
+                (vec![], vec![("name", "Panda".into())]),
+                (vec![0], vec![("name", "Penguin".into())]),
+                (vec![0], vec![("name", "Polar Bear".into())]),
+            ],
+        );
+        assert_eq!(ConflictReport::new(&operations, None).len(), 1);
+
+        let previous = DocumentViewId::new(&[operations[1].0.clone(), operations[2].0.clone()]);
+        let operation = OperationBuilder::new(schema.id())
+            .action(OperationAction::Delete)
+            .previous(&previous)
+            .build()
+            .unwrap();
+        operations.push((
+            random_operation_id(),
+            operation,
+            KeyPair::new().public_key(),
+        ));
+
+        assert!(ConflictReport::new(&operations, None).is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::btree_map::Iter;
+use std::collections::BTreeMap;
+
+use crate::document::{DocumentViewFields, DocumentViewId, DocumentViewValue};
+use crate::operation::OperationId;
+
+/// Change of a single field between two document views.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldChange {
+    /// The field has a value in the second view but not in the first one.
+    Added(DocumentViewValue),
+
+    /// The field has a different value in the second view, contains the previous and the new
+    /// value.
+    Changed(DocumentViewValue, DocumentViewValue),
+
+    /// The field has a value in the first view but not in the second one, contains the previous
+    /// value and the id of the DELETE operation which removed it.
+    Removed(DocumentViewValue, OperationId),
+}
+
+impl FieldChange {
+    /// Returns the id of the operation which caused the change.
+    ///
+    /// This is the operation which wrote the value of the field in the second view or the DELETE
+    /// operation which removed it.
+    pub fn operation_id(&self) -> &OperationId {
+        match self {
+            FieldChange::Added(value) | FieldChange::Changed(_, value) => value.id(),
+            FieldChange::Removed(_, operation_id) => operation_id,
+        }
+    }
+}
+
+/// Changes to the fields of a document between two of its views.
+///
+/// Fields are compared by their values, a field which was written again with the same value is
+/// not changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentDiff {
+    from: DocumentViewId,
+    to: DocumentViewId,
+    changes: BTreeMap<String, FieldChange>,
+}
+
+impl DocumentDiff {
+    /// Returns the changes between the fields of two document views, `None` are the fields of a
+    /// deleted document.
+    ///
+    /// `delete_id` is the id of the DELETE operation when the second view is of a deleted
+    /// document, all fields of the first view are removed by it.
+    pub(crate) fn new(
+        from: (&DocumentViewId, Option<&DocumentViewFields>),
+        to: (&DocumentViewId, Option<&DocumentViewFields>),
+        delete_id: Option<&OperationId>,
+    ) -> Self {
+        let (from_view_id, from_fields) = from;
+        let (to_view_id, to_fields) = to;
+
+        let mut changes = BTreeMap::new();
+
+        if let Some(to_fields) = to_fields {
+            for (name, value) in to_fields.iter() {
+                match from_fields.and_then(|fields| fields.get(name)) {
+                    Some(previous) if previous.value() == value.value() => (),
+                    Some(previous) => {
+                        changes.insert(
+                            name.to_owned(),
+                            FieldChange::Changed(previous.to_owned(), value.to_owned()),
+                        );
+                    }
+                    None => {
+                        changes.insert(name.to_owned(), FieldChange::Added(value.to_owned()));
+                    }
+                }
+            }
+        }
+
+        // Fields are only removed by deleting the document.
+        if let (Some(from_fields), None, Some(delete_id)) = (from_fields, to_fields, delete_id) {
+            for (name, previous) in from_fields.iter() {
+                changes.insert(
+                    name.to_owned(),
+                    FieldChange::Removed(previous.to_owned(), delete_id.to_owned()),
+                );
+            }
+        }
+
+        Self {
+            from: from_view_id.to_owned(),
+            to: to_view_id.to_owned(),
+            changes,
+        }
+    }
+
+    /// Returns the id of the first compared document view.
+    pub fn from_view_id(&self) -> &DocumentViewId {
+        &self.from
+    }
+
+    /// Returns the id of the second compared document view.
+    pub fn to_view_id(&self) -> &DocumentViewId {
+        &self.to
+    }
+
+    /// Returns the change of a field, `None` if it didn't change.
+    pub fn get(&self, name: &str) -> Option<&FieldChange> {
+        self.changes.get(name)
+    }
+
+    /// Returns the number of changed fields.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns true if no field changed.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns an iterator of all changed fields.
+    pub fn iter(&self) -> Iter<'_, String, FieldChange> {
+        self.changes.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::document::{DocumentViewFields, DocumentViewId, DocumentViewValue};
+    use crate::operation::{OperationId, OperationValue};
+    use crate::test_utils::fixtures::{random_document_view_id, random_operation_id};
+
+    use super::{DocumentDiff, FieldChange};
+
+    #[rstest]
+    fn field_changes(
+        #[from(random_document_view_id)] from_view_id: DocumentViewId,
+        #[from(random_document_view_id)] to_view_id: DocumentViewId,
+        #[from(random_operation_id)] create_id: OperationId,
+        #[from(random_operation_id)] update_id: OperationId,
+    ) {
+        let mut from_fields = DocumentViewFields::new();
+        from_fields.insert("name", DocumentViewValue::new(&create_id, &"Panda".into()));
+        from_fields.insert("age", DocumentViewValue::new(&create_id, &12.into()));
+        from_fields.insert("cute", DocumentViewValue::new(&create_id, &true.into()));
+
+        let mut to_fields = DocumentViewFields::new();
+        to_fields.insert(
+            "name",
+            DocumentViewValue::new(&update_id, &"Penguin".into()),
+        );
+        to_fields.insert("age", DocumentViewValue::new(&update_id, &12.into()));
+        to_fields.insert("cute", DocumentViewValue::new(&create_id, &true.into()));
+        to_fields.insert("height", DocumentViewValue::new(&update_id, &1.2.into()));
+
+        let diff = DocumentDiff::new(
+            (&from_view_id, Some(&from_fields)),
+            (&to_view_id, Some(&to_fields)),
+            None,
+        );
+
+        assert_eq!(diff.from_view_id(), &from_view_id);
+        assert_eq!(diff.to_view_id(), &to_view_id);
+        assert_eq!(diff.len(), 2);
+
+        // Writing the same value again is not a change.
+        assert_eq!(diff.get("age"), None);
+        assert_eq!(diff.get("cute"), None);
+
+        let name = diff.get("name").unwrap();
+        assert_eq!(
+            name,
+            &FieldChange::Changed(
+                DocumentViewValue::new(&create_id, &"Panda".into()),
+                DocumentViewValue::new(&update_id, &"Penguin".into()),
+            )
+        );
+        assert_eq!(name.operation_id(), &update_id);
+
+        assert_eq!(
+            diff.get("height"),
+            Some(&FieldChange::Added(DocumentViewValue::new(
+                &update_id,
+                &OperationValue::Float(1.2)
+            )))
+        );
+    }
+
+    #[rstest]
+    fn deleted_document(
+        #[from(random_document_view_id)] from_view_id: DocumentViewId,
+        #[from(random_document_view_id)] to_view_id: DocumentViewId,
+        #[from(random_operation_id)] create_id: OperationId,
+        #[from(random_operation_id)] delete_id: OperationId,
+    ) {
+        let mut fields = DocumentViewFields::new();
+        fields.insert("name", DocumentViewValue::new(&create_id, &"Panda".into()));
+
+        // All fields are removed by the DELETE operation.
+        let diff = DocumentDiff::new(
+            (&from_view_id, Some(&fields)),
+            (&to_view_id, None),
+            Some(&delete_id),
+        );
+        assert_eq!(diff.len(), 1);
+
+        let name = diff.get("name").unwrap();
+        assert_eq!(
+            name,
+            &FieldChange::Removed(
+                DocumentViewValue::new(&create_id, &"Panda".into()),
+                delete_id.clone()
+            )
+        );
+        assert_eq!(name.operation_id(), &delete_id);
+
+        let diff = DocumentDiff::new((&from_view_id, None), (&to_view_id, None), Some(&delete_id));
+        assert!(diff.is_empty());
+    }
+}
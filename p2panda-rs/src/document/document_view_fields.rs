@@ -23,6 +23,32 @@ impl DocumentViewValue {
         }
     }
 
+    /// Returns the `DocumentViewValue` of a field after an operation wrote a value to it.
+    ///
+    /// Collaborative text fields hold the string resulting from applying the written edits to
+    /// the previous text of the field, all other fields hold the written value.
+    pub(crate) fn from_written(
+        operation_id: &OperationId,
+        value: &OperationValue,
+        previous: Option<&DocumentViewValue>,
+    ) -> Self {
+        let value = match value {
+            OperationValue::Text(text_delta) => {
+                let text = match previous.map(|previous| previous.value()) {
+                    Some(OperationValue::String(text)) => text.as_str(),
+                    _ => "",
+                };
+                OperationValue::String(text_delta.apply(text))
+            }
+            value => value.to_owned(),
+        };
+
+        Self {
+            operation_id: operation_id.clone(),
+            value,
+        }
+    }
+
     /// Get the OperationId of this document value.
     pub fn id(&self) -> &OperationId {
         &self.operation_id
@@ -49,7 +75,7 @@ impl DocumentViewFields {
         let mut document_view_fields = DocumentViewFields::new();
 
         for (name, value) in fields.iter() {
-            document_view_fields.insert(name, DocumentViewValue::new(id, value));
+            document_view_fields.insert(name, DocumentViewValue::from_written(id, value, None));
         }
 
         document_view_fields
@@ -102,7 +128,10 @@ impl<T: AsOperation + WithId<OperationId>> From<T> for DocumentViewFields {
 
         if let Some(fields) = operation.fields() {
             for (name, value) in fields.iter() {
-                document_view_fields.insert(name, DocumentViewValue::new(operation.id(), value));
+                document_view_fields.insert(
+                    name,
+                    DocumentViewValue::from_written(operation.id(), value, None),
+                );
             }
         }
 
@@ -4,7 +4,9 @@
 //! format of document ids and document view ids.
 use thiserror::Error;
 
+use crate::document::DocumentViewId;
 use crate::operation::OperationId;
+use crate::schema::SchemaId;
 
 /// Error types for methods of `DocumentBuilder` struct.
 #[derive(Error, Debug)]
@@ -21,6 +23,15 @@ pub enum DocumentBuilderError {
     #[error("multiple CREATE operations found when building operation graph")]
     MultipleCreateOperations,
 
+    /// An operation doesn't follow the schema set on the builder.
+    #[error("operation {0} does not follow the schema {1}")]
+    InvalidSchemaId(OperationId, SchemaId),
+
+    /// The sorted operations passed to an incremental build don't lead to the view of the
+    /// existing document.
+    #[error("sorted operations do not match the view {0} of the existing document")]
+    InvalidSortedOperations(DocumentViewId),
+
     /// Handle errors from validating CBOR schemas.
     #[error(transparent)]
     DocumentViewError(#[from] DocumentViewError),
@@ -0,0 +1,644 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::{HashMap, HashSet};
+
+use crate::document::text::TextSequence;
+use crate::document::{Document, DocumentId, DocumentViewFields, DocumentViewValue};
+use crate::operation::traits::AsOperation;
+use crate::operation::{OperationId, OperationValue, RelationList};
+use crate::schema::{MergeStrategy, SchemaFields};
+
+/// A document in an add-wins set together with the operations which added and removed it.
+#[derive(Clone, Debug)]
+struct AddWinsElement {
+    document_id: DocumentId,
+
+    /// Operations which added the document.
+    adds: Vec<OperationId>,
+
+    /// Adds which were removed, together with the operation removing them.
+    removes: Vec<(OperationId, OperationId)>,
+}
+
+impl AddWinsElement {
+    /// Returns true if any add of this document wasn't removed.
+    fn is_present(&self) -> bool {
+        self.adds
+            .iter()
+            .any(|add| !self.removes.iter().any(|(removed, _)| removed == add))
+    }
+
+    /// Returns the adds of this document which are visible to an operation with the passed causal
+    /// past, they were added and not removed in it.
+    fn visible_adds(&self, ancestors: &HashSet<OperationId>) -> Vec<OperationId> {
+        self.adds
+            .iter()
+            .filter(|add| ancestors.contains(add))
+            .filter(|add| {
+                !self
+                    .removes
+                    .iter()
+                    .any(|(removed, remover)| removed == *add && ancestors.contains(remover))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Merges the values written to fields with a merge strategy other than last-write-wins and to
+/// collaborative text fields while a document is reduced.
+///
+/// Operations need to be passed in their topologically sorted order, right after they were
+/// committed to the document with last-write-wins semantics. Merged values replace the committed
+/// ones, they are attributed to the operation which last changed them.
+///
+/// Add-wins sets and text fields require knowing the causal past of every operation, which is
+/// tracked for all visited operations if the document has fields using them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FieldMerger {
+    /// Fields which don't use last-write-wins and their strategies.
+    strategies: Vec<(String, MergeStrategy)>,
+
+    /// Documents of every add-wins set field, in the order they were first added.
+    add_wins_sets: HashMap<String, Vec<AddWinsElement>>,
+
+    /// Characters of every collaborative text field.
+    texts: HashMap<String, TextSequence>,
+
+    /// Causal past of every visited operation, only tracked for add-wins sets and text fields.
+    ancestors: HashMap<OperationId, HashSet<OperationId>>,
+}
+
+impl FieldMerger {
+    /// Returns a merger for the strategies of the passed schema fields.
+    pub(crate) fn new(schema_fields: &SchemaFields) -> Self {
+        let strategies = schema_fields
+            .keys()
+            .into_iter()
+            .filter_map(|name| match schema_fields.merge_strategy(&name) {
+                MergeStrategy::LastWriteWins => None,
+                strategy => Some((name, strategy)),
+            })
+            .collect();
+
+        Self {
+            strategies,
+            ..Self::default()
+        }
+    }
+
+    /// Returns true if any field uses another merge strategy than last-write-wins.
+    pub(crate) fn has_strategies(&self) -> bool {
+        !self.strategies.is_empty()
+    }
+
+    /// Merge the field values written by an operation into the document.
+    ///
+    /// `previous_fields` are the fields of the document before the operation was committed to it,
+    /// `None` for CREATE operations.
+    pub(crate) fn merge<T: AsOperation>(
+        &mut self,
+        operation_id: &OperationId,
+        operation: &T,
+        previous_fields: Option<&DocumentViewFields>,
+        document: &mut Document,
+    ) {
+        let ancestors = self.track_ancestors(operation_id, operation);
+
+        let (written_fields, document_fields) = match (operation.fields(), document.fields_mut()) {
+            (Some(written_fields), Some(document_fields)) => (written_fields, document_fields),
+            // Deleted documents don't have any values to merge.
+            _ => return,
+        };
+
+        let Self {
+            strategies,
+            add_wins_sets,
+            texts,
+            ..
+        } = self;
+
+        for (name, value) in written_fields.iter() {
+            if let OperationValue::Text(text_delta) = value {
+                let text = texts.entry(name.to_owned()).or_default();
+                text.apply(operation_id, &ancestors, text_delta);
+                document_fields.insert(
+                    name,
+                    DocumentViewValue::new(operation_id, &OperationValue::String(text.text())),
+                );
+            }
+        }
+
+        for (name, strategy) in strategies.iter() {
+            let written = match written_fields.get(name) {
+                Some(written) => written,
+                None => continue,
+            };
+            let previous = previous_fields.and_then(|fields| fields.get(name));
+
+            let merged = match strategy {
+                MergeStrategy::AddWinsSet => {
+                    let elements = add_wins_sets.entry(name.to_owned()).or_default();
+                    merge_add_wins_set(elements, operation_id, written, &ancestors)
+                }
+                strategy => {
+                    merge_value(strategy, previous.map(|previous| previous.value()), written)
+                }
+            };
+
+            // Values which couldn't be merged keep the committed value, unchanged values keep
+            // the operation which wrote them last.
+            match (merged, previous) {
+                (Some(merged), Some(previous)) if &merged == previous.value() => {
+                    document_fields.insert(name, previous.to_owned());
+                }
+                (Some(merged), _) => {
+                    document_fields.insert(name, DocumentViewValue::new(operation_id, &merged));
+                }
+                (None, _) => (),
+            }
+        }
+    }
+
+    /// Record the causal past of an operation if any field is an add-wins set or a text and
+    /// return it.
+    fn track_ancestors<T: AsOperation>(
+        &mut self,
+        operation_id: &OperationId,
+        operation: &T,
+    ) -> HashSet<OperationId> {
+        // Documents with text fields already contain them in their CREATE operation.
+        let is_tracked = !self.texts.is_empty()
+            || has_text(operation)
+            || self
+                .strategies
+                .iter()
+                .any(|(_, strategy)| strategy == &MergeStrategy::AddWinsSet);
+
+        if !is_tracked {
+            return HashSet::new();
+        }
+
+        let ancestors = causal_past(&self.ancestors, operation);
+        self.ancestors
+            .insert(operation_id.to_owned(), ancestors.clone());
+        ancestors
+    }
+}
+
+/// Returns the causal past of an operation, all operations it refers to in its `previous` field
+/// and their causal past.
+///
+/// `ancestors` contains the causal past of all operations visited before, operations need to be
+/// visited in their topologically sorted order.
+pub(crate) fn causal_past<T: AsOperation>(
+    ancestors: &HashMap<OperationId, HashSet<OperationId>>,
+    operation: &T,
+) -> HashSet<OperationId> {
+    let mut causal_past = HashSet::new();
+
+    if let Some(previous) = operation.previous() {
+        for previous_id in previous.iter() {
+            causal_past.insert(previous_id.to_owned());
+
+            if let Some(previous_ancestors) = ancestors.get(previous_id) {
+                causal_past.extend(previous_ancestors.iter().cloned());
+            }
+        }
+    }
+
+    causal_past
+}
+
+/// Returns true if the operation writes to collaborative text fields.
+pub(crate) fn has_text<T: AsOperation>(operation: &T) -> bool {
+    match operation.fields() {
+        Some(fields) => fields
+            .iter()
+            .any(|(_, value)| matches!(value, OperationValue::Text(_))),
+        None => false,
+    }
+}
+
+/// Merge a written value into the previous value of a field, returns `None` if the values can't
+/// be merged with this strategy.
+fn merge_value(
+    strategy: &MergeStrategy,
+    previous: Option<&OperationValue>,
+    written: &OperationValue,
+) -> Option<OperationValue> {
+    // The first value of a field is taken as it is.
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Some(written.to_owned()),
+    };
+
+    match (strategy, previous, written) {
+        (
+            MergeStrategy::Counter,
+            OperationValue::Integer(value),
+            OperationValue::Integer(delta),
+        ) => Some(OperationValue::Integer(value.saturating_add(*delta))),
+        (MergeStrategy::Max, OperationValue::Integer(a), OperationValue::Integer(b)) => {
+            Some(OperationValue::Integer(*a.max(b)))
+        }
+        (MergeStrategy::Min, OperationValue::Integer(a), OperationValue::Integer(b)) => {
+            Some(OperationValue::Integer(*a.min(b)))
+        }
+        (MergeStrategy::Max, OperationValue::Float(a), OperationValue::Float(b)) => {
+            Some(OperationValue::Float(if b > a { *b } else { *a }))
+        }
+        (MergeStrategy::Min, OperationValue::Float(a), OperationValue::Float(b)) => {
+            Some(OperationValue::Float(if b < a { *b } else { *a }))
+        }
+        (
+            MergeStrategy::GrowOnlySet,
+            OperationValue::RelationList(previous),
+            OperationValue::RelationList(written),
+        ) => {
+            let mut document_ids = previous.document_ids().to_vec();
+
+            for document_id in written.iter() {
+                if !document_ids.contains(document_id) {
+                    document_ids.push(document_id.to_owned());
+                }
+            }
+
+            Some(OperationValue::RelationList(RelationList::new(
+                document_ids,
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// Apply the relation list written by an operation to an add-wins set and return it's new value.
+///
+/// Documents in the list which the operation didn't see in the set are added. Documents it saw
+/// which are missing from the list are removed, which only affects the adds in it's causal past.
+fn merge_add_wins_set(
+    elements: &mut Vec<AddWinsElement>,
+    operation_id: &OperationId,
+    written: &OperationValue,
+    ancestors: &HashSet<OperationId>,
+) -> Option<OperationValue> {
+    let written = match written {
+        OperationValue::RelationList(written) => written,
+        _ => return None,
+    };
+
+    for element in elements.iter_mut() {
+        if !written.iter().any(|id| id == &element.document_id) {
+            for add in element.visible_adds(ancestors) {
+                element.removes.push((add, operation_id.to_owned()));
+            }
+        }
+    }
+
+    for document_id in written.iter() {
+        match elements
+            .iter_mut()
+            .find(|element| &element.document_id == document_id)
+        {
+            Some(element) => {
+                if element.visible_adds(ancestors).is_empty() {
+                    element.adds.push(operation_id.to_owned());
+                }
+            }
+            None => elements.push(AddWinsElement {
+                document_id: document_id.to_owned(),
+                adds: vec![operation_id.to_owned()],
+                removes: Vec::new(),
+            }),
+        }
+    }
+
+    let document_ids = elements
+        .iter()
+        .filter(|element| element.is_present())
+        .map(|element| element.document_id.to_owned())
+        .collect();
+
+    Some(OperationValue::RelationList(RelationList::new(
+        document_ids,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::document::error::DocumentBuilderError;
+    use crate::document::traits::AsDocument;
+    use crate::document::{DocumentBuilder, DocumentId};
+    use crate::identity::PublicKey;
+    use crate::operation::{Operation, OperationId, OperationValue, RelationList};
+    use crate::schema::{FieldType, MergeStrategy, Schema, SchemaId, SchemaName};
+    use crate::test_utils::fixtures::{
+        graph_operations, random_document_id, random_document_view_id, schema, OperationGraph,
+    };
+
+    /// Returns a schema with a field for every merge strategy.
+    fn merging_schema() -> Schema {
+        let schema_id = SchemaId::new_application(
+            &SchemaName::new("venue").unwrap(),
+            &random_document_view_id(),
+        );
+        let relation_schema_id = SchemaId::new_application(
+            &SchemaName::new("person").unwrap(),
+            &random_document_view_id(),
+        );
+
+        schema(
+            vec![
+                ("name".into(), FieldType::String),
+                ("visitors".into(), FieldType::Integer),
+                ("highest".into(), FieldType::Integer),
+                ("lowest".into(), FieldType::Float),
+                (
+                    "tags".into(),
+                    FieldType::RelationList(relation_schema_id.clone()),
+                ),
+                (
+                    "members".into(),
+                    FieldType::RelationList(relation_schema_id),
+                ),
+            ],
+            schema_id,
+            "Venue with merged fields",
+        )
+        .with_merge_strategy("visitors", MergeStrategy::Counter)
+        .unwrap()
+        .with_merge_strategy("highest", MergeStrategy::Max)
+        .unwrap()
+        .with_merge_strategy("lowest", MergeStrategy::Min)
+        .unwrap()
+        .with_merge_strategy("tags", MergeStrategy::GrowOnlySet)
+        .unwrap()
+        .with_merge_strategy("members", MergeStrategy::AddWinsSet)
+        .unwrap()
+    }
+
+    fn relation_list(document_ids: &[&DocumentId]) -> OperationValue {
+        OperationValue::RelationList(RelationList::new(
+            document_ids
+                .iter()
+                .map(|id| id.to_owned().to_owned())
+                .collect(),
+        ))
+    }
+
+    /// Returns the operations of a document following the passed graph. The CREATE operation
+    /// comes first and writes default values to the fields which aren't set in it's entry.
+    fn operations(
+        schema: &Schema,
+        mut graph: OperationGraph,
+    ) -> Vec<(OperationId, Operation, PublicKey)> {
+        let (_, fields) = &mut graph[0];
+
+        for (name, value) in [
+            ("name", OperationValue::String("Panda Cafe".into())),
+            ("visitors", OperationValue::Integer(0)),
+            ("highest", OperationValue::Integer(0)),
+            ("lowest", OperationValue::Float(0.0)),
+            ("tags", relation_list(&[])),
+            ("members", relation_list(&[])),
+        ] {
+            if !fields.iter().any(|(field, _)| field == &name) {
+                fields.push((name, value));
+            }
+        }
+
+        graph_operations(schema.id(), graph)
+    }
+
+    /// Builds the document from the graph a couple of times and asserts that the field always
+    /// holds the expected value, independent of how concurrent operations are sorted.
+    fn assert_merged(graph: OperationGraph, field: &str, expected: OperationValue) {
+        let schema = merging_schema();
+
+        for _ in 0..10 {
+            let operations = operations(&schema, graph.clone());
+            let (document, _) = DocumentBuilder::new(operations)
+                .with_schema(&schema)
+                .build()
+                .unwrap();
+
+            match (document.get(field), &expected) {
+                (
+                    Some(OperationValue::RelationList(list)),
+                    OperationValue::RelationList(expected),
+                ) => {
+                    let mut document_ids = list.document_ids().to_vec();
+                    let mut expected_ids = expected.document_ids().to_vec();
+                    document_ids.sort();
+                    expected_ids.sort();
+                    assert_eq!(document_ids, expected_ids);
+                }
+                (value, expected) => assert_eq!(value, Some(expected)),
+            }
+        }
+    }
+
+    #[test]
+    fn counter() {
+        let graph = vec![
+            (vec![], vec![("visitors", OperationValue::Integer(1))]),
+            (vec![0], vec![("visitors", OperationValue::Integer(2))]),
+            (vec![0], vec![("visitors", OperationValue::Integer(3))]),
+            (
+                vec![1, 2],
+                vec![
+                    ("name", OperationValue::String("Polar Bear Cafe".into())),
+                    ("visitors", OperationValue::Integer(-4)),
+                ],
+            ),
+        ];
+
+        assert_merged(graph.clone(), "visitors", OperationValue::Integer(2));
+
+        // Fields without a merge strategy are still last-write-wins.
+        assert_merged(
+            graph,
+            "name",
+            OperationValue::String("Polar Bear Cafe".into()),
+        );
+    }
+
+    #[rstest]
+    #[case::max("highest", OperationValue::Integer(3), vec![
+        OperationValue::Integer(3),
+        OperationValue::Integer(-5),
+        OperationValue::Integer(1),
+    ])]
+    #[case::min("lowest", OperationValue::Float(-2.5), vec![
+        OperationValue::Float(4.0),
+        OperationValue::Float(-2.5),
+        OperationValue::Float(0.5),
+    ])]
+    fn registers(
+        #[case] field: &str,
+        #[case] expected: OperationValue,
+        #[case] values: Vec<OperationValue>,
+    ) {
+        let graph = vec![
+            (vec![], vec![]),
+            (vec![0], vec![(field, values[0].clone())]),
+            (vec![0], vec![(field, values[1].clone())]),
+            (vec![1, 2], vec![(field, values[2].clone())]),
+        ];
+
+        assert_merged(graph, field, expected);
+    }
+
+    #[test]
+    fn grow_only_set() {
+        let (panda, penguin, bear) = (
+            random_document_id(),
+            random_document_id(),
+            random_document_id(),
+        );
+
+        let graph = vec![
+            (vec![], vec![("tags", relation_list(&[&panda]))]),
+            (vec![0], vec![("tags", relation_list(&[&penguin]))]),
+            (vec![0], vec![("tags", relation_list(&[&bear, &panda]))]),
+            (vec![1, 2], vec![("tags", relation_list(&[]))]),
+        ];
+
+        assert_merged(graph, "tags", relation_list(&[&panda, &penguin, &bear]));
+    }
+
+    #[test]
+    fn add_wins_set() {
+        let (panda, penguin, bear) = (
+            random_document_id(),
+            random_document_id(),
+            random_document_id(),
+        );
+
+        // 0: Add panda and penguin.
+        // 1: Remove panda.
+        // 2: Remove panda and add bear, concurrently to 1.
+        // 3: Add panda again, concurrently to 1.
+        let mut graph = vec![
+            (
+                vec![],
+                vec![("members", relation_list(&[&panda, &penguin]))],
+            ),
+            (vec![0], vec![("members", relation_list(&[&penguin]))]),
+            (
+                vec![0],
+                vec![("members", relation_list(&[&penguin, &bear]))],
+            ),
+            (
+                vec![2],
+                vec![("members", relation_list(&[&panda, &penguin, &bear]))],
+            ),
+        ];
+
+        // The concurrent add of panda wins over it's removal.
+        assert_merged(
+            graph.clone(),
+            "members",
+            relation_list(&[&panda, &penguin, &bear]),
+        );
+
+        // Removing panda after seeing all adds removes it.
+        graph.push((
+            vec![1, 3],
+            vec![("members", relation_list(&[&penguin, &bear]))],
+        ));
+        assert_merged(graph, "members", relation_list(&[&penguin, &bear]));
+    }
+
+    #[test]
+    fn schema_needs_to_match() {
+        let schema = merging_schema();
+        let operations = operations(&schema, vec![(vec![], vec![])]);
+
+        let result = DocumentBuilder::new(operations)
+            .with_schema(&merging_schema())
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(DocumentBuilderError::InvalidSchemaId(..))
+        ));
+    }
+
+    #[test]
+    fn incremental_build_with_merge_strategies() {
+        let schema = merging_schema();
+        let operations = operations(
+            &schema,
+            vec![
+                (vec![], vec![("visitors", OperationValue::Integer(1))]),
+                (vec![0], vec![("visitors", OperationValue::Integer(2))]),
+                (vec![0], vec![("visitors", OperationValue::Integer(3))]),
+            ],
+        );
+
+        let (document, sorted) = DocumentBuilder::new(operations[..2].to_vec())
+            .with_schema(&schema)
+            .build()
+            .unwrap();
+
+        let (document, _) = DocumentBuilder::new(operations)
+            .with_schema(&schema)
+            .build_incremental(&document, &sorted)
+            .unwrap();
+
+        assert_eq!(document.get("visitors"), Some(&OperationValue::Integer(6)));
+    }
+
+    #[test]
+    fn commits_with_merge_strategies() {
+        let schema = merging_schema();
+        let operations = operations(
+            &schema,
+            vec![
+                (vec![], vec![("visitors", OperationValue::Integer(1))]),
+                (
+                    vec![0],
+                    vec![
+                        ("visitors", OperationValue::Integer(2)),
+                        ("highest", OperationValue::Integer(5)),
+                    ],
+                ),
+                (
+                    vec![1],
+                    vec![
+                        ("visitors", OperationValue::Integer(3)),
+                        ("highest", OperationValue::Integer(4)),
+                    ],
+                ),
+            ],
+        );
+        let (operation_id, operation, _) = &operations[2];
+
+        // Documents built with the schema merge committed operations like the builder does.
+        let (mut document, _) = DocumentBuilder::new(operations[..2].to_vec())
+            .with_schema(&schema)
+            .build()
+            .unwrap();
+        document.commit(operation_id, operation).unwrap();
+
+        let (expected, _) = DocumentBuilder::new(operations.clone())
+            .with_schema(&schema)
+            .build()
+            .unwrap();
+        assert_eq!(document.get("visitors"), Some(&OperationValue::Integer(6)));
+        assert_eq!(document.get("highest"), Some(&OperationValue::Integer(5)));
+        assert_eq!(document.fields(), expected.fields());
+        assert_eq!(document.view_id(), expected.view_id());
+
+        // Documents built without the schema resolve all fields with last-write-wins.
+        let (mut document, _) = DocumentBuilder::new(operations[..2].to_vec())
+            .build()
+            .unwrap();
+        document.commit(operation_id, operation).unwrap();
+        assert_eq!(document.get("visitors"), Some(&OperationValue::Integer(3)));
+        assert_eq!(document.get("highest"), Some(&OperationValue::Integer(4)));
+    }
+}
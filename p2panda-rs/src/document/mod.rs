@@ -6,7 +6,11 @@
 //! state for any two replicas. The underlying structure which make this possible is a directed acyclic graph
 //! of [`Operation`]'s. To arrive at the current state of a document the graph is topologically sorted,
 //! with any branches being ordered according to the conflicting operations [`OperationId`]. Each operation's
-//! mutation is applied in order which results in a LWW (last write wins) resolution strategy.
+//! mutation is applied in order which results in a LWW (last write wins) resolution strategy. Fields of a
+//! schema can use other [merge strategies][`crate::schema::MergeStrategy`] when the schema is passed to
+//! [`DocumentBuilder::with_schema`]. Concurrent edits of collaborative [text][`crate::operation::TextDelta`] fields
+//! are merged with a sequence CRDT, the resolved text is stored as a string in the document fields. Fields with
+//! concurrent writes resolved by LWW are reported by [`DocumentBuilder::build_with_conflicts`].
 //!
 //! All documents have an accomapanying `Schema` which describes the shape of the data they will contain. Every
 //! operation should have been validated aginst this schema before being included in the graph.
@@ -135,15 +139,21 @@
 //! ```
 #[allow(clippy::module_inception)]
 mod document;
+mod document_conflicts;
+mod document_diff;
 mod document_id;
 mod document_view;
 mod document_view_fields;
 mod document_view_hash;
 mod document_view_id;
 pub mod error;
+mod merge;
+mod text;
 pub mod traits;
 
 pub use document::{Document, DocumentBuilder};
+pub use document_conflicts::{ConflictReport, FieldConflict};
+pub use document_diff::{DocumentDiff, FieldChange};
 pub use document_id::DocumentId;
 pub use document_view::DocumentView;
 pub use document_view_fields::{DocumentViewFields, DocumentViewValue};
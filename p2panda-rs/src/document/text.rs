@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::HashSet;
+
+use crate::operation::{OperationId, TextDelta};
+
+/// Identifier of a character in a collaborative text.
+///
+/// Characters are ordered by the size of the causal past of the operation which inserted them,
+/// the operation id and their offset in the inserted text. Every operation inserts characters
+/// with larger identifiers than all characters it knows about.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct CharacterId {
+    clock: usize,
+    operation_id: OperationId,
+    offset: usize,
+}
+
+/// Character of a collaborative text together with the operations which deleted it.
+#[derive(Clone, Debug)]
+struct Character {
+    id: CharacterId,
+    value: char,
+    deleted_by: Vec<OperationId>,
+}
+
+/// Sequence of all characters ever inserted into a collaborative text field.
+///
+/// This is a replicated growable array (RGA): deleted characters are kept as tombstones and
+/// concurrent insertions at the same position are ordered by their identifiers, which leads to the
+/// same text independent of the order concurrent operations are applied in.
+///
+/// Positions of text edits are resolved against the text as it was known to the writing
+/// operation, the characters inserted and not deleted by the operations in its causal past.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TextSequence {
+    characters: Vec<Character>,
+}
+
+impl TextSequence {
+    /// Apply the edits of an operation, `ancestors` is its causal past.
+    ///
+    /// Operations need to be applied in a topologically sorted order.
+    pub(crate) fn apply(
+        &mut self,
+        operation_id: &OperationId,
+        ancestors: &HashSet<OperationId>,
+        text_delta: &TextDelta,
+    ) {
+        let is_known = |id: &OperationId| id == operation_id || ancestors.contains(id);
+        let mut offset = 0;
+
+        for edit in text_delta.iter() {
+            // Indexes of the characters of the text this operation knows about.
+            let visible: Vec<usize> = self
+                .characters
+                .iter()
+                .enumerate()
+                .filter(|(_, character)| {
+                    is_known(&character.id.operation_id)
+                        && !character.deleted_by.iter().any(is_known)
+                })
+                .map(|(index, _)| index)
+                .collect();
+
+            let (start, end) = edit.range(visible.len());
+
+            for index in &visible[start..end] {
+                self.characters[*index]
+                    .deleted_by
+                    .push(operation_id.to_owned());
+            }
+
+            // Insert every character after the previous one, skipping concurrently inserted
+            // characters with larger identifiers.
+            let mut after = start.checked_sub(1).map(|position| visible[position]);
+
+            for value in edit.inserted().chars() {
+                let id = CharacterId {
+                    clock: ancestors.len(),
+                    operation_id: operation_id.to_owned(),
+                    offset,
+                };
+                offset += 1;
+
+                let mut index = after.map_or(0, |after| after + 1);
+                while index < self.characters.len() && self.characters[index].id > id {
+                    index += 1;
+                }
+
+                self.characters.insert(
+                    index,
+                    Character {
+                        id,
+                        value,
+                        deleted_by: Vec::new(),
+                    },
+                );
+                after = Some(index);
+            }
+        }
+    }
+
+    /// Returns the text of all characters which weren't deleted.
+    pub(crate) fn text(&self) -> String {
+        self.characters
+            .iter()
+            .filter(|character| character.deleted_by.is_empty())
+            .map(|character| character.value)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::operation::{OperationId, TextDelta, TextEdit};
+    use crate::test_utils::fixtures::random_operation_id;
+
+    use super::TextSequence;
+
+    /// Apply operations in the passed order, every operation is given with the indexes of the
+    /// operations in its causal past and its edits.
+    fn apply(operations: &[(OperationId, Vec<usize>, TextDelta)], order: &[usize]) -> String {
+        let mut sequence = TextSequence::default();
+
+        for index in order {
+            let (operation_id, ancestors, text_delta) = &operations[*index];
+            let ancestors: HashSet<OperationId> = ancestors
+                .iter()
+                .map(|index| operations[*index].0.clone())
+                .collect();
+            sequence.apply(operation_id, &ancestors, text_delta);
+        }
+
+        sequence.text()
+    }
+
+    #[test]
+    fn sequential_edits() {
+        let operations = vec![
+            (random_operation_id(), vec![], TextDelta::from("Hello")),
+            (
+                random_operation_id(),
+                vec![0],
+                TextDelta::new(vec![
+                    TextEdit::insert(5, " Panda"),
+                    TextEdit::new(0, 1, "J"),
+                ]),
+            ),
+            (
+                random_operation_id(),
+                vec![0, 1],
+                TextDelta::new(vec![TextEdit::delete(1, 4), TextEdit::insert(1, "ust")]),
+            ),
+        ];
+
+        assert_eq!(apply(&operations, &[0, 1, 2]), "Just Panda");
+    }
+
+    #[test]
+    fn concurrent_edits() {
+        // Two concurrent edits of "Hello Panda" and an edit knowing about both.
+        let operations = vec![
+            (
+                random_operation_id(),
+                vec![],
+                TextDelta::from("Hello Panda"),
+            ),
+            (
+                random_operation_id(),
+                vec![0],
+                TextDelta::new(vec![TextEdit::new(0, 5, "Hi")]),
+            ),
+            (
+                random_operation_id(),
+                vec![0],
+                TextDelta::new(vec![
+                    TextEdit::insert(11, "s"),
+                    TextEdit::insert(6, "happy "),
+                ]),
+            ),
+            (
+                random_operation_id(),
+                vec![0, 1, 2],
+                TextDelta::new(vec![TextEdit::insert(15, "!")]),
+            ),
+        ];
+
+        assert_eq!(apply(&operations, &[0, 1, 2, 3]), "Hi happy Pandas!");
+        assert_eq!(apply(&operations, &[0, 2, 1, 3]), "Hi happy Pandas!");
+    }
+
+    #[test]
+    fn concurrent_inserts_at_same_position() {
+        let operations = vec![
+            (random_operation_id(), vec![], TextDelta::from("ac")),
+            (
+                random_operation_id(),
+                vec![0],
+                TextEdit::insert(1, "bb").into(),
+            ),
+            (
+                random_operation_id(),
+                vec![0],
+                TextEdit::insert(1, "BB").into(),
+            ),
+            (
+                random_operation_id(),
+                vec![0, 2],
+                TextEdit::insert(2, "-").into(),
+            ),
+        ];
+
+        let text = apply(&operations, &[0, 1, 2, 3]);
+        assert_eq!(text, apply(&operations, &[0, 2, 1, 3]));
+        assert_eq!(text, apply(&operations, &[0, 2, 3, 1]));
+
+        // Concurrent insertions don't interleave and edits stay where their writer put them.
+        assert!(text == "aB-Bbbc" || text == "abbB-Bc");
+    }
+
+    #[test]
+    fn concurrent_deletes() {
+        let operations = vec![
+            (random_operation_id(), vec![], TextDelta::from("Panda")),
+            (
+                random_operation_id(),
+                vec![0],
+                TextEdit::delete(0, 3).into(),
+            ),
+            (
+                random_operation_id(),
+                vec![0],
+                TextEdit::delete(2, 3).into(),
+            ),
+            (
+                random_operation_id(),
+                vec![0, 2],
+                TextEdit::insert(2, "!").into(),
+            ),
+        ];
+
+        assert_eq!(apply(&operations, &[0, 1, 2, 3]), "!");
+        assert_eq!(apply(&operations, &[0, 2, 3, 1]), "!");
+    }
+}
@@ -65,6 +65,9 @@ pub trait AsDocument {
     ///
     /// For the update to be successful the passed operation must refer to this documents' current
     /// view id in it's previous field and must update a field which exists on this document.
+    ///
+    /// Fields are updated with last-write-wins semantics. `Document` overrides `commit_unchecked`
+    /// to also merge fields following the merge strategies of it's schema.
     fn commit<T: AsOperation>(
         &mut self,
         operation_id: &OperationId,
@@ -98,36 +101,46 @@ pub trait AsDocument {
 
     /// Commit an new operation to the document without performing any validation.
     fn commit_unchecked<T: AsOperation>(&mut self, operation_id: &OperationId, operation: &T) {
-        let next_fields = match operation.fields() {
-            // If the operation contains fields it's an UPDATE and so we want to apply the changes
-            // to the designated fields.
-            Some(fields) => {
-                // Get the current document fields, we can unwrap as we checked for deleted
-                // documents above.
-                let mut document_fields = self.fields().unwrap().to_owned();
-
-                // For every field in the UPDATE operation update the relevant field in the
-                // current document fields.
-                for (name, value) in fields.iter() {
-                    let document_field_value = DocumentViewValue::new(operation_id, value);
-
-                    // We know all the fields are correct for this document as we checked the
-                    // schema id above.
-                    document_fields.insert(name, document_field_value);
-                }
-
-                // Return the updated fields.
-                Some(document_fields)
+        commit_last_write_wins(self, operation_id, operation);
+    }
+}
+
+/// Commit an operation to a document, writing all of it's fields with last-write-wins semantics.
+pub(crate) fn commit_last_write_wins<D: AsDocument + ?Sized, T: AsOperation>(
+    document: &mut D,
+    operation_id: &OperationId,
+    operation: &T,
+) {
+    let next_fields = match operation.fields() {
+        // If the operation contains fields it's an UPDATE and so we want to apply the changes
+        // to the designated fields.
+        Some(fields) => {
+            // Get the current document fields, we can unwrap as we checked for deleted
+            // documents above.
+            let mut document_fields = document.fields().unwrap().to_owned();
+
+            // For every field in the UPDATE operation update the relevant field in the
+            // current document fields.
+            for (name, value) in fields.iter() {
+                let document_field_value =
+                    DocumentViewValue::from_written(operation_id, value, document_fields.get(name));
+
+                // We know all the fields are correct for this document as we checked the
+                // schema id above.
+                document_fields.insert(name, document_field_value);
             }
-            // If the operation doesn't contain fields this must be a DELETE so we return None as we want to remove the
-            // current document's fields.
-            None => None,
-        };
 
-        // Construct the new document view id.
-        let document_view_id = DocumentViewId::new(&[operation_id.to_owned()]);
+            // Return the updated fields.
+            Some(document_fields)
+        }
+        // If the operation doesn't contain fields this must be a DELETE so we return None as we want to remove the
+        // current document's fields.
+        None => None,
+    };
 
-        // Update the documents' view, edited/deleted state and view id.
-        self.update_view(&document_view_id, next_fields.as_ref());
-    }
+    // Construct the new document view id.
+    let document_view_id = DocumentViewId::new(&[operation_id.to_owned()]);
+
+    // Update the documents' view, edited/deleted state and view id.
+    document.update_view(&document_view_id, next_fields.as_ref());
 }
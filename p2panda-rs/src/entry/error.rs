@@ -80,6 +80,14 @@ pub enum ValidateEntryError {
     #[error("claimed hash does not match skiplink entry")]
     WrongSkiplinkHash,
 
+    /// Entry does not match the encoded entry it was passed with.
+    #[error("entry does not match encoded entry")]
+    EncodedEntryMismatch,
+
+    /// Entry required to verify the log integrity is missing in the certificate pool.
+    #[error("entry with seq num {0} missing in certificate pool")]
+    CertificatePoolEntryMissing(u64),
+
     /// Could not verify authorship of entry.
     #[error("signature invalid")]
     KeyPairError(#[from] crate::identity::error::KeyPairError),
@@ -4,9 +4,13 @@
 //!
 //! You will not find methods here to check the encoding of Bamboo entries, as this is handled
 //! inside the external bamboo-rs crate.
+use std::collections::HashMap;
+
+use bamboo_rs_core_ed25519_yasmf::decode;
+
 use crate::entry::error::ValidateEntryError;
 use crate::entry::traits::{AsEncodedEntry, AsEntry};
-use crate::entry::{Entry, Signature};
+use crate::entry::{Entry, SeqNum, Signature};
 use crate::hash::Hash;
 use crate::identity::{KeyPair, PublicKey};
 use crate::operation::EncodedOperation;
@@ -130,21 +134,133 @@ pub fn validate_payload(
     Ok(())
 }
 
+/// Checks if an entry can be verified against its certificate pool.
+///
+/// The certificate pool of an entry contains all entries on the shortest path from it to the first
+/// entry of the log, following the lipmaa link (skiplink or backlink) of every entry. With it a
+/// peer can verify an entry without knowing the full log.
+///
+/// The following validation steps are applied:
+///
+/// 1. Does the entry match the encoded entry and is it's signature valid?
+/// 2. Is every entry on the path to the first entry part of the pool?
+/// 3. Is every entry on the path part of the same log, published by the same key and matching
+///    the hash it is linked by?
+/// 4. Does every entry on the path match it's encoded form and is it's signature valid?
+pub fn validate_certificate_pool<P: AsEntry + AsEncodedEntry>(
+    entry: &impl AsEntry,
+    encoded_entry: &impl AsEncodedEntry,
+    certificate_pool: &[P],
+) -> Result<(), ValidateEntryError> {
+    validate_encoding(entry, encoded_entry)?;
+    validate_signature(entry.public_key(), entry.signature(), encoded_entry)?;
+
+    if entry.seq_num().is_first() {
+        return Ok(());
+    }
+
+    let pool: HashMap<SeqNum, &P> = certificate_pool
+        .iter()
+        .map(|pool_entry| (*pool_entry.seq_num(), pool_entry))
+        .collect();
+
+    let mut link = certificate_pool_link(entry, &pool)?;
+
+    while !link.seq_num().is_first() {
+        link = certificate_pool_link(link, &pool)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the entry from the certificate pool the passed entry links to with its lipmaa link.
+///
+/// Validates that the linked entry belongs to the same log and that it's signature is correct.
+fn certificate_pool_link<'a, P: AsEntry + AsEncodedEntry>(
+    entry: &impl AsEntry,
+    pool: &HashMap<SeqNum, &'a P>,
+) -> Result<&'a P, ValidateEntryError> {
+    // Unwrap as only entries which are not the first one in a log are passed here.
+    let lipmaa_seq_num = entry.seq_num().skiplink_seq_num().unwrap();
+
+    let link = *pool
+        .get(&lipmaa_seq_num)
+        .ok_or_else(|| ValidateEntryError::CertificatePoolEntryMissing(lipmaa_seq_num.as_u64()))?;
+    validate_encoding(link, link)?;
+    let link_hash = link.hash();
+
+    // When the lipmaa entry is the previous entry it is referred to by the backlink, in all other
+    // cases by the skiplink.
+    if entry.seq_num().backlink_seq_num() == Some(lipmaa_seq_num) {
+        if entry.backlink().is_none() {
+            return Err(ValidateEntryError::InvalidLinks);
+        }
+
+        validate_log_integrity(entry, None::<(&P, &Hash)>, Some((link, &link_hash)))?;
+    } else {
+        if entry.skiplink().is_none() {
+            return Err(ValidateEntryError::InvalidLinks);
+        }
+
+        validate_log_integrity(entry, Some((link, &link_hash)), None::<(&P, &Hash)>)?;
+    }
+
+    validate_signature(link.public_key(), link.signature(), link)?;
+
+    Ok(link)
+}
+
+/// Checks if the entry matches the decoded encoded entry.
+///
+/// Signatures only cover the encoded bytes, the values of an entry can only be trusted after
+/// making sure that they were decoded from these bytes.
+fn validate_encoding(
+    entry: &impl AsEntry,
+    encoded_entry: &impl AsEncodedEntry,
+) -> Result<(), ValidateEntryError> {
+    let decoded: Entry = decode(&encoded_entry.into_bytes())
+        .map_err(|_| ValidateEntryError::EncodedEntryMismatch)?
+        .into();
+
+    if decoded.public_key() != entry.public_key()
+        || decoded.log_id() != entry.log_id()
+        || decoded.seq_num() != entry.seq_num()
+        || decoded.skiplink() != entry.skiplink()
+        || decoded.backlink() != entry.backlink()
+        || decoded.payload_size() != entry.payload_size()
+        || decoded.payload_hash() != entry.payload_hash()
+        || decoded.signature() != entry.signature()
+    {
+        return Err(ValidateEntryError::EncodedEntryMismatch);
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
 
+    use crate::entry::decode::decode_entry;
     use crate::entry::encode::encode_entry;
+    use crate::entry::error::ValidateEntryError;
     use crate::entry::traits::{AsEncodedEntry, AsEntry};
-    use crate::entry::{EncodedEntry, Entry, EntryBuilder, SeqNum, Signature};
+    use crate::entry::{EncodedEntry, Entry, EntryBuilder, LogId, SeqNum, Signature};
     use crate::hash::Hash;
     use crate::identity::KeyPair;
     use crate::operation::EncodedOperation;
+    use crate::storage_provider::traits::EntryStore;
     use crate::test_utils::fixtures::{
         encoded_entry, encoded_operation, entry, entry_auto_gen_links, key_pair,
+        populate_store_config,
     };
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+    use crate::test_utils::memory_store::MemoryStore;
 
-    use super::{validate_links, validate_log_integrity, validate_payload, validate_signature};
+    use super::{
+        validate_certificate_pool, validate_links, validate_log_integrity, validate_payload,
+        validate_signature,
+    };
 
     #[rstest]
     fn duplicate_back_and_skiplink(
@@ -268,4 +384,99 @@ mod tests {
         )
         .is_err());
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn check_certificate_pool(
+        #[from(populate_store_config)]
+        #[with(20, 2, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        let get_entry = |log_id: u64, seq_num: u64| {
+            let store = store.clone();
+            async move {
+                store
+                    .get_entry_at_seq_num(
+                        &public_key,
+                        &LogId::new(log_id),
+                        &SeqNum::new(seq_num).unwrap(),
+                    )
+                    .await
+                    .unwrap()
+                    .unwrap()
+            }
+        };
+
+        let entry = get_entry(0, 20).await;
+        let certificate_pool = store
+            .get_certificate_pool(&public_key, &LogId::default(), entry.seq_num())
+            .await
+            .unwrap();
+
+        // Entry can be verified with it's certificate pool
+        assert!(validate_certificate_pool(&entry, &entry, &certificate_pool).is_ok());
+
+        // First entries don't need a certificate pool
+        let first_entry = get_entry(0, 1).await;
+        assert!(validate_certificate_pool(
+            &first_entry,
+            &first_entry,
+            std::slice::from_ref(&first_entry)
+        )
+        .is_ok());
+
+        // Entry on the path is missing
+        let incomplete_pool: Vec<_> = certificate_pool
+            .iter()
+            .filter(|pool_entry| pool_entry.seq_num().as_u64() != 13)
+            .cloned()
+            .collect();
+        assert!(matches!(
+            validate_certificate_pool(&entry, &entry, &incomplete_pool),
+            Err(ValidateEntryError::CertificatePoolEntryMissing(13))
+        ));
+
+        // Entry on the path is from another log with the same sequence number
+        let mut wrong_pool = certificate_pool.clone();
+        let position = wrong_pool
+            .iter()
+            .position(|pool_entry| pool_entry.seq_num().as_u64() == 4)
+            .unwrap();
+        wrong_pool[position] = get_entry(1, 4).await;
+        assert!(matches!(
+            validate_certificate_pool(&entry, &entry, &wrong_pool),
+            Err(ValidateEntryError::WrongSkiplinkLogId(0, 1))
+        ));
+
+        // Entry itself doesn't match the entries in the pool
+        let other_log_entry = get_entry(1, 20).await;
+        assert!(matches!(
+            validate_certificate_pool(&other_log_entry, &other_log_entry, &certificate_pool),
+            Err(ValidateEntryError::WrongBacklinkLogId(1, 0))
+        ));
+
+        // Entry doesn't match the encoded entry it is passed with
+        let other_entry = get_entry(0, 19).await;
+        let mismatched_entry = decode_entry(&other_entry).unwrap();
+        assert!(matches!(
+            validate_certificate_pool(&mismatched_entry, &entry, &certificate_pool),
+            Err(ValidateEntryError::EncodedEntryMismatch)
+        ));
+
+        // Entry on the path doesn't match it's encoded entry
+        let mut mismatched_pool = certificate_pool.clone();
+        let position = mismatched_pool
+            .iter()
+            .position(|pool_entry| pool_entry.seq_num().as_u64() == 13)
+            .unwrap();
+        mismatched_pool[position].entry = get_entry(1, 13).await.entry;
+        assert!(matches!(
+            validate_certificate_pool(&entry, &entry, &mismatched_pool),
+            Err(ValidateEntryError::EncodedEntryMismatch)
+        ));
+    }
 }
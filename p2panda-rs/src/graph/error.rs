@@ -31,6 +31,10 @@ pub enum GraphError {
     #[error("Requested trim nodes not found in graph")]
     InvalidTrimNodes,
 
+    /// Passed nodes are not in the order the graph is sorted in.
+    #[error("Passed nodes do not follow the sorted order of the graph")]
+    InvalidSortedNodes,
+
     /// Requested trim nodes not found in graph.
     #[error(transparent)]
     ReducerError(#[from] ReducerError),
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use log::debug;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::Hash;
 
@@ -116,6 +116,28 @@ impl<V: PartialEq + Clone + Debug> GraphData<V> {
     }
 }
 
+/// Reducer which ignores all values, used when walking a graph only to sort it's nodes.
+struct SkipReducer;
+
+impl<V> Reducer<V> for SkipReducer {
+    type Error = ReducerError;
+
+    fn combine(&mut self, _value: &V) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// State of a walk over a graph, used to continue sorting a graph after new nodes were added to
+/// it.
+#[derive(Debug, Clone)]
+pub(crate) struct WalkState<K> {
+    /// Keys of all nodes visited so far.
+    visited: HashSet<K>,
+
+    /// Keys of nodes waiting to be visited, the last one is visited next.
+    queue: Vec<K>,
+}
+
 impl<K, V> Node<K, V>
 where
     K: Hash + Ord + PartialOrd + Eq + PartialEq + Clone + Debug,
@@ -225,32 +247,21 @@ where
     }
 
     /// Check if all a nodes dependencies have been visited.
-    fn dependencies_visited(&self, sorted: &[&Node<K, V>], node: &Node<K, V>) -> bool {
-        let mut has_dependencies = true;
-        let previous_nodes = node.previous();
-
-        for node_key in previous_nodes {
-            let node = self.get_node(node_key).expect("Node not in graph");
-            if !sorted.contains(&node) {
-                has_dependencies = false
-            }
-        }
-
-        has_dependencies
+    fn dependencies_visited(&self, visited: &HashSet<K>, node: &Node<K, V>) -> bool {
+        node.previous()
+            .iter()
+            .all(|node_key| visited.contains(node_key))
     }
 
     /// Returns the next un-visited node following the passed node.
-    fn next(&'a self, sorted: &[&Node<K, V>], node: &Node<K, V>) -> Option<Vec<&'a Node<K, V>>> {
+    fn next(&'a self, visited: &HashSet<K>, node: &Node<K, V>) -> Option<Vec<&'a Node<K, V>>> {
         let mut next_nodes: Vec<&'a Node<K, V>> = Vec::new();
 
         for node_key in node.next() {
             // Nodes returned by `next()` have always been added by `add_link()`, which ensures
             // that these keys all have corresponding nodes in the graph so we can unwrap here.
             let node = self.get_node(node_key).unwrap();
-            if !sorted
-                .iter()
-                .any(|sorted_node| sorted_node.key() == node.key())
-            {
+            if !visited.contains(node.key()) {
                 next_nodes.push(node)
             }
         }
@@ -372,25 +383,86 @@ where
         key: &K,
         reducer: &mut impl Reducer<V>,
     ) -> Result<GraphData<V>, GraphError> {
-        let root_node = match self.get_node(key) {
-            Some(node) => Ok(node),
-            None => Err(GraphError::NodeNotFound),
-        }?;
-        let mut queue = vec![root_node];
-        let mut sorted_nodes = vec![];
+        let state = WalkState {
+            visited: HashSet::new(),
+            queue: vec![key.to_owned()],
+        };
+
+        self.walk_from_state(state, reducer)
+    }
+
+    /// Sorts the graph topologically, continuing a walk from it's passed state.
+    ///
+    /// Nodes which were already visited don't need to be part of the graph, all queued nodes do.
+    pub(crate) fn walk_from_state(
+        &'a self,
+        state: WalkState<K>,
+        reducer: &mut impl Reducer<V>,
+    ) -> Result<GraphData<V>, GraphError> {
+        let (graph_data, _) = self.walk(state, reducer, None)?;
+        Ok(graph_data)
+    }
+
+    /// Sorts the graph topologically starting from the root node, stopping right before the node
+    /// with the passed key is visited.
+    ///
+    /// Returns the data of all nodes visited before and the state of the walk, which can be
+    /// continued with `walk_from_state`. Returns an error if the walk doesn't visit the node.
+    pub(crate) fn walk_until(&'a self, key: &K) -> Result<(Vec<V>, WalkState<K>), GraphError> {
+        let state = WalkState {
+            visited: HashSet::new(),
+            queue: vec![self.root_node_key()?.to_owned()],
+        };
+
+        match self.walk(state, &mut SkipReducer, Some(key))? {
+            (graph_data, Some(state)) => Ok((graph_data.sorted, state)),
+            (_, None) => Err(GraphError::NodeNotFound),
+        }
+    }
+
+    /// Sorts the graph topologically, continuing a walk from it's passed state.
+    ///
+    /// When a `stop` key is passed the walk ends right before visiting this node and returns it's
+    /// state at that point.
+    fn walk(
+        &'a self,
+        state: WalkState<K>,
+        reducer: &mut impl Reducer<V>,
+        stop: Option<&K>,
+    ) -> Result<(GraphData<V>, Option<WalkState<K>>), GraphError> {
+        let WalkState { mut visited, queue } = state;
+        let mut queue = queue
+            .iter()
+            .map(|key| self.get_node(key).ok_or(GraphError::NodeNotFound))
+            .collect::<Result<Vec<&Node<K, V>>, GraphError>>()?;
         let mut graph_data = GraphData {
             sorted: vec![],
             graph_tips: vec![],
         };
 
+        // Returns the state of the walk right before the passed node is visited.
+        let stop_state = |visited: HashSet<K>, queue: &[&Node<K, V>], node: &Node<K, V>| {
+            let queue = queue
+                .iter()
+                .chain(std::iter::once(&node))
+                .map(|node| node.key().to_owned())
+                .collect();
+            WalkState { visited, queue }
+        };
+
         // Pop from the queue while it has items.
         while let Some(mut current_node) = queue.pop() {
+            if stop == Some(current_node.key()) {
+                let state = stop_state(visited, &queue, current_node);
+                return Ok((graph_data, Some(state)));
+            }
+
             // If the sorted stack is bigger than the number of existing nodes we have a cycle.
-            if sorted_nodes.len() > self.0.len() {
+            if graph_data.sorted.len() > self.0.len() {
                 return Err(GraphError::CycleDetected);
             }
             // Push this node to the sorted stack...
-            sorted_nodes.push(current_node);
+            visited.insert(current_node.key().to_owned());
             graph_data.sorted.push(current_node.data());
             if current_node.is_tip() {
                 graph_data.graph_tips.push(current_node.data())
@@ -404,11 +476,11 @@ where
             debug!(
                 "{:?}: sorted to position {}",
                 current_node.key(),
-                sorted_nodes.len()
+                graph_data.sorted.len()
             );
 
             // ...and then walk the graph starting from this node.
-            while let Some(mut next_nodes) = self.next(&sorted_nodes, current_node) {
+            while let Some(mut next_nodes) = self.next(&visited, current_node) {
                 // Pop off the next node we will visit.
                 //
                 // Nodes returned by `next()` have always been added by `add_link()`, which ensures
@@ -424,7 +496,7 @@ where
 
                 // If it's a merge node, check it's dependencies have all been visited.
                 if next_node.is_merge() {
-                    if self.dependencies_visited(&sorted_nodes, next_node) {
+                    if self.dependencies_visited(&visited, next_node) {
                         // If they have been, push this node to the queue and exit this loop.
                         debug!(
                             "{:?}: is merge and has all dependencies met",
@@ -447,8 +519,14 @@ where
                     );
                     break;
                 }
+
+                if stop == Some(next_node.key()) {
+                    let state = stop_state(visited, &queue, next_node);
+                    return Ok((graph_data, Some(state)));
+                }
+
                 // If it wasn't a merge node, push it to the sorted stack and keep walking.
-                sorted_nodes.push(next_node);
+                visited.insert(next_node.key().to_owned());
                 graph_data.sorted.push(next_node.data());
 
                 // If it is a tip, push it to the graph tips list.
@@ -464,12 +542,12 @@ where
                 debug!(
                     "{:?}: sorted to position {}",
                     next_node.key(),
-                    sorted_nodes.len()
+                    graph_data.sorted.len()
                 );
                 current_node = next_node;
             }
         }
-        Ok(graph_data)
+        Ok((graph_data, None))
     }
 
     /// Sort the entire graph, starting from the root node.
@@ -494,7 +572,7 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::graph::error::ReducerError;
+    use crate::graph::error::{GraphError, ReducerError};
     use crate::graph::{Graph, Reducer};
 
     use super::GraphData;
@@ -629,6 +707,66 @@ mod test {
         assert_eq!(reducer.acc, "ABCIJKGHDEF".to_string());
     }
 
+    #[test]
+    fn can_continue_walk() {
+        let mut graph: Graph<char, char> = Graph::default();
+        let links = [
+            ('a', 'b'),
+            ('b', 'c'),
+            ('c', 'd'),
+            ('d', 'e'),
+            ('e', 'f'),
+            ('a', 'g'),
+            ('g', 'h'),
+            ('h', 'd'),
+            ('c', 'i'),
+            ('i', 'j'),
+            ('j', 'k'),
+            ('k', 'f'),
+        ];
+
+        for key in "abcdefghijk".chars() {
+            graph.add_node(&key, key.to_ascii_uppercase());
+        }
+        for (from, to) in links {
+            graph.add_link(&from, &to);
+        }
+
+        //             /--[I]<--[J]<--[K]<--\
+        //  /--[B]<--[C]--\                  \
+        // [A]<--[G]<-----[H]<--[D]<--[E]<---[F]
+        //
+
+        // Stop the walk right before [C] is visited and continue it from there.
+        let (sorted, state) = graph.walk_until(&'c').unwrap();
+        assert_eq!(sorted, vec!['A', 'B']);
+
+        let mut reducer = CharReducer::default();
+        let graph_data = graph.walk_from_state(state, &mut reducer).unwrap();
+
+        assert_eq!(
+            graph_data.sorted(),
+            vec!['C', 'I', 'J', 'K', 'G', 'H', 'D', 'E', 'F']
+        );
+        assert_eq!(reducer.acc, "CIJKGHDEF".to_string());
+
+        // [G] was queued after visiting [A] and is taken from the queue later.
+        let (sorted, state) = graph.walk_until(&'g').unwrap();
+        assert_eq!(sorted, vec!['A', 'B', 'C', 'I', 'J', 'K']);
+
+        let mut reducer = CharReducer::default();
+        let graph_data = graph.walk_from_state(state, &mut reducer).unwrap();
+
+        assert_eq!(graph_data.sorted(), vec!['G', 'H', 'D', 'E', 'F']);
+        assert_eq!(reducer.acc, "GHDEF".to_string());
+
+        // The walk never visits a node which is not part of the graph.
+        assert!(matches!(
+            graph.walk_until(&'z'),
+            Err(GraphError::NodeNotFound)
+        ));
+    }
+
     #[test]
     fn has_cycle() {
         let mut graph = Graph::new();
@@ -133,6 +133,7 @@ mod operation_value;
 mod operation_version;
 pub mod plain;
 mod relation;
+mod text_delta;
 pub mod traits;
 pub mod validate;
 
@@ -144,3 +145,4 @@ pub use operation_id::OperationId;
 pub use operation_value::OperationValue;
 pub use operation_version::OperationVersion;
 pub use relation::{PinnedRelation, PinnedRelationList, Relation, RelationList};
+pub use text_delta::{TextDelta, TextEdit};
@@ -3,7 +3,7 @@
 use serde::Serialize;
 
 use crate::document::{DocumentId, DocumentViewId};
-use crate::operation::{PinnedRelation, PinnedRelationList, Relation, RelationList};
+use crate::operation::{PinnedRelation, PinnedRelationList, Relation, RelationList, TextDelta};
 
 /// Enum of possible data types which can be added to the operations fields as values.
 #[derive(Clone, Debug, PartialEq, Serialize)]
@@ -35,6 +35,9 @@ pub enum OperationValue {
 
     /// Reference to a list of document views.
     PinnedRelationList(PinnedRelationList),
+
+    /// Edits of a collaborative text.
+    Text(TextDelta),
 }
 
 impl OperationValue {
@@ -50,6 +53,7 @@ impl OperationValue {
             OperationValue::RelationList(_) => "relation_list",
             OperationValue::PinnedRelation(_) => "pinned_relation",
             OperationValue::PinnedRelationList(_) => "pinned_relation_list",
+            OperationValue::Text(_) => "text",
         }
     }
 }
@@ -90,6 +94,12 @@ impl From<&[u8]> for OperationValue {
     }
 }
 
+impl From<TextDelta> for OperationValue {
+    fn from(value: TextDelta) -> Self {
+        OperationValue::Text(value)
+    }
+}
+
 impl From<DocumentId> for OperationValue {
     fn from(value: DocumentId) -> Self {
         OperationValue::Relation(Relation::new(value))
@@ -120,7 +130,7 @@ mod tests {
 
     use crate::document::{DocumentId, DocumentViewId};
     use crate::operation::{
-        OperationId, PinnedRelation, PinnedRelationList, Relation, RelationList,
+        OperationId, PinnedRelation, PinnedRelationList, Relation, RelationList, TextDelta,
     };
     use crate::test_utils::fixtures::{document_id, document_view_id, random_operation_id};
 
@@ -157,6 +167,9 @@ mod tests {
             vec![DocumentViewId::new(&[operation_id])],
         ));
         assert_eq!(pinned_relation_list.field_type(), "pinned_relation_list");
+
+        let text_delta = OperationValue::Text(TextDelta::from("Hello"));
+        assert_eq!(text_delta.field_type(), "text");
     }
 
     #[rstest]
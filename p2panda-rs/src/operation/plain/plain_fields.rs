@@ -141,6 +141,7 @@ impl From<&OperationFields> for PlainFields {
                 OperationValue::PinnedRelationList(list) => {
                     list.document_view_ids().to_vec().into()
                 }
+                OperationValue::Text(delta) => PlainValue::TextDelta(delta.to_owned()),
             };
 
             // Unwrap here because we already know that there are no duplicates in
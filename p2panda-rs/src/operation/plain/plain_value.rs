@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use crate::document::{DocumentId, DocumentViewId};
 use crate::hash::{Hash, HashId};
 use crate::operation::error::PlainValueError;
+use crate::operation::TextDelta;
 
 /// Operation field values which have not been checked against a schema yet.
 ///
@@ -42,6 +43,9 @@ pub enum PlainValue {
 
     /// List of a list of hashes which is a pinned relation list.
     PinnedRelationList(Vec<Vec<Hash>>),
+
+    /// List of `[position, delete, insert]` arrays which are edits of a collaborative text.
+    TextDelta(TextDelta),
 }
 
 impl PlainValue {
@@ -57,6 +61,7 @@ impl PlainValue {
             PlainValue::BytesOrRelation(_) => "bytes",
             PlainValue::AmbiguousRelation(_) => "hash[]",
             PlainValue::PinnedRelationList(_) => "hash[][]",
+            PlainValue::TextDelta(_) => "text_delta",
         }
     }
 }
@@ -123,6 +128,12 @@ impl From<&str> for PlainValue {
     }
 }
 
+impl From<TextDelta> for PlainValue {
+    fn from(value: TextDelta) -> Self {
+        PlainValue::TextDelta(value)
+    }
+}
+
 impl From<DocumentId> for PlainValue {
     fn from(value: DocumentId) -> Self {
         PlainValue::BytesOrRelation(hex::decode(value.as_str()).unwrap())
@@ -210,26 +221,36 @@ fn to_plain_value_list(
 
     // Next we try and parse into a list of `Vec<Hash>` which means this is a `PinnedRelationList`
     // value
-    let mut pinned_relations = Vec::new();
-    for inner_array in array {
-        let inner_array = match inner_array.as_array() {
-            Some(array) => Ok(array),
-            None => Err(PlainValueError::UnsupportedValue),
-        }?;
-
-        let pinned_relation: Result<Vec<Hash>, PlainValueError> = inner_array
-            .iter()
-            .map(|value| {
-                let hex_str = to_hex_str(value)?;
-                let hash = Hash::new(&hex_str).map_err(|_| PlainValueError::UnsupportedValue)?;
-                Ok(hash)
-            })
-            .collect();
-
-        pinned_relations.push(pinned_relation?);
-    }
+    let pinned_relations: Result<Vec<Vec<Hash>>, PlainValueError> = array
+        .iter()
+        .map(|inner_array| {
+            let inner_array = match inner_array.as_array() {
+                Some(array) => Ok(array),
+                None => Err(PlainValueError::UnsupportedValue),
+            }?;
+
+            inner_array
+                .iter()
+                .map(|value| {
+                    let hex_str = to_hex_str(value)?;
+                    let hash =
+                        Hash::new(&hex_str).map_err(|_| PlainValueError::UnsupportedValue)?;
+                    Ok(hash)
+                })
+                .collect()
+        })
+        .collect();
 
-    Ok(PlainValue::PinnedRelationList(pinned_relations))
+    if let Ok(pinned_relations) = pinned_relations {
+        return Ok(PlainValue::PinnedRelationList(pinned_relations));
+    };
+
+    // Finally we try to parse the list of `[position, delete, insert]` arrays of a `TextDelta`
+    let text_delta = Value::Array(array)
+        .deserialized::<TextDelta>()
+        .map_err(|_| PlainValueError::UnsupportedValue)?;
+
+    Ok(PlainValue::TextDelta(text_delta))
 }
 
 #[cfg(test)]
@@ -240,6 +261,7 @@ mod tests {
 
     use crate::document::{DocumentId, DocumentViewId};
     use crate::hash::{Hash, HashId};
+    use crate::operation::{TextDelta, TextEdit};
     use crate::serde::{deserialize_into, hex_string_to_bytes, serialize_from, serialize_value};
     use crate::test_utils::fixtures::{document_id, document_view_id, random_hash};
 
@@ -328,6 +350,11 @@ mod tests {
             serialize_value(cbor!("username"))
         );
 
+        assert_eq!(
+            serialize_from(PlainValue::TextDelta(TextDelta::from("Hi"))),
+            serialize_value(cbor!([[0, 0, "Hi"]]))
+        );
+
         assert_eq!(
             serialize_from(PlainValue::AmbiguousRelation(vec![])),
             serialize_value(cbor!([]))
@@ -359,6 +386,14 @@ mod tests {
             deserialize_into::<PlainValue>(&serialize_value(cbor!([]))).unwrap(),
             PlainValue::AmbiguousRelation(vec![])
         );
+        assert_eq!(
+            deserialize_into::<PlainValue>(&serialize_value(cbor!([[0, 0, "Hi"], [1, 1, "o"]])))
+                .unwrap(),
+            PlainValue::TextDelta(TextDelta::new(vec![
+                TextEdit::insert(0, "Hi"),
+                TextEdit::new(1, 1, "o")
+            ]))
+        );
     }
 
     #[test]
@@ -379,6 +414,10 @@ mod tests {
             serde_json::from_str::<PlainValue>("[]").unwrap(),
             PlainValue::AmbiguousRelation(vec![])
         );
+        assert_eq!(
+            serde_json::from_str::<PlainValue>("[[2, 0, \"!\"]]").unwrap(),
+            PlainValue::TextDelta(TextEdit::insert(2, "!").into())
+        );
         assert_eq!(
             serde_json::from_str::<PlainValue>(
                 "[[\"00200801063d8aaba76c283a2fb63cf5cd4ec86765424452ce7327fda04c5da80d62\"]]"
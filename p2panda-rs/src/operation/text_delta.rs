@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::convert::TryFrom;
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// Edit of a collaborative text field, deleting and inserting characters at a position.
+///
+/// Positions and lengths count unicode scalar values (`char`s) of the text. They are resolved
+/// against the text of the document view the operation refers to in its `previous` field,
+/// positions beyond the end of the text are clamped to it.
+///
+/// Edits are encoded as `[position, delete, insert]` arrays.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TextEdit(u64, u64, String);
+
+impl TextEdit {
+    /// Returns an edit deleting `delete` characters at `position` and inserting `insert` there.
+    pub fn new(position: u64, delete: u64, insert: &str) -> Self {
+        Self(position, delete, insert.to_owned())
+    }
+
+    /// Returns an edit inserting text at a position.
+    pub fn insert(position: u64, insert: &str) -> Self {
+        Self::new(position, 0, insert)
+    }
+
+    /// Returns an edit deleting characters at a position.
+    pub fn delete(position: u64, delete: u64) -> Self {
+        Self::new(position, delete, "")
+    }
+
+    /// Returns the position of this edit.
+    pub fn position(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of deleted characters.
+    pub fn deleted(&self) -> u64 {
+        self.1
+    }
+
+    /// Returns the inserted text.
+    pub fn inserted(&self) -> &str {
+        &self.2
+    }
+
+    /// Returns the deleted range of this edit in a text with the passed number of characters.
+    pub(crate) fn range(&self, len: usize) -> (usize, usize) {
+        let position = usize::try_from(self.0).unwrap_or(usize::MAX).min(len);
+        let delete = usize::try_from(self.1).unwrap_or(usize::MAX);
+        (position, position.saturating_add(delete).min(len))
+    }
+}
+
+/// Field type representing changes to a collaborative text.
+///
+/// Operations don't write the whole text but a list of edits which are applied in order, every
+/// edit refers to the text resulting from the previous one. Concurrent edits are merged when
+/// building the document, the resolved text is available as a string in its fields.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TextDelta(Vec<TextEdit>);
+
+impl TextDelta {
+    /// Returns a new text delta.
+    pub fn new(edits: Vec<TextEdit>) -> Self {
+        Self(edits)
+    }
+
+    /// Returns the edits of this delta.
+    pub fn edits(&self) -> &[TextEdit] {
+        self.0.as_slice()
+    }
+
+    /// Returns iterator over edits.
+    pub fn iter(&self) -> Iter<'_, TextEdit> {
+        self.0.iter()
+    }
+
+    /// Apply all edits to a text and return the result.
+    pub fn apply(&self, text: &str) -> String {
+        let mut chars: Vec<char> = text.chars().collect();
+
+        for edit in self.iter() {
+            let (start, end) = edit.range(chars.len());
+            chars.splice(start..end, edit.inserted().chars());
+        }
+
+        chars.into_iter().collect()
+    }
+}
+
+impl From<TextEdit> for TextDelta {
+    fn from(value: TextEdit) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl From<&str> for TextDelta {
+    fn from(value: &str) -> Self {
+        Self(vec![TextEdit::insert(0, value)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{TextDelta, TextEdit};
+
+    #[rstest]
+    #[case::insert("Hello", vec![TextEdit::insert(5, " Panda")], "Hello Panda")]
+    #[case::delete("Hello Panda", vec![TextEdit::delete(0, 6)], "Panda")]
+    #[case::replace("Hello Panda", vec![TextEdit::new(6, 5, "Penguin")], "Hello Penguin")]
+    #[case::consecutive("", vec![TextEdit::insert(0, "ac"), TextEdit::insert(1, "b")], "abc")]
+    #[case::characters("Grüße", vec![TextEdit::new(3, 1, "ss")], "Grüsse")]
+    #[case::clamped("Panda", vec![TextEdit::new(3, 10, "!"), TextEdit::insert(99, "?")], "Pan!?")]
+    fn apply(#[case] text: &str, #[case] edits: Vec<TextEdit>, #[case] expected: &str) {
+        assert_eq!(TextDelta::new(edits).apply(text), expected);
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(TextDelta::from("Panda").apply(""), "Panda");
+    }
+}
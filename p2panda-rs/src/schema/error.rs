@@ -3,7 +3,7 @@
 //! Error types for creating schema instances and schema ids.
 use thiserror::Error;
 
-use crate::schema::SchemaId;
+use crate::schema::{FieldType, MergeStrategy, SchemaId};
 
 /// Custom errors related to `SchemaName`.
 #[derive(Clone, Error, Debug)]
@@ -41,6 +41,14 @@ pub enum SchemaFieldError {
     /// Schema fields cannot contain duplicate fields.
     #[error("Schema fields cannot contain duplicate field names")]
     DuplicateFields,
+
+    /// A merge strategy was set for a field which doesn't exist.
+    #[error("Schema field {0} does not exist")]
+    UnknownField(String),
+
+    /// A merge strategy was set for a field of a type it can't merge.
+    #[error("Merge strategy {1} can not be used for schema field {0} of type {2}")]
+    IncompatibleMergeStrategy(String, MergeStrategy, FieldType),
 }
 
 /// Custom errors related to `SchemaId`.
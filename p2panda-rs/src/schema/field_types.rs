@@ -38,6 +38,10 @@ pub enum FieldType {
     /// Defines a text string field.
     String,
 
+    /// Defines a collaborative text field, written with [`TextDelta`][`crate::operation::TextDelta`]
+    /// edits which are merged when building the document.
+    Text,
+
     /// Defines a [`Relation`][`crate::operation::Relation`] field that references the given
     /// schema.
     Relation(SchemaId),
@@ -65,6 +69,7 @@ impl Display for FieldType {
             FieldType::Integer => "int".to_string(),
             FieldType::Float => "float".to_string(),
             FieldType::String => "str".to_string(),
+            FieldType::Text => "text".to_string(),
             FieldType::Relation(schema_id) => format!("relation({})", schema_id),
             FieldType::RelationList(schema_id) => {
                 format!("relation_list({})", schema_id)
@@ -97,6 +102,7 @@ impl FromStr for FieldType {
             "int" => Ok(FieldType::Integer),
             "float" => Ok(FieldType::Float),
             "str" => Ok(FieldType::String),
+            "text" => Ok(FieldType::Text),
             "bytes" => Ok(FieldType::Bytes),
             _ => Err(FieldTypeError::InvalidFieldType(s.into())),
         };
@@ -146,6 +152,7 @@ mod tests {
         assert_eq!(FieldType::Boolean.to_string(), "bool");
         assert_eq!(FieldType::Integer.to_string(), "int");
         assert_eq!(FieldType::Float.to_string(), "float");
+        assert_eq!(FieldType::Text.to_string(), "text");
         assert_eq!(FieldType::String.to_string(), "str");
         assert_eq!(FieldType::Bytes.to_string(), "bytes");
         assert_eq!(
@@ -171,6 +178,7 @@ mod tests {
         assert_eq!(FieldType::Boolean, "bool".parse().unwrap());
         assert_eq!(FieldType::Integer, "int".parse().unwrap());
         assert_eq!(FieldType::Float, "float".parse().unwrap());
+        assert_eq!(FieldType::Text, "text".parse().unwrap());
         assert_eq!(FieldType::String, "str".parse().unwrap());
         assert_eq!(FieldType::Bytes, "bytes".parse().unwrap());
         assert_eq!(
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::fmt::Display;
+
+use crate::schema::FieldType;
+
+/// Strategies for merging the values written to a schema field by concurrent or consecutive
+/// operations when materialising a document.
+///
+/// Merge strategies are a local setting of a [`Schema`][`crate::schema::Schema`], they are not
+/// part of published schema definitions. Fields without a strategy use `LastWriteWins`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MergeStrategy {
+    /// The value of the last operation in the sorted operation graph wins.
+    #[default]
+    LastWriteWins,
+
+    /// Integer counter. The CREATE operation sets the initial value, every UPDATE operation
+    /// writes a delta which is added to it.
+    Counter,
+
+    /// Grow-only set of relations. Every operation adds the documents of it's relation list,
+    /// documents are never removed.
+    GrowOnlySet,
+
+    /// Add-wins set of relations. Documents missing from the relation list of an operation are
+    /// removed, unless they were added by a concurrent operation the writer didn't know about.
+    AddWinsSet,
+
+    /// Register holding the largest value ever written.
+    Max,
+
+    /// Register holding the smallest value ever written.
+    Min,
+}
+
+impl MergeStrategy {
+    /// Returns true if fields of this type can be merged with this strategy.
+    pub fn is_compatible(&self, field_type: &FieldType) -> bool {
+        match self {
+            MergeStrategy::LastWriteWins => true,
+            MergeStrategy::Counter => field_type == &FieldType::Integer,
+            MergeStrategy::GrowOnlySet | MergeStrategy::AddWinsSet => {
+                matches!(field_type, FieldType::RelationList(_))
+            }
+            MergeStrategy::Max | MergeStrategy::Min => {
+                matches!(field_type, FieldType::Integer | FieldType::Float)
+            }
+        }
+    }
+}
+
+impl Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let strategy_str = match self {
+            MergeStrategy::LastWriteWins => "last_write_wins",
+            MergeStrategy::Counter => "counter",
+            MergeStrategy::GrowOnlySet => "grow_only_set",
+            MergeStrategy::AddWinsSet => "add_wins_set",
+            MergeStrategy::Max => "max",
+            MergeStrategy::Min => "min",
+        };
+
+        write!(f, "{}", strategy_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::schema::{FieldType, SchemaId};
+
+    use super::MergeStrategy;
+
+    #[rstest]
+    #[case(MergeStrategy::LastWriteWins, FieldType::Bytes, true)]
+    #[case(MergeStrategy::Counter, FieldType::Integer, true)]
+    #[case(MergeStrategy::Counter, FieldType::Float, false)]
+    #[case(
+        MergeStrategy::GrowOnlySet,
+        FieldType::RelationList(SchemaId::SchemaDefinition(1)),
+        true
+    )]
+    #[case(
+        MergeStrategy::AddWinsSet,
+        FieldType::PinnedRelationList(SchemaId::SchemaDefinition(1)),
+        false
+    )]
+    #[case(MergeStrategy::Max, FieldType::Float, true)]
+    #[case(MergeStrategy::Min, FieldType::String, false)]
+    fn compatible_field_types(
+        #[case] strategy: MergeStrategy,
+        #[case] field_type: FieldType,
+        #[case] is_compatible: bool,
+    ) {
+        assert_eq!(strategy.is_compatible(&field_type), is_compatible);
+    }
+}
@@ -3,6 +3,7 @@
 //! Schemas describe the format of data used in operation fields.
 pub mod error;
 mod field_types;
+mod merge_strategy;
 #[allow(clippy::module_inception)]
 mod schema;
 mod schema_description;
@@ -13,6 +14,7 @@ pub mod system;
 pub mod validate;
 
 pub use field_types::FieldType;
+pub use merge_strategy::MergeStrategy;
 pub use schema::{FieldName, Schema};
 pub use schema_description::SchemaDescription;
 pub use schema_fields::SchemaFields;
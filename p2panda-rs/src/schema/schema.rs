@@ -10,7 +10,7 @@ use crate::schema::system::{
     SchemaView,
 };
 use crate::schema::SchemaName;
-use crate::schema::{FieldType, SchemaId, SchemaVersion};
+use crate::schema::{FieldType, MergeStrategy, SchemaId, SchemaVersion};
 use crate::schema::{SchemaDescription, SchemaFields};
 use crate::Human;
 
@@ -279,6 +279,21 @@ impl Schema {
     pub fn fields(&self) -> &SchemaFields {
         &self.fields
     }
+
+    /// Returns this schema with a merge strategy set for one of it's fields.
+    ///
+    /// Merge strategies decide how the values written to a field are combined when a document
+    /// of this schema is built with `DocumentBuilder::with_schema`. They are not part of the
+    /// published schema definition, nodes need to agree on them to arrive at the same document
+    /// state.
+    pub fn with_merge_strategy(
+        mut self,
+        name: &str,
+        strategy: MergeStrategy,
+    ) -> Result<Self, SchemaError> {
+        self.fields.set_merge_strategy(name, strategy)?;
+        Ok(self)
+    }
 }
 
 impl Display for Schema {
@@ -302,9 +317,11 @@ mod tests {
     use crate::document::DocumentViewId;
     use crate::document::{DocumentView, DocumentViewFields, DocumentViewValue};
     use crate::operation::{OperationId, OperationValue, PinnedRelationList};
+    use crate::schema::error::{SchemaError, SchemaFieldError};
     use crate::schema::system::{SchemaFieldView, SchemaView};
     use crate::schema::{
-        FieldType, Schema, SchemaDescription, SchemaFields, SchemaId, SchemaName, SchemaVersion,
+        FieldType, MergeStrategy, Schema, SchemaDescription, SchemaFields, SchemaId, SchemaName,
+        SchemaVersion,
     };
     use crate::test_utils::fixtures::{document_view_id, random_operation_id};
     use crate::Human;
@@ -649,4 +666,54 @@ mod tests {
         )
         .is_err());
     }
+
+    #[rstest]
+    fn merge_strategies(#[from(document_view_id)] schema_view_id: DocumentViewId) {
+        let schema_id =
+            SchemaId::new_application(&SchemaName::new("venue").unwrap(), &schema_view_id);
+        let schema = Schema::new(
+            &schema_id,
+            "Some venues",
+            &[
+                ("name", FieldType::String),
+                ("visitors", FieldType::Integer),
+            ],
+        )
+        .unwrap();
+        assert!(!schema.fields().has_merge_strategies());
+
+        let schema = schema
+            .with_merge_strategy("visitors", MergeStrategy::Counter)
+            .unwrap();
+        assert!(schema.fields().has_merge_strategies());
+        assert_eq!(
+            schema.fields().merge_strategy("visitors"),
+            MergeStrategy::Counter
+        );
+        assert_eq!(
+            schema.fields().merge_strategy("name"),
+            MergeStrategy::LastWriteWins
+        );
+
+        // Setting last-write-wins again removes the strategy.
+        let schema = schema
+            .with_merge_strategy("visitors", MergeStrategy::LastWriteWins)
+            .unwrap();
+        assert!(!schema.fields().has_merge_strategies());
+
+        assert!(matches!(
+            schema
+                .clone()
+                .with_merge_strategy("name", MergeStrategy::Max),
+            Err(SchemaError::SchemaFieldsError(
+                SchemaFieldError::IncompatibleMergeStrategy(..)
+            ))
+        ));
+        assert!(matches!(
+            schema.with_merge_strategy("address", MergeStrategy::Counter),
+            Err(SchemaError::SchemaFieldsError(
+                SchemaFieldError::UnknownField(_)
+            ))
+        ));
+    }
 }
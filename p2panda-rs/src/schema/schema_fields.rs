@@ -5,12 +5,19 @@ use std::collections::BTreeMap;
 
 use crate::schema::error::SchemaFieldError;
 use crate::schema::validate::validate_field_name;
-use crate::schema::FieldType;
+use crate::schema::{FieldType, MergeStrategy};
 use crate::Validate;
 
 /// The fields definitions of a [`Schema`].
 #[derive(Clone, Debug, PartialEq, Default, Eq)]
-pub struct SchemaFields(BTreeMap<String, FieldType>);
+pub struct SchemaFields {
+    /// Maps field names to their types.
+    fields: BTreeMap<String, FieldType>,
+
+    /// Maps field names to their merge strategies, fields which are not contained use
+    /// `MergeStrategy::LastWriteWins`.
+    merge_strategies: BTreeMap<String, MergeStrategy>,
+}
 
 impl SchemaFields {
     /// Creates a new schema fields instance from a vector of key values.
@@ -27,7 +34,10 @@ impl SchemaFields {
         for (key, value) in fields {
             schema_fields.insert(key.to_string(), value.to_owned());
         }
-        let schema_fields = Self(schema_fields);
+        let schema_fields = Self {
+            fields: schema_fields,
+            merge_strategies: BTreeMap::new(),
+        };
 
         // Validate the schema fields.
         schema_fields.validate()?;
@@ -36,31 +46,69 @@ impl SchemaFields {
 
     /// Returns the number of added fields.
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.fields.len()
     }
 
     /// Returns true when no field is given.
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.fields.is_empty()
     }
 
     /// Returns a field value.
     pub fn get(&self, name: &str) -> Option<&FieldType> {
-        if !self.0.contains_key(name) {
+        if !self.fields.contains_key(name) {
             return None;
         }
 
-        self.0.get(name)
+        self.fields.get(name)
     }
 
     /// Returns an array of existing schema field keys.
     pub fn keys(&self) -> Vec<String> {
-        self.0.keys().cloned().collect()
+        self.fields.keys().cloned().collect()
     }
 
     /// Returns an iterator of existing schema fields.
     pub fn iter(&self) -> Iter<String, FieldType> {
-        self.0.iter()
+        self.fields.iter()
+    }
+
+    /// Returns the merge strategy of a field.
+    pub fn merge_strategy(&self, name: &str) -> MergeStrategy {
+        self.merge_strategies.get(name).copied().unwrap_or_default()
+    }
+
+    /// Returns true if any field uses another merge strategy than `LastWriteWins`.
+    pub fn has_merge_strategies(&self) -> bool {
+        !self.merge_strategies.is_empty()
+    }
+
+    /// Sets the merge strategy of a field.
+    ///
+    /// Returns an error if the field doesn't exist or the strategy can't be used with it's type.
+    pub fn set_merge_strategy(
+        &mut self,
+        name: &str,
+        strategy: MergeStrategy,
+    ) -> Result<(), SchemaFieldError> {
+        let field_type = self
+            .get(name)
+            .ok_or_else(|| SchemaFieldError::UnknownField(name.to_owned()))?;
+
+        if !strategy.is_compatible(field_type) {
+            return Err(SchemaFieldError::IncompatibleMergeStrategy(
+                name.to_owned(),
+                strategy,
+                field_type.to_owned(),
+            ));
+        }
+
+        match strategy {
+            MergeStrategy::LastWriteWins => self.merge_strategies.remove(name),
+            strategy => self.merge_strategies.insert(name.to_owned(), strategy),
+        };
+
+        Ok(())
     }
 }
 
@@ -88,7 +136,7 @@ impl Validate for SchemaFields {
         }
 
         // Check there are no more than 1024 fields.
-        if self.0.len() > 1024 {
+        if self.fields.len() > 1024 {
             return Err(SchemaFieldError::TooManyFields);
         }
 
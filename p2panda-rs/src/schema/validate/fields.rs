@@ -10,6 +10,7 @@ use crate::hash::Hash;
 use crate::operation::plain::{PlainFields, PlainValue};
 use crate::operation::{
     OperationFields, OperationValue, PinnedRelation, PinnedRelationList, Relation, RelationList,
+    TextDelta,
 };
 use crate::schema::validate::error::ValidationError;
 use crate::schema::validate::{
@@ -312,6 +313,22 @@ fn validate_field_value(
                 )),
             }
         }
+        FieldType::Text => {
+            match plain_value {
+                PlainValue::TextDelta(text_delta) => {
+                    Ok(OperationValue::Text(text_delta.to_owned()))
+                }
+                // An empty list can't be told apart from an empty relation list, it is a text
+                // delta without any edits.
+                PlainValue::AmbiguousRelation(hashes) if hashes.is_empty() => {
+                    Ok(OperationValue::Text(TextDelta::default()))
+                }
+                _ => Err(ValidationError::InvalidType(
+                    plain_value.field_type().to_owned(),
+                    schema_field_type.to_string(),
+                )),
+            }
+        }
     }
 }
 
@@ -347,7 +364,7 @@ mod tests {
 
     use crate::document::DocumentViewId;
     use crate::operation::plain::{PlainFields, PlainValue};
-    use crate::operation::{OperationFields, OperationValue};
+    use crate::operation::{OperationFields, OperationValue, TextDelta};
     use crate::schema::{FieldType, Schema, SchemaId, SchemaName};
     use crate::test_utils::constants::{HASH, SCHEMA_ID};
     use crate::test_utils::fixtures::{document_view_id, random_hash, schema_id};
@@ -406,6 +423,8 @@ mod tests {
     #[case(PlainValue::Integer(512), FieldType::Integer)]
     #[case(PlainValue::Float(1024.32), FieldType::Float)]
     #[case(PlainValue::Boolean(true), FieldType::Boolean)]
+    #[case(PlainValue::TextDelta(TextDelta::from("Handa")), FieldType::Text)]
+    #[case(PlainValue::AmbiguousRelation(vec![]), FieldType::Text)]
     #[case(
         PlainValue::BytesOrRelation(random_hash().to_bytes()),
         FieldType::Relation(schema_id(SCHEMA_ID))
@@ -456,6 +475,11 @@ mod tests {
         FieldType::Integer,
         "invalid field type 'float', expected 'int'"
     )]
+    #[case(
+        PlainValue::String("Handa".to_string()),
+        FieldType::Text,
+        "invalid field type 'str', expected 'text'"
+    )]
     #[case(
         PlainValue::BytesOrRelation(HASH.as_bytes().to_vec()),
         FieldType::RelationList(schema_id(SCHEMA_ID)),
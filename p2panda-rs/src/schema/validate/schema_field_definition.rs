@@ -25,12 +25,12 @@ pub fn validate_field_name(value: &str) -> bool {
 
 /// Checks "type" field in a schema field definition operation.
 ///
-/// 1. It must be one of: bool, int, float, str, relation, pinned_relation, relation_list,
-///    pinned_relation_list
+/// 1. It must be one of: bool, int, float, str, bytes, text, relation, pinned_relation,
+///    relation_list, pinned_relation_list
 /// 2. Relations need to specify a valid and canonical schema id
 fn validate_type(value: &str) -> bool {
     match value {
-        "bool" | "int" | "float" | "str" | "bytes" => true,
+        "bool" | "int" | "float" | "str" | "bytes" | "text" => true,
         relation => validate_relation_type(relation),
     }
 }
@@ -201,6 +201,7 @@ mod test {
     #[case("str")]
     #[case("bytes")]
     #[case("float")]
+    #[case("text")]
     #[case("relation(schema_field_definition_v1)")]
     #[case("relation(schema_definition_v1)")]
     #[case(concat!(
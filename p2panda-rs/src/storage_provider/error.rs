@@ -81,8 +81,8 @@ pub enum EntryStorageError {
     #[error("Could not find expected skiplink entry in database")]
     ExpectedNextSkiplinkMissing,
 
-    /// Error which originates in `get_all_skiplink_entries_for_entry` if an entry in
-    /// the requested cert pool is missing.
+    /// Error which originates in `get_certificate_pool` if an entry in the requested cert pool is
+    /// missing.
     #[error("Entry required for requested certificate pool missing at seq num: {0}")]
     CertPoolEntryMissing(u64),
 
@@ -107,6 +107,40 @@ pub enum OperationStorageError {
     InsertionError(OperationId),
 }
 
+/// `TransactionStore` errors.
+#[derive(thiserror::Error, Debug)]
+pub enum TransactionError {
+    /// Catch all error which implementers can use for passing their own errors up the chain.
+    #[error("Error occured in TransactionStore: {0}")]
+    Custom(String),
+
+    /// Error returned when a transaction is started on a transaction handle.
+    #[error("Transactions can't be nested")]
+    NestedTransaction,
+
+    /// Error returned when committing or rolling back a handle which is not a transaction in
+    /// progress.
+    #[error("No transaction in progress")]
+    NoTransaction,
+}
+
+/// Errors from building a `DocumentQuery` against a schema.
+#[derive(thiserror::Error, Debug)]
+pub enum DocumentQueryError {
+    /// The queried field is not defined in the schema.
+    #[error("Field {0} does not exist in queried schema")]
+    UnknownField(String),
+
+    /// The filter can't be applied to the field because of it's type or because the filter value
+    /// has a different type.
+    #[error("Filter can not be applied to field {0} of type {1}")]
+    InvalidFilter(String, String),
+
+    /// Documents can't be ordered by a field of this type.
+    #[error("Documents can not be ordered by field {0} of type {1}")]
+    InvalidOrdering(String, String),
+}
+
 /// `DocumentStore` errors.
 #[derive(thiserror::Error, Debug)]
 pub enum DocumentStorageError {
@@ -137,4 +171,26 @@ pub enum DocumentStorageError {
     /// Error returned from `DocumentBuilder`.
     #[error(transparent)]
     DocumentBuilderError(#[from] DocumentBuilderError),
+
+    /// Error returned from building a `DocumentQuery`.
+    #[error(transparent)]
+    DocumentQueryError(#[from] DocumentQueryError),
+}
+
+/// Errors from checking a store with `verify_store`.
+///
+/// Inconsistencies found in the store are not errors, they are collected in the returned report.
+#[derive(thiserror::Error, Debug)]
+pub enum VerifyStoreError {
+    /// Error returned from `LogStore`.
+    #[error(transparent)]
+    LogStorageError(#[from] LogStorageError),
+
+    /// Error returned from `EntryStore`.
+    #[error(transparent)]
+    EntryStorageError(#[from] EntryStorageError),
+
+    /// Error returned from `OperationStore`.
+    #[error(transparent)]
+    OperationStorageError(#[from] OperationStorageError),
 }
@@ -0,0 +1,395 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Events emitted by stores when new data was persisted and types for subscribing to them.
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc::{self, Sender};
+use futures::stream::{BoxStream, StreamExt};
+use log::warn;
+
+use crate::document::{DocumentId, DocumentViewId};
+use crate::entry::{LogId, SeqNum};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::OperationId;
+use crate::schema::SchemaId;
+
+/// Change to the data of a store which subscribers get notified about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreEvent {
+    /// An entry was inserted into a log.
+    EntryInserted {
+        /// Hash of the inserted entry.
+        hash: Hash,
+
+        /// Public key of the entry's author.
+        public_key: PublicKey,
+
+        /// Log the entry was inserted into.
+        log_id: LogId,
+
+        /// Sequence number of the entry.
+        seq_num: SeqNum,
+
+        /// Schema of the operation contained in the entry.
+        schema_id: SchemaId,
+
+        /// Document the operation contained in the entry belongs to.
+        document_id: DocumentId,
+    },
+
+    /// An operation was inserted.
+    OperationInserted {
+        /// Id of the inserted operation.
+        operation_id: OperationId,
+
+        /// Public key of the operation's author.
+        public_key: PublicKey,
+
+        /// Schema of the operation.
+        schema_id: SchemaId,
+
+        /// Document the operation belongs to.
+        document_id: DocumentId,
+    },
+
+    /// A materialised view of a document was inserted.
+    DocumentViewUpdated {
+        /// Id of the inserted document view.
+        view_id: DocumentViewId,
+
+        /// Schema of the document.
+        schema_id: SchemaId,
+
+        /// Document the view belongs to.
+        document_id: DocumentId,
+    },
+}
+
+impl StoreEvent {
+    /// Returns the schema id of the data this event is about.
+    pub fn schema_id(&self) -> &SchemaId {
+        match self {
+            StoreEvent::EntryInserted { schema_id, .. } => schema_id,
+            StoreEvent::OperationInserted { schema_id, .. } => schema_id,
+            StoreEvent::DocumentViewUpdated { schema_id, .. } => schema_id,
+        }
+    }
+
+    /// Returns the id of the document this event is about.
+    pub fn document_id(&self) -> &DocumentId {
+        match self {
+            StoreEvent::EntryInserted { document_id, .. } => document_id,
+            StoreEvent::OperationInserted { document_id, .. } => document_id,
+            StoreEvent::DocumentViewUpdated { document_id, .. } => document_id,
+        }
+    }
+
+    /// Returns the public key of the author of the inserted data.
+    ///
+    /// Document views are derived from operations of possibly many authors and don't have a
+    /// public key.
+    pub fn public_key(&self) -> Option<&PublicKey> {
+        match self {
+            StoreEvent::EntryInserted { public_key, .. } => Some(public_key),
+            StoreEvent::OperationInserted { public_key, .. } => Some(public_key),
+            StoreEvent::DocumentViewUpdated { .. } => None,
+        }
+    }
+}
+
+/// Selects the events a subscriber gets notified about.
+///
+/// An empty filter matches all events, every further condition narrows the selection down.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    schema_id: Option<SchemaId>,
+    document_id: Option<DocumentId>,
+    public_key: Option<PublicKey>,
+}
+
+impl EventFilter {
+    /// Returns a filter which matches all events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match events about data following this schema.
+    pub fn schema_id(mut self, schema_id: &SchemaId) -> Self {
+        self.schema_id = Some(schema_id.to_owned());
+        self
+    }
+
+    /// Only match events about this document.
+    pub fn document_id(mut self, document_id: &DocumentId) -> Self {
+        self.document_id = Some(document_id.to_owned());
+        self
+    }
+
+    /// Only match events about data authored by this public key.
+    ///
+    /// Events about document views are never matched as they don't have a single author.
+    pub fn public_key(mut self, public_key: &PublicKey) -> Self {
+        self.public_key = Some(public_key.to_owned());
+        self
+    }
+
+    /// Returns true if the event satisfies all conditions of this filter.
+    pub fn matches(&self, event: &StoreEvent) -> bool {
+        if let Some(schema_id) = &self.schema_id {
+            if schema_id != event.schema_id() {
+                return false;
+            }
+        }
+
+        if let Some(document_id) = &self.document_id {
+            if document_id != event.document_id() {
+                return false;
+            }
+        }
+
+        if let Some(public_key) = &self.public_key {
+            if Some(public_key) != event.public_key() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Number of events buffered for every subscriber by default.
+pub const DEFAULT_SUBSCRIPTION_CAPACITY: usize = 1024;
+
+/// Filter of a subscription and the sending half of it's channel.
+type Subscriber = (EventFilter, Sender<StoreEvent>);
+
+/// Delivers store events to all subscribers with a matching filter.
+///
+/// Stores can hold a broadcaster to implement `SubscriptionStore`. Events are buffered for every
+/// subscriber until they are polled from it's stream, subscribers are removed as soon as their
+/// stream is dropped.
+///
+/// The buffer of every subscriber is bounded. Events sent to a subscriber whose buffer is full are
+/// dropped for it, subscribers which can't keep up need to query the store to catch up again.
+#[derive(Clone, Debug)]
+pub struct EventBroadcaster {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    capacity: usize,
+}
+
+impl EventBroadcaster {
+    /// Returns a new broadcaster without any subscribers, buffering
+    /// `DEFAULT_SUBSCRIPTION_CAPACITY` events for every subscriber.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_SUBSCRIPTION_CAPACITY)
+    }
+
+    /// Returns a new broadcaster without any subscribers, buffering at least `capacity` events for
+    /// every subscriber.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            capacity,
+        }
+    }
+
+    /// Returns a stream of all events sent after this call which match the filter.
+    pub fn subscribe(&self, filter: EventFilter) -> BoxStream<'static, StoreEvent> {
+        let (sender, receiver) = mpsc::channel(self.capacity);
+        self.subscribers.lock().unwrap().push((filter, sender));
+        receiver.boxed()
+    }
+
+    /// Send an event to all subscribers whose filter matches it.
+    ///
+    /// Subscribers whose buffer is full miss this event.
+    pub fn send(&self, event: &StoreEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain_mut(|(filter, sender)| {
+                if sender.is_closed() {
+                    return false;
+                }
+
+                if !filter.matches(event) {
+                    return true;
+                }
+
+                match sender.try_send(event.to_owned()) {
+                    Ok(_) => true,
+                    Err(err) if err.is_full() => {
+                        warn!("Dropped event for subscriber with full buffer: {:?}", event);
+                        true
+                    }
+                    // The receiver was dropped in the meantime.
+                    Err(_) => false,
+                }
+            });
+    }
+
+    /// Returns the number of active subscriptions.
+    pub fn len(&self) -> usize {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, sender)| !sender.is_closed())
+            .count()
+    }
+
+    /// Returns true if there are no active subscriptions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for EventBroadcaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{FutureExt, StreamExt};
+    use rstest::rstest;
+
+    use crate::document::DocumentId;
+    use crate::entry::{LogId, SeqNum};
+    use crate::hash::Hash;
+    use crate::identity::KeyPair;
+    use crate::operation::OperationId;
+    use crate::schema::SchemaId;
+    use crate::test_utils::fixtures::{
+        key_pair, random_document_id, random_document_view_id, random_hash, random_operation_id,
+        schema_id,
+    };
+
+    use super::{EventBroadcaster, EventFilter, StoreEvent};
+
+    fn entry_event(
+        key_pair: &KeyPair,
+        schema_id: &SchemaId,
+        document_id: &DocumentId,
+        hash: Hash,
+    ) -> StoreEvent {
+        StoreEvent::EntryInserted {
+            hash,
+            public_key: key_pair.public_key(),
+            log_id: LogId::default(),
+            seq_num: SeqNum::default(),
+            schema_id: schema_id.to_owned(),
+            document_id: document_id.to_owned(),
+        }
+    }
+
+    #[rstest]
+    fn filters_events(
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        #[from(random_document_id)] document_id: DocumentId,
+        #[from(random_operation_id)] operation_id: OperationId,
+        #[from(random_hash)] hash: Hash,
+    ) {
+        let operation_event = StoreEvent::OperationInserted {
+            operation_id,
+            public_key: key_pair.public_key(),
+            schema_id: schema_id.clone(),
+            document_id: document_id.clone(),
+        };
+        let view_event = StoreEvent::DocumentViewUpdated {
+            view_id: random_document_view_id(),
+            schema_id: schema_id.clone(),
+            document_id: document_id.clone(),
+        };
+        let entry_event = entry_event(&key_pair, &schema_id, &document_id, hash);
+
+        let filter = EventFilter::new();
+        assert!(filter.matches(&operation_event));
+        assert!(filter.matches(&view_event));
+        assert!(filter.matches(&entry_event));
+
+        let filter = EventFilter::new()
+            .schema_id(&schema_id)
+            .document_id(&document_id);
+        assert!(filter.matches(&operation_event));
+        assert!(filter.matches(&view_event));
+
+        let filter = EventFilter::new().public_key(&key_pair.public_key());
+        assert!(filter.matches(&operation_event));
+        assert!(filter.matches(&entry_event));
+        assert!(!filter.matches(&view_event));
+
+        let filter = EventFilter::new().document_id(&random_document_id());
+        assert!(!filter.matches(&operation_event));
+        assert!(!filter.matches(&entry_event));
+
+        let filter = EventFilter::new().schema_id(&SchemaId::SchemaDefinition(1));
+        assert!(!filter.matches(&view_event));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn broadcasts_events_to_subscribers(
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        #[from(random_document_id)] document_id: DocumentId,
+    ) {
+        let broadcaster = EventBroadcaster::new();
+
+        let mut all_events = broadcaster.subscribe(EventFilter::new());
+        let mut document_events =
+            broadcaster.subscribe(EventFilter::new().document_id(&document_id));
+        let other_document_events =
+            broadcaster.subscribe(EventFilter::new().document_id(&random_document_id()));
+        assert_eq!(broadcaster.len(), 3);
+
+        let event = entry_event(&key_pair, &schema_id, &document_id, random_hash());
+        broadcaster.send(&event);
+
+        assert_eq!(all_events.next().await, Some(event.clone()));
+        assert_eq!(document_events.next().await, Some(event));
+
+        // Dropped subscriptions are removed.
+        drop(other_document_events);
+        drop(document_events);
+        broadcaster.send(&entry_event(
+            &key_pair,
+            &schema_id,
+            &document_id,
+            random_hash(),
+        ));
+        assert_eq!(broadcaster.len(), 1);
+        assert!(all_events.next().await.is_some());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn drops_events_for_full_subscribers(
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        #[from(random_document_id)] document_id: DocumentId,
+    ) {
+        let broadcaster = EventBroadcaster::with_capacity(1);
+        let mut events = broadcaster.subscribe(EventFilter::new());
+
+        let sent: Vec<StoreEvent> = (0..5)
+            .map(|_| entry_event(&key_pair, &schema_id, &document_id, random_hash()))
+            .collect();
+        for event in &sent {
+            broadcaster.send(event);
+        }
+
+        // The channel holds one buffered event plus one for it's sender, the rest was dropped.
+        assert_eq!(events.next().await, Some(sent[0].clone()));
+        assert_eq!(events.next().await, Some(sent[1].clone()));
+        assert!(events.next().now_or_never().is_none());
+
+        // The subscriber receives events again once it caught up.
+        broadcaster.send(&sent[4]);
+        assert_eq!(broadcaster.len(), 1);
+        assert_eq!(events.next().await, Some(sent[4].clone()));
+    }
+}
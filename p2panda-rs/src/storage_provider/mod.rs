@@ -2,5 +2,11 @@
 
 //! Storage provider traits needed for implementing custom p2panda storage solutions.
 pub mod error;
+pub mod events;
+pub mod pagination;
+pub mod query;
+#[cfg(feature = "sqlite-store")]
+pub mod sqlite;
 pub mod traits;
 pub mod utils;
+pub mod verify;
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Types used for paginated queries on the storage provider traits.
+use crate::document::DocumentViewId;
+use crate::entry::SeqNum;
+use crate::operation::OperationValue;
+
+/// Position in a log after which the next page of entries starts.
+///
+/// The cursor points at the last entry of the previous page, it can be persisted or sent to other
+/// peers to continue reading a log later.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryCursor(SeqNum);
+
+impl EntryCursor {
+    /// Returns a new cursor pointing at the entry with the passed sequence number.
+    pub fn new(seq_num: SeqNum) -> Self {
+        Self(seq_num)
+    }
+
+    /// Returns the sequence number of the last entry of the previous page.
+    pub fn seq_num(&self) -> &SeqNum {
+        &self.0
+    }
+}
+
+/// A page of entries returned from a paginated query.
+#[derive(Clone, Debug)]
+pub struct EntryPage<E> {
+    /// Entries of this page, ordered by sequence number.
+    pub entries: Vec<E>,
+
+    /// Cursor for requesting the next page, `None` if this is the last page.
+    pub next_cursor: Option<EntryCursor>,
+}
+
+/// Position in the result set of a document query after which the next page of documents starts.
+///
+/// The cursor remembers the ordering key of the last document of the previous page, it is only
+/// meaningful when passed to the same query it was returned from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentCursor {
+    /// Value of the ordered field of the last document, `None` when ordered by view id.
+    pub(crate) value: Option<OperationValue>,
+
+    /// View id of the last document.
+    pub(crate) view_id: DocumentViewId,
+}
+
+impl DocumentCursor {
+    /// Returns the view id of the last document of the previous page.
+    pub fn view_id(&self) -> &DocumentViewId {
+        &self.view_id
+    }
+}
+
+/// A page of documents returned from a paginated query.
+#[derive(Clone, Debug)]
+pub struct DocumentPage<D> {
+    /// Documents of this page, in the order requested by the query.
+    pub documents: Vec<D>,
+
+    /// Cursor for requesting the next page, `None` if this is the last page.
+    pub next_cursor: Option<DocumentCursor>,
+}
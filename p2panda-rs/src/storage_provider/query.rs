@@ -0,0 +1,606 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Filtered, ordered and paginated queries over documents of one schema.
+//!
+//! Queries are created with the [`DocumentQueryBuilder`] which validates all filters and the
+//! ordering against the field types of the queried schema. Storage implementations can pass a
+//! query to [`evaluate_query`] to apply it to a collection of documents, this is what the default
+//! implementation of `DocumentStore::query_documents` does.
+use std::cmp::Ordering;
+
+use crate::document::traits::AsDocument;
+use crate::document::{DocumentId, DocumentViewId};
+use crate::operation::OperationValue;
+use crate::schema::{FieldType, Schema, SchemaFields, SchemaId};
+use crate::storage_provider::error::DocumentQueryError;
+use crate::storage_provider::pagination::{DocumentCursor, DocumentPage};
+
+/// Condition a document field needs to meet to be included in the query result.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    /// Field value equals the passed value, can be applied to fields of any type.
+    Equal(OperationValue),
+
+    /// Integer or float field value is greater than the passed value.
+    GreaterThan(OperationValue),
+
+    /// Integer or float field value is greater than or equal to the passed value.
+    GreaterThanOrEqual(OperationValue),
+
+    /// Integer or float field value is lower than the passed value.
+    LowerThan(OperationValue),
+
+    /// Integer or float field value is lower than or equal to the passed value.
+    LowerThanOrEqual(OperationValue),
+
+    /// String field value starts with the passed prefix.
+    Prefix(String),
+
+    /// Relation or relation list field points at the passed document.
+    Contains(DocumentId),
+
+    /// Pinned relation or pinned relation list field points at the passed document view.
+    ContainsView(DocumentViewId),
+}
+
+/// Key documents are ordered by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderBy {
+    /// Order by document view id.
+    ViewId,
+
+    /// Order by the value of a boolean, bytes, integer, float or string field. Documents with
+    /// equal values are ordered by their view id.
+    Field(String),
+}
+
+/// Direction in which documents are ordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Lowest value first.
+    Ascending,
+
+    /// Highest value first.
+    Descending,
+}
+
+/// Query for documents of one schema, validated against it's field types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentQuery {
+    /// Schema of the queried documents.
+    schema_id: SchemaId,
+
+    /// Filters applied to the document fields, all of them need to match.
+    filters: Vec<(String, Filter)>,
+
+    /// Key documents are ordered by.
+    order_by: OrderBy,
+
+    /// Direction in which documents are ordered.
+    direction: Direction,
+
+    /// Maximum number of documents on one page.
+    limit: Option<usize>,
+
+    /// Position after which the requested page starts.
+    cursor: Option<DocumentCursor>,
+}
+
+impl DocumentQuery {
+    /// Returns the schema id of the queried documents.
+    pub fn schema_id(&self) -> &SchemaId {
+        &self.schema_id
+    }
+
+    /// Returns all filters of this query with the names of the fields they are applied to.
+    pub fn filters(&self) -> &[(String, Filter)] {
+        &self.filters
+    }
+
+    /// Returns the key documents are ordered by.
+    pub fn order_by(&self) -> &OrderBy {
+        &self.order_by
+    }
+
+    /// Returns the direction in which documents are ordered.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns the maximum number of documents on one page, `None` if the result is not limited.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Returns the position after which the requested page starts.
+    pub fn cursor(&self) -> Option<&DocumentCursor> {
+        self.cursor.as_ref()
+    }
+}
+
+/// Create new document queries.
+///
+/// Without any further settings the query returns all documents of the schema which are not
+/// deleted, ordered by their view id.
+#[derive(Clone, Debug)]
+pub struct DocumentQueryBuilder {
+    /// Schema id of the queried documents.
+    schema_id: SchemaId,
+
+    /// Field definitions of the queried schema.
+    schema_fields: SchemaFields,
+
+    /// Filters applied to the document fields.
+    filters: Vec<(String, Filter)>,
+
+    /// Key documents are ordered by.
+    order_by: OrderBy,
+
+    /// Direction in which documents are ordered.
+    direction: Direction,
+
+    /// Maximum number of documents on one page.
+    limit: Option<usize>,
+
+    /// Position after which the requested page starts.
+    cursor: Option<DocumentCursor>,
+}
+
+impl DocumentQueryBuilder {
+    /// Returns a new instance of `DocumentQueryBuilder` for documents of the passed schema.
+    pub fn new(schema: &Schema) -> Self {
+        Self {
+            schema_id: schema.id().to_owned(),
+            schema_fields: schema.fields().to_owned(),
+            filters: Vec::new(),
+            order_by: OrderBy::ViewId,
+            direction: Direction::Ascending,
+            limit: None,
+            cursor: None,
+        }
+    }
+
+    /// Add a filter on a field.
+    pub fn filter(mut self, field_name: &str, filter: Filter) -> Self {
+        self.filters.push((field_name.to_owned(), filter));
+        self
+    }
+
+    /// Set ordering.
+    pub fn order_by(mut self, order_by: OrderBy, direction: Direction) -> Self {
+        self.order_by = order_by;
+        self.direction = direction;
+        self
+    }
+
+    /// Set maximum number of documents on one page.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Start the page after the passed cursor.
+    pub fn after(mut self, cursor: &DocumentCursor) -> Self {
+        self.cursor = Some(cursor.to_owned());
+        self
+    }
+
+    /// Builds and returns a new `DocumentQuery` instance.
+    ///
+    /// This method checks if all filtered fields exist in the schema, if the filters can be
+    /// applied to the field types and if documents can be ordered by the given field.
+    pub fn build(&self) -> Result<DocumentQuery, DocumentQueryError> {
+        for (field_name, filter) in &self.filters {
+            let field_type = self.field_type(field_name)?;
+
+            if !is_valid_filter(filter, field_type) {
+                return Err(DocumentQueryError::InvalidFilter(
+                    field_name.to_owned(),
+                    field_type.to_string(),
+                ));
+            }
+        }
+
+        if let OrderBy::Field(field_name) = &self.order_by {
+            let field_type = self.field_type(field_name)?;
+
+            if !is_orderable(field_type) {
+                return Err(DocumentQueryError::InvalidOrdering(
+                    field_name.to_owned(),
+                    field_type.to_string(),
+                ));
+            }
+        }
+
+        Ok(DocumentQuery {
+            schema_id: self.schema_id.to_owned(),
+            filters: self.filters.to_owned(),
+            order_by: self.order_by.to_owned(),
+            direction: self.direction,
+            limit: self.limit,
+            cursor: self.cursor.to_owned(),
+        })
+    }
+
+    fn field_type(&self, field_name: &str) -> Result<&FieldType, DocumentQueryError> {
+        self.schema_fields
+            .get(field_name)
+            .ok_or_else(|| DocumentQueryError::UnknownField(field_name.to_owned()))
+    }
+}
+
+/// Returns true if the value is of the passed field type.
+///
+/// Documents hold the resolved string of collaborative text fields, they are filtered by string
+/// values.
+fn is_of_type(value: &OperationValue, field_type: &FieldType) -> bool {
+    matches!(
+        (value, field_type),
+        (OperationValue::Boolean(_), FieldType::Boolean)
+            | (OperationValue::Bytes(_), FieldType::Bytes)
+            | (OperationValue::Integer(_), FieldType::Integer)
+            | (OperationValue::Float(_), FieldType::Float)
+            | (
+                OperationValue::String(_),
+                FieldType::String | FieldType::Text
+            )
+            | (OperationValue::Relation(_), FieldType::Relation(_))
+            | (OperationValue::RelationList(_), FieldType::RelationList(_))
+            | (
+                OperationValue::PinnedRelation(_),
+                FieldType::PinnedRelation(_)
+            )
+            | (
+                OperationValue::PinnedRelationList(_),
+                FieldType::PinnedRelationList(_)
+            )
+    )
+}
+
+/// Returns true if the filter can be applied to fields of this type.
+fn is_valid_filter(filter: &Filter, field_type: &FieldType) -> bool {
+    match filter {
+        Filter::Equal(value) => is_of_type(value, field_type),
+        Filter::GreaterThan(value)
+        | Filter::GreaterThanOrEqual(value)
+        | Filter::LowerThan(value)
+        | Filter::LowerThanOrEqual(value) => {
+            matches!(field_type, FieldType::Integer | FieldType::Float)
+                && is_of_type(value, field_type)
+        }
+        Filter::Prefix(_) => matches!(field_type, FieldType::String | FieldType::Text),
+        Filter::Contains(_) => matches!(
+            field_type,
+            FieldType::Relation(_) | FieldType::RelationList(_)
+        ),
+        Filter::ContainsView(_) => matches!(
+            field_type,
+            FieldType::PinnedRelation(_) | FieldType::PinnedRelationList(_)
+        ),
+    }
+}
+
+/// Returns true if documents can be ordered by fields of this type.
+fn is_orderable(field_type: &FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::Boolean
+            | FieldType::Bytes
+            | FieldType::Integer
+            | FieldType::Float
+            | FieldType::String
+            | FieldType::Text
+    )
+}
+
+/// Compares two values of the same orderable type.
+fn compare_values(a: &OperationValue, b: &OperationValue) -> Ordering {
+    match (a, b) {
+        (OperationValue::Boolean(a), OperationValue::Boolean(b)) => a.cmp(b),
+        (OperationValue::Bytes(a), OperationValue::Bytes(b)) => a.cmp(b),
+        (OperationValue::Integer(a), OperationValue::Integer(b)) => a.cmp(b),
+        (OperationValue::Float(a), OperationValue::Float(b)) => a.total_cmp(b),
+        (OperationValue::String(a), OperationValue::String(b)) => a.cmp(b),
+        // Values of other types are never compared as the query was validated against the schema.
+        _ => Ordering::Equal,
+    }
+}
+
+/// Returns true if the field value matches the filter.
+pub(crate) fn matches_filter(value: &OperationValue, filter: &Filter) -> bool {
+    match filter {
+        Filter::Equal(expected) => value == expected,
+        Filter::GreaterThan(bound) => compare_values(value, bound) == Ordering::Greater,
+        Filter::GreaterThanOrEqual(bound) => compare_values(value, bound) != Ordering::Less,
+        Filter::LowerThan(bound) => compare_values(value, bound) == Ordering::Less,
+        Filter::LowerThanOrEqual(bound) => compare_values(value, bound) != Ordering::Greater,
+        Filter::Prefix(prefix) => match value {
+            OperationValue::String(value) => value.starts_with(prefix),
+            _ => false,
+        },
+        Filter::Contains(document_id) => match value {
+            OperationValue::Relation(relation) => relation.document_id() == document_id,
+            OperationValue::RelationList(relations) => relations.iter().any(|id| id == document_id),
+            _ => false,
+        },
+        Filter::ContainsView(view_id) => match value {
+            OperationValue::PinnedRelation(relation) => relation.view_id() == view_id,
+            OperationValue::PinnedRelationList(relations) => {
+                relations.iter().any(|id| id == view_id)
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Returns the ordering key of a document.
+fn cursor_for<D: AsDocument>(document: &D, order_by: &OrderBy) -> DocumentCursor {
+    let value = match order_by {
+        OrderBy::ViewId => None,
+        OrderBy::Field(field_name) => document.get(field_name).cloned(),
+    };
+
+    DocumentCursor {
+        value,
+        view_id: document.view_id().to_owned(),
+    }
+}
+
+/// Compares two ordering keys in the requested direction.
+fn compare_cursors(a: &DocumentCursor, b: &DocumentCursor, direction: Direction) -> Ordering {
+    let ordering = match (&a.value, &b.value) {
+        (Some(a_value), Some(b_value)) => compare_values(a_value, b_value),
+        _ => Ordering::Equal,
+    }
+    .then_with(|| a.view_id.cmp(&b.view_id));
+
+    match direction {
+        Direction::Ascending => ordering,
+        Direction::Descending => ordering.reverse(),
+    }
+}
+
+/// Apply a query to a collection of documents.
+///
+/// Deleted documents and documents of other schemas are never included. The remaining documents
+/// are filtered, ordered and the page following the query's cursor is returned.
+pub fn evaluate_query<D: AsDocument>(query: &DocumentQuery, documents: Vec<D>) -> DocumentPage<D> {
+    let mut documents: Vec<(DocumentCursor, D)> = documents
+        .into_iter()
+        .filter(|document| document.schema_id() == query.schema_id() && !document.is_deleted())
+        .filter(|document| {
+            query
+                .filters()
+                .iter()
+                .all(|(field_name, filter)| match document.get(field_name) {
+                    Some(value) => matches_filter(value, filter),
+                    None => false,
+                })
+        })
+        .map(|document| (cursor_for(&document, query.order_by()), document))
+        .filter(|(key, _)| match query.cursor() {
+            Some(cursor) => compare_cursors(key, cursor, query.direction()) == Ordering::Greater,
+            None => true,
+        })
+        .collect();
+
+    documents.sort_by(|(a, _), (b, _)| compare_cursors(a, b, query.direction()));
+
+    let has_next_page = match query.limit() {
+        Some(limit) => {
+            let has_next_page = documents.len() > limit;
+            documents.truncate(limit);
+            has_next_page
+        }
+        None => false,
+    };
+
+    let next_cursor = match has_next_page {
+        true => documents.last().map(|(key, _)| key.to_owned()),
+        false => None,
+    };
+
+    DocumentPage {
+        documents: documents
+            .into_iter()
+            .map(|(_, document)| document)
+            .collect(),
+        next_cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::document::traits::AsDocument;
+    use crate::document::{Document, DocumentBuilder, DocumentId};
+    use crate::identity::KeyPair;
+    use crate::operation::{OperationBuilder, OperationId, OperationValue, RelationList};
+    use crate::schema::{FieldType, Schema};
+    use crate::storage_provider::error::DocumentQueryError;
+    use crate::test_utils::constants::{self, test_fields};
+    use crate::test_utils::fixtures::{random_document_id, random_operation_id, schema};
+
+    use super::{evaluate_query, Direction, DocumentQueryBuilder, Filter, OrderBy};
+
+    fn document(schema: &Schema, username: &str, age: i64, friends: Vec<DocumentId>) -> Document {
+        let mut fields = test_fields();
+        for (name, value) in fields.iter_mut() {
+            match *name {
+                "username" => *value = OperationValue::String(username.to_owned()),
+                "age" => *value = OperationValue::Integer(age),
+                "my_friends" => {
+                    *value = OperationValue::RelationList(RelationList::new(friends.clone()))
+                }
+                _ => (),
+            }
+        }
+
+        let operation = OperationBuilder::new(schema.id())
+            .fields(&fields)
+            .build()
+            .unwrap();
+
+        let (document, _) = DocumentBuilder::new(vec![(
+            random_operation_id(),
+            operation,
+            KeyPair::new().public_key(),
+        )])
+        .build()
+        .unwrap();
+
+        document
+    }
+
+    fn usernames(documents: &[Document]) -> Vec<String> {
+        documents
+            .iter()
+            .map(|document| match document.get("username").unwrap() {
+                OperationValue::String(username) => username.to_owned(),
+                _ => panic!("Expected string"),
+            })
+            .collect()
+    }
+
+    #[rstest]
+    fn filters_documents(schema: Schema, #[from(random_document_id)] friend: DocumentId) {
+        let documents = vec![
+            document(&schema, "panda", 12, vec![friend.clone()]),
+            document(&schema, "penguin", 28, vec![]),
+            document(&schema, "pangolin", 40, vec![friend.clone()]),
+            document(&schema, "bear", 28, vec![]),
+        ];
+
+        let query = DocumentQueryBuilder::new(&schema)
+            .filter("username", Filter::Prefix("pan".into()))
+            .order_by(OrderBy::Field("username".into()), Direction::Ascending)
+            .build()
+            .unwrap();
+        let page = evaluate_query(&query, documents.clone());
+        assert_eq!(usernames(&page.documents), vec!["panda", "pangolin"]);
+
+        let query = DocumentQueryBuilder::new(&schema)
+            .filter(
+                "age",
+                Filter::GreaterThanOrEqual(OperationValue::Integer(28)),
+            )
+            .filter("age", Filter::LowerThan(OperationValue::Integer(40)))
+            .order_by(OrderBy::Field("username".into()), Direction::Descending)
+            .build()
+            .unwrap();
+        let page = evaluate_query(&query, documents.clone());
+        assert_eq!(usernames(&page.documents), vec!["penguin", "bear"]);
+
+        let query = DocumentQueryBuilder::new(&schema)
+            .filter("my_friends", Filter::Contains(friend))
+            .filter("age", Filter::Equal(OperationValue::Integer(40)))
+            .build()
+            .unwrap();
+        let page = evaluate_query(&query, documents);
+        assert_eq!(usernames(&page.documents), vec!["pangolin"]);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[rstest]
+    fn paginates_documents(schema: Schema) {
+        let documents: Vec<Document> = (0..5)
+            .map(|age| document(&schema, &format!("panda_{}", age), age, vec![]))
+            .collect();
+
+        let builder = DocumentQueryBuilder::new(&schema)
+            .order_by(OrderBy::Field("age".into()), Direction::Descending)
+            .limit(2);
+
+        let mut pages = Vec::new();
+        let mut query = builder.build().unwrap();
+
+        loop {
+            let page = evaluate_query(&query, documents.clone());
+            pages.push(usernames(&page.documents));
+
+            match page.next_cursor {
+                Some(cursor) => query = builder.clone().after(&cursor).build().unwrap(),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            pages,
+            vec![
+                vec!["panda_4", "panda_3"],
+                vec!["panda_2", "panda_1"],
+                vec!["panda_0"],
+            ]
+        );
+    }
+
+    #[rstest]
+    fn excludes_deleted_documents(schema: Schema) {
+        let mut deleted_document = document(&schema, "panda", 12, vec![]);
+        let view_id = deleted_document.view_id().to_owned();
+        deleted_document.update_view(&view_id, None);
+
+        let documents = vec![deleted_document, document(&schema, "penguin", 28, vec![])];
+
+        let query = DocumentQueryBuilder::new(&schema).build().unwrap();
+        let page = evaluate_query(&query, documents);
+        assert_eq!(usernames(&page.documents), vec!["penguin"]);
+    }
+
+    #[rstest]
+    #[case::unknown_field(
+        DocumentQueryBuilder::new(&constants::schema()).filter("colour", Filter::Prefix("b".into())),
+        "Field colour does not exist in queried schema"
+    )]
+    #[case::range_on_string(
+        DocumentQueryBuilder::new(&constants::schema())
+            .filter("username", Filter::GreaterThan(OperationValue::String("a".into()))),
+        "Filter can not be applied to field username of type str"
+    )]
+    #[case::wrong_value_type(
+        DocumentQueryBuilder::new(&constants::schema())
+            .filter("age", Filter::Equal(OperationValue::Float(28.0))),
+        "Filter can not be applied to field age of type int"
+    )]
+    #[case::prefix_on_integer(
+        DocumentQueryBuilder::new(&constants::schema()).filter("age", Filter::Prefix("2".into())),
+        "Filter can not be applied to field age of type int"
+    )]
+    #[case::contains_on_pinned_relation(
+        DocumentQueryBuilder::new(&constants::schema())
+            .filter("past_event", Filter::Contains(random_document_id())),
+        "Filter can not be applied to field past_event of type pinned_relation(venue_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b)"
+    )]
+    #[case::order_by_relation(
+        DocumentQueryBuilder::new(&constants::schema())
+            .order_by(OrderBy::Field("my_friends".into()), Direction::Ascending),
+        "Documents can not be ordered by field my_friends of type relation_list(venue_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b)"
+    )]
+    fn validates_query_against_schema(
+        #[case] builder: DocumentQueryBuilder,
+        #[case] expected_error: &str,
+    ) {
+        let error: DocumentQueryError = builder.build().unwrap_err();
+        assert_eq!(error.to_string(), expected_error);
+    }
+
+    #[rstest]
+    fn accepts_valid_filters(
+        schema: Schema,
+        #[from(random_operation_id)] operation_id: OperationId,
+    ) {
+        assert_eq!(schema.fields().get("height"), Some(&FieldType::Float));
+
+        assert!(DocumentQueryBuilder::new(&schema)
+            .filter(
+                "height",
+                Filter::LowerThanOrEqual(OperationValue::Float(4.0))
+            )
+            .filter("past_event", Filter::ContainsView(operation_id.into()))
+            .filter("is_admin", Filter::Equal(OperationValue::Boolean(false)))
+            .order_by(OrderBy::Field("data".into()), Direction::Ascending)
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use rusqlite::Connection;
+
+/// Ordered list of migrations which bring the database schema up to date.
+///
+/// The index of a migration in this list plus one is the schema version it results in. The
+/// current version is tracked in SQLite's `user_version` pragma. Existing migrations must never
+/// be changed, new ones are appended to the end of the list.
+const MIGRATIONS: &[&str] = &[
+    // Version 1: Initial tables for logs, entries and operations.
+    r#"
+    CREATE TABLE logs (
+        public_key      TEXT NOT NULL,
+        log_id          TEXT NOT NULL,
+        document_id     TEXT NOT NULL,
+        schema_id       TEXT NOT NULL,
+        PRIMARY KEY (public_key, log_id)
+    );
+
+    CREATE INDEX idx_logs_document_id ON logs (public_key, document_id);
+
+    CREATE TABLE entries (
+        entry_hash      TEXT NOT NULL PRIMARY KEY,
+        public_key      TEXT NOT NULL,
+        log_id          TEXT NOT NULL,
+        seq_num         TEXT NOT NULL,
+        entry_bytes     BLOB NOT NULL,
+        payload_bytes   BLOB,
+        UNIQUE (public_key, log_id, seq_num)
+    );
+
+    CREATE TABLE operations (
+        operation_id    TEXT NOT NULL PRIMARY KEY,
+        public_key      TEXT NOT NULL,
+        document_id     TEXT NOT NULL,
+        schema_id       TEXT NOT NULL,
+        action          INTEGER NOT NULL,
+        version         INTEGER NOT NULL,
+        previous        TEXT
+    );
+
+    CREATE INDEX idx_operations_document_id ON operations (document_id);
+    CREATE INDEX idx_operations_schema_id ON operations (schema_id);
+
+    CREATE TABLE operation_fields (
+        operation_id    TEXT NOT NULL REFERENCES operations (operation_id) ON DELETE CASCADE,
+        name            TEXT NOT NULL,
+        field_type      TEXT NOT NULL,
+        value           TEXT NOT NULL,
+        PRIMARY KEY (operation_id, name)
+    );
+
+    CREATE TABLE operation_relations (
+        target          TEXT NOT NULL,
+        is_pinned       INTEGER NOT NULL,
+        operation_id    TEXT NOT NULL REFERENCES operations (operation_id) ON DELETE CASCADE,
+        PRIMARY KEY (target, is_pinned, operation_id)
+    );
+    "#,
+    // Version 2: Materialised documents and document views.
+    r#"
+    CREATE TABLE documents (
+        document_id         TEXT NOT NULL PRIMARY KEY,
+        document_view_id    TEXT NOT NULL,
+        schema_id           TEXT NOT NULL,
+        author              TEXT NOT NULL,
+        is_deleted          INTEGER NOT NULL
+    );
+
+    CREATE TABLE document_views (
+        document_view_id    TEXT NOT NULL PRIMARY KEY,
+        document_id         TEXT NOT NULL,
+        schema_id           TEXT NOT NULL
+    );
+
+    CREATE INDEX idx_document_views_document_id ON document_views (document_id);
+
+    CREATE TABLE document_view_fields (
+        document_view_id    TEXT NOT NULL
+                            REFERENCES document_views (document_view_id) ON DELETE CASCADE,
+        name                TEXT NOT NULL,
+        operation_id        TEXT NOT NULL,
+        field_type          TEXT NOT NULL,
+        value               TEXT NOT NULL,
+        PRIMARY KEY (document_view_id, name)
+    );
+    "#,
+    // Version 3: Sequence numbers below which logs were pruned.
+    r#"
+    CREATE TABLE pruned_logs (
+        public_key      TEXT NOT NULL,
+        log_id          TEXT NOT NULL,
+        seq_num         TEXT NOT NULL,
+        PRIMARY KEY (public_key, log_id)
+    );
+    "#,
+    // Version 4: Running totals of stored payload bytes per public key and documents per schema,
+    // kept up to date by triggers.
+    r#"
+    CREATE TABLE payload_sizes (
+        public_key      TEXT NOT NULL PRIMARY KEY,
+        size            INTEGER NOT NULL
+    );
+
+    INSERT INTO payload_sizes (public_key, size)
+        SELECT public_key, SUM(LENGTH(payload_bytes)) FROM entries
+        WHERE payload_bytes IS NOT NULL
+        GROUP BY public_key;
+
+    CREATE TRIGGER insert_payload_size AFTER INSERT ON entries
+    WHEN NEW.payload_bytes IS NOT NULL
+    BEGIN
+        INSERT INTO payload_sizes (public_key, size)
+            VALUES (NEW.public_key, LENGTH(NEW.payload_bytes))
+            ON CONFLICT (public_key) DO UPDATE SET size = size + excluded.size;
+    END;
+
+    CREATE TRIGGER update_payload_size AFTER UPDATE OF payload_bytes ON entries
+    BEGIN
+        INSERT INTO payload_sizes (public_key, size)
+            VALUES (
+                NEW.public_key,
+                IFNULL(LENGTH(NEW.payload_bytes), 0) - IFNULL(LENGTH(OLD.payload_bytes), 0)
+            )
+            ON CONFLICT (public_key) DO UPDATE SET size = size + excluded.size;
+    END;
+
+    CREATE TRIGGER delete_payload_size AFTER DELETE ON entries
+    WHEN OLD.payload_bytes IS NOT NULL
+    BEGIN
+        UPDATE payload_sizes SET size = size - LENGTH(OLD.payload_bytes)
+            WHERE public_key = OLD.public_key;
+    END;
+
+    CREATE TABLE document_counts (
+        schema_id       TEXT NOT NULL PRIMARY KEY,
+        count           INTEGER NOT NULL
+    );
+
+    INSERT INTO document_counts (schema_id, count)
+        SELECT schema_id, COUNT(*) FROM operations
+        WHERE action = 0
+        GROUP BY schema_id;
+
+    CREATE TRIGGER insert_document_count AFTER INSERT ON operations
+    WHEN NEW.action = 0
+    BEGIN
+        INSERT INTO document_counts (schema_id, count)
+            VALUES (NEW.schema_id, 1)
+            ON CONFLICT (schema_id) DO UPDATE SET count = count + 1;
+    END;
+
+    CREATE TRIGGER delete_document_count AFTER DELETE ON operations
+    WHEN OLD.action = 0
+    BEGIN
+        UPDATE document_counts SET count = count - 1 WHERE schema_id = OLD.schema_id;
+    END;
+    "#,
+];
+
+/// Apply all migrations which have not been run yet against this database.
+///
+/// Every migration is executed inside its own transaction together with the version bump, a
+/// failing migration therefore leaves the database at the last successful version.
+pub fn run_migrations(connection: &mut Connection) -> rusqlite::Result<()> {
+    let current_version: u32 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current_version as usize) {
+        let tx = connection.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", index as u32 + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::{run_migrations, MIGRATIONS};
+
+    #[test]
+    fn migrations_are_idempotent() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        run_migrations(&mut connection).unwrap();
+        run_migrations(&mut connection).unwrap();
+
+        let version: u32 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version as usize, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn totals_include_existing_rows() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        // Prepare a database which was created before running totals were introduced.
+        for migration in &MIGRATIONS[..3] {
+            connection.execute_batch(migration).unwrap();
+        }
+        connection.pragma_update(None, "user_version", 3).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO entries VALUES ('a', 'key', '0', '1', x'00', x'0102');
+                INSERT INTO entries VALUES ('b', 'key', '0', '2', x'00', NULL);
+                INSERT INTO operations VALUES ('a', 'key', 'a', 'schema', 0, 1, NULL);
+                INSERT INTO operations VALUES ('c', 'key', 'a', 'schema', 1, 1, 'a');",
+            )
+            .unwrap();
+
+        run_migrations(&mut connection).unwrap();
+
+        let size: i64 = connection
+            .query_row(
+                "SELECT size FROM payload_sizes WHERE public_key = 'key'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(size, 2);
+
+        let count: i64 = connection
+            .query_row(
+                "SELECT count FROM document_counts WHERE schema_id = 'schema'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+}
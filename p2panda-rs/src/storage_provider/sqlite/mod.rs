@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Implementation of `storage_provider` traits for a persistent SQLite store.
+//!
+//! - `SqliteStore` implementation of all storage provider traits
+//! - schema migrations which are applied when a store is opened
+mod migrations;
+mod provider;
+mod stores;
+mod types;
+mod utils;
+
+pub use provider::SqliteStore;
+pub use types::{SqliteEntry, SqliteOperation};
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::api::{
+        export_archive, import_archive, next_args, publish, purge_deleted_documents,
+        ArchiveSelection,
+    };
+    use crate::document::traits::AsDocument;
+    use crate::document::{DocumentId, DocumentViewId};
+    use crate::entry::encode::sign_and_encode_entry;
+    use crate::entry::traits::AsEncodedEntry;
+    use crate::entry::{LogId, SeqNum};
+    use crate::identity::{KeyPair, PublicKey};
+    use crate::operation::decode::decode_operation;
+    use crate::operation::encode::encode_operation;
+    use crate::operation::OperationValue;
+    use crate::schema::{FieldType, Schema};
+    use crate::storage_provider::traits::{DocumentStore, EntryStore, LogStore, OperationStore};
+    use crate::storage_provider::verify::verify_store;
+    use crate::test_utils::fixtures::{
+        create_operation, delete_operation, key_pair, populate_store_config, schema,
+        update_operation,
+    };
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::SqliteStore;
+
+    crate::store_conformance_tests!(SqliteStore::in_memory().unwrap());
+
+    #[rstest]
+    #[tokio::test]
+    async fn publish_many_entries(
+        #[with(vec![("name".to_string(), FieldType::String)])] schema: Schema,
+        key_pair: KeyPair,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+
+        // Publish enough entries to require skiplinks and sequence numbers with several digits.
+        let num_of_entries = 20;
+        let mut document_id: Option<DocumentId> = None;
+        let public_key = key_pair.public_key();
+
+        for index in 0..num_of_entries {
+            let document_view_id: Option<DocumentViewId> =
+                document_id.clone().map(|id| id.as_str().parse().unwrap());
+
+            let (backlink, skiplink, seq_num, log_id) =
+                next_args(&store, &public_key, document_view_id.as_ref())
+                    .await
+                    .unwrap();
+
+            let schema_id = schema.id().to_owned();
+            let operation = if index == 0 {
+                create_operation(
+                    vec![("name", OperationValue::String("Panda".to_string()))],
+                    schema_id,
+                )
+            } else if index == (num_of_entries - 1) {
+                delete_operation(backlink.clone().unwrap().into(), schema_id)
+            } else {
+                update_operation(
+                    vec![("name", OperationValue::String(format!("🐼 {}", index)))],
+                    backlink.clone().unwrap().into(),
+                    schema_id,
+                )
+            };
+
+            let encoded_operation = encode_operation(&operation).unwrap();
+            let encoded_entry = sign_and_encode_entry(
+                &log_id,
+                &seq_num,
+                skiplink.as_ref(),
+                backlink.as_ref(),
+                &encoded_operation,
+                &key_pair,
+            )
+            .unwrap();
+
+            if index == 0 {
+                document_id = Some(encoded_entry.hash().into());
+            }
+
+            let (_, _, next_seq_num, _) = publish(
+                &store,
+                &schema,
+                &encoded_entry,
+                &decode_operation(&encoded_operation).unwrap(),
+                &encoded_operation,
+            )
+            .await
+            .unwrap();
+
+            let mut previous_seq_num = seq_num;
+            assert_eq!(next_seq_num, previous_seq_num.next().unwrap());
+            assert_eq!(log_id, LogId::default());
+        }
+
+        // The document was deleted by the last operation.
+        let document = store
+            .get_document(document_id.as_ref().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(document.is_deleted());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn purges_deleted_documents(
+        key_pair: KeyPair,
+        #[from(populate_store_config)]
+        #[with(4, 1, 1, true)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (_, documents) = populate_store(&store, &config).await;
+
+        let purged_documents = purge_deleted_documents(&store, config.schema.id())
+            .await
+            .unwrap();
+        assert_eq!(purged_documents, documents);
+
+        assert!(store
+            .get_operations_by_document_id(&documents[0])
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Entries are kept without their payloads.
+        let entries = store
+            .get_entries_from(
+                &key_pair.public_key(),
+                &LogId::default(),
+                &SeqNum::default(),
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 4);
+        assert!(entries.iter().all(|entry| entry.payload().is_none()));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn imports_archive_from_other_store(
+        #[from(populate_store_config)]
+        #[with(5, 2, 2)]
+        config: PopulateStoreConfig,
+    ) {
+        let memory_store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&memory_store, &config).await;
+
+        let selection = ArchiveSelection::new().schema_id(config.schema.id());
+        let archive = export_archive(&memory_store, &selection).await.unwrap();
+
+        let store = SqliteStore::in_memory().unwrap();
+        let published = import_archive(&store, &archive, std::slice::from_ref(&config.schema))
+            .await
+            .unwrap();
+        assert_eq!(published.len(), 5 * 2 * 2);
+
+        // Exporting the same selection from the imported store results in the same archive.
+        assert_eq!(export_archive(&store, &selection).await.unwrap(), archive);
+
+        for key_pair in &key_pairs {
+            assert_eq!(
+                store.latest_log_id(&key_pair.public_key()).await.unwrap(),
+                Some(LogId::new(1))
+            );
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn verifies_consistent_store(
+        #[from(populate_store_config)]
+        #[with(10, 2, 2, true)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_keys: Vec<PublicKey> = key_pairs
+            .iter()
+            .map(|key_pair| key_pair.public_key())
+            .collect();
+
+        let report = verify_store(&store, &public_keys).await.unwrap();
+        assert_eq!(report.violations(), &[]);
+    }
+}
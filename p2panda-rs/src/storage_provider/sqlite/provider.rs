@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::lock::{Mutex, MutexGuard, OwnedMutexGuard};
+use rusqlite::Connection;
+
+use crate::storage_provider::events::{EventBroadcaster, StoreEvent};
+use crate::storage_provider::sqlite::migrations::run_migrations;
+
+/// A persistent implementation of p2panda storage traits backed by SQLite.
+///
+/// All queries are executed synchronously on a single shared connection. This keeps the
+/// implementation simple and is suitable for clients and small nodes, larger deployments will
+/// want to implement the storage traits on top of a connection pool instead.
+///
+/// A transaction takes exclusive ownership of the connection until it is committed or dropped,
+/// all other queries wait for it to finish.
+#[derive(Clone)]
+pub struct SqliteStore {
+    /// Connection to the underlying SQLite database.
+    pub(crate) connection: ConnectionHandle,
+
+    /// Subscribers to changes of the stored data.
+    pub(crate) events: EventBroadcaster,
+}
+
+impl SqliteStore {
+    /// Open or create a SQLite database at the given path.
+    ///
+    /// Pending schema migrations are applied before the store is returned.
+    pub fn new<P: AsRef<Path>>(path: P) -> rusqlite::Result<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Create a store backed by a temporary in-memory SQLite database.
+    pub fn in_memory() -> rusqlite::Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut connection: Connection) -> rusqlite::Result<Self> {
+        run_migrations(&mut connection)?;
+
+        Ok(Self {
+            connection: ConnectionHandle::Shared(Arc::new(Mutex::new(connection))),
+            events: EventBroadcaster::new(),
+        })
+    }
+
+    /// Returns exclusive access to the connection queries of this store are executed on.
+    ///
+    /// Panics when this store is a handle to a transaction which was already committed or rolled
+    /// back.
+    pub(crate) async fn connection(&self) -> ConnectionGuard<'_> {
+        match &self.connection {
+            ConnectionHandle::Shared(connection) => {
+                ConnectionGuard::Shared(connection.lock().await)
+            }
+            ConnectionHandle::Transaction(connection) => {
+                let guard = connection.lock().await;
+                assert!(guard.is_some(), "Transaction was already finished");
+                ConnectionGuard::Transaction(guard)
+            }
+        }
+    }
+}
+
+/// Connection of a store, either shared between all it's users or owned by a transaction.
+#[derive(Clone)]
+pub(crate) enum ConnectionHandle {
+    /// Connection which is shared between all users of the store.
+    Shared(Arc<Mutex<Connection>>),
+
+    /// Connection which is owned by a transaction, `None` after the transaction was finished.
+    Transaction(Arc<Mutex<Option<TransactionConnection>>>),
+}
+
+/// Exclusive access to the shared connection, held for the whole lifetime of a transaction.
+///
+/// The transaction is rolled back when it is dropped before it was committed.
+pub(crate) struct TransactionConnection {
+    /// Connection the transaction was started on.
+    pub(crate) connection: OwnedMutexGuard<Connection>,
+
+    /// Events about writes of the transaction, sent to subscribers once it was committed.
+    pub(crate) events: Vec<StoreEvent>,
+}
+
+impl Drop for TransactionConnection {
+    fn drop(&mut self) {
+        if !self.connection.is_autocommit() {
+            // There is no way to report an error from here, the connection is left in autocommit
+            // mode by SQLite even if the rollback failed.
+            let _ = self.connection.execute_batch("ROLLBACK");
+        }
+    }
+}
+
+/// Locked connection of a store returned by `SqliteStore::connection`.
+pub(crate) enum ConnectionGuard<'a> {
+    Shared(MutexGuard<'a, Connection>),
+    Transaction(MutexGuard<'a, Option<TransactionConnection>>),
+}
+
+impl ConnectionGuard<'_> {
+    /// Notify subscribers about data written on this connection.
+    ///
+    /// Events about writes of a transaction are queued until it was committed and discarded when
+    /// it is rolled back, all other events are sent right away.
+    pub(crate) fn send_event(&mut self, events: &EventBroadcaster, event: StoreEvent) {
+        match self {
+            ConnectionGuard::Shared(_) => events.send(&event),
+            ConnectionGuard::Transaction(connection) => {
+                connection.as_mut().unwrap().events.push(event)
+            }
+        }
+    }
+}
+
+impl Deref for ConnectionGuard<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            ConnectionGuard::Shared(connection) => connection,
+            // Checked to be `Some` when the guard was created.
+            ConnectionGuard::Transaction(connection) => &connection.as_ref().unwrap().connection,
+        }
+    }
+}
+
+impl DerefMut for ConnectionGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        match self {
+            ConnectionGuard::Shared(connection) => connection,
+            ConnectionGuard::Transaction(connection) => {
+                &mut connection.as_mut().unwrap().connection
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SqliteStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqliteStore").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use rstest::rstest;
+
+    use crate::entry::traits::AsEntry;
+    use crate::entry::LogId;
+    use crate::storage_provider::traits::{EntryStore, LogStore, OperationStore};
+    use crate::test_utils::fixtures::populate_store_config;
+    use crate::test_utils::generate_random_bytes;
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+
+    use super::SqliteStore;
+
+    #[rstest]
+    #[tokio::test]
+    async fn persists_data_between_connections(
+        #[from(populate_store_config)]
+        #[with(3, 2, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let path = env::temp_dir().join(format!(
+            "p2panda-{}.sqlite3",
+            hex::encode(generate_random_bytes(8))
+        ));
+
+        let store = SqliteStore::new(&path).unwrap();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        drop(store);
+
+        // Opening the same database again runs the migrations a second time, which should be a
+        // no-op, and gives us access to everything written before.
+        let store = SqliteStore::new(&path).unwrap();
+        let public_key = key_pairs[0].public_key();
+
+        assert_eq!(
+            store.latest_log_id(&public_key).await.unwrap(),
+            Some(LogId::new(1))
+        );
+        assert_eq!(
+            store
+                .get_latest_entry(&public_key, &LogId::new(1))
+                .await
+                .unwrap()
+                .unwrap()
+                .seq_num()
+                .as_u64(),
+            3
+        );
+
+        for document_id in &documents {
+            let operations = store
+                .get_operations_by_document_id(document_id)
+                .await
+                .unwrap();
+            assert_eq!(operations.len(), 3);
+        }
+
+        drop(store);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,838 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::document::traits::AsDocument;
+use crate::document::{
+    Document, DocumentBuilder, DocumentId, DocumentView, DocumentViewFields, DocumentViewId,
+    DocumentViewValue,
+};
+use crate::identity::PublicKey;
+use crate::operation::OperationId;
+use crate::schema::SchemaId;
+use crate::storage_provider::error::DocumentStorageError;
+use crate::storage_provider::events::StoreEvent;
+use crate::storage_provider::sqlite::utils::{
+    decode_value, encode_value, stream_pages, STREAM_PAGE_SIZE,
+};
+use crate::storage_provider::sqlite::SqliteStore;
+use crate::storage_provider::traits::{DocumentStore, OperationStore};
+use crate::storage_provider::utils::current_view_id;
+use crate::WithId;
+
+fn fatal<E: ToString>(err: E) -> DocumentStorageError {
+    DocumentStorageError::FatalStorageError(err.to_string())
+}
+
+/// Select the fields of a stored document view.
+fn select_document_view_fields(
+    connection: &Connection,
+    document_view_id: &str,
+) -> Result<DocumentViewFields, DocumentStorageError> {
+    let mut statement = connection
+        .prepare(
+            "SELECT name, operation_id, field_type, value FROM document_view_fields
+            WHERE document_view_id = ?1",
+        )
+        .map_err(fatal)?;
+
+    let rows = statement
+        .query_map([document_view_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(fatal)?
+        .collect::<Result<Vec<(String, String, String, String)>, _>>()
+        .map_err(fatal)?;
+
+    let mut fields = DocumentViewFields::new();
+    for (name, operation_id, field_type, value) in rows {
+        let operation_id = OperationId::from_str(&operation_id).map_err(fatal)?;
+        let value = decode_value(&field_type, &value).map_err(fatal)?;
+        fields.insert(&name, DocumentViewValue::new(&operation_id, &value));
+    }
+
+    Ok(fields)
+}
+
+/// Select a stored document in the state it was inserted.
+fn select_stored_document(
+    connection: &Connection,
+    document_id: &DocumentId,
+) -> Result<Option<Document>, DocumentStorageError> {
+    let row: Option<(String, String, String, bool)> = connection
+        .query_row(
+            "SELECT document_view_id, schema_id, author, is_deleted FROM documents
+            WHERE document_id = ?1",
+            params![document_id.as_str()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(fatal)?;
+
+    let (view_id, schema_id, author, is_deleted) = match row {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+
+    let fields = match is_deleted {
+        true => None,
+        false => Some(select_document_view_fields(connection, &view_id)?),
+    };
+
+    Ok(Some(Document::new(
+        document_id,
+        fields.as_ref(),
+        &SchemaId::from_str(&schema_id).map_err(fatal)?,
+        &DocumentViewId::from_str(&view_id).map_err(fatal)?,
+        &PublicKey::new(&author).map_err(fatal)?,
+    )))
+}
+
+/// Select the document at a stored view, `None` if this view or the document it belongs to were
+/// not inserted.
+fn select_stored_document_view(
+    connection: &Connection,
+    document_view_id: &DocumentViewId,
+) -> Result<Option<Document>, DocumentStorageError> {
+    let view_id = document_view_id.to_string();
+
+    // The author is only known from the stored document.
+    let row: Option<(String, String, String)> = connection
+        .query_row(
+            "SELECT document_views.document_id, document_views.schema_id, documents.author
+            FROM document_views
+            JOIN documents ON documents.document_id = document_views.document_id
+            WHERE document_views.document_view_id = ?1",
+            params![view_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(fatal)?;
+
+    let (document_id, schema_id, author) = match row {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+
+    Ok(Some(Document::new(
+        &DocumentId::from_str(&document_id).map_err(fatal)?,
+        Some(&select_document_view_fields(connection, &view_id)?),
+        &SchemaId::from_str(&schema_id).map_err(fatal)?,
+        document_view_id,
+        &PublicKey::new(&author).map_err(fatal)?,
+    )))
+}
+
+/// Select the ids of at most `STREAM_PAGE_SIZE` documents of a schema, ordered by their id and
+/// starting after the passed document id.
+fn select_document_ids_page(
+    connection: &Connection,
+    schema_id: &SchemaId,
+    after: &str,
+) -> Result<Vec<String>, DocumentStorageError> {
+    let mut statement = connection
+        .prepare(
+            "SELECT DISTINCT document_id FROM operations
+            WHERE schema_id = ?1 AND document_id > ?2
+            ORDER BY document_id
+            LIMIT ?3",
+        )
+        .map_err(fatal)?;
+
+    let document_ids = statement
+        .query_map(
+            params![schema_id.to_string(), after, STREAM_PAGE_SIZE as i64],
+            |row| row.get(0),
+        )
+        .map_err(fatal)?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(fatal)?;
+
+    Ok(document_ids)
+}
+
+/// Insert a document view and it's fields, does nothing if the view already exists.
+///
+/// Returns true if the view was inserted.
+fn insert_document_view_rows(
+    connection: &Connection,
+    document_view: &DocumentView,
+    document_id: &DocumentId,
+    schema_id: &SchemaId,
+) -> Result<bool, DocumentStorageError> {
+    let view_id = document_view.id().to_string();
+
+    let inserted = connection
+        .execute(
+            "INSERT OR IGNORE INTO document_views (document_view_id, document_id, schema_id)
+            VALUES (?1, ?2, ?3)",
+            params![view_id, document_id.as_str(), schema_id.to_string()],
+        )
+        .map_err(fatal)?;
+
+    if inserted == 0 {
+        return Ok(false);
+    }
+
+    for (name, view_value) in document_view.iter() {
+        connection
+            .execute(
+                "INSERT INTO document_view_fields
+                    (document_view_id, name, operation_id, field_type, value)
+                VALUES
+                    (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    view_id,
+                    name,
+                    view_value.id().as_str(),
+                    view_value.value().field_type(),
+                    encode_value(view_value.value()),
+                ],
+            )
+            .map_err(fatal)?;
+    }
+
+    Ok(true)
+}
+
+/// Documents are materialised from their operations on each query unless a materialised state was
+/// inserted before. Stored documents are only returned while they are at their latest view and
+/// stored document views are returned directly. Any document view can be queried as long as it's
+/// operations are stored.
+#[async_trait]
+impl DocumentStore for SqliteStore {
+    type Document = Document;
+
+    /// Get a document by it's document id.
+    async fn get_document(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Option<Document>, DocumentStorageError> {
+        let operations = self.get_operations_by_document_id(id).await?;
+
+        if operations.is_empty() {
+            return Ok(None);
+        }
+
+        // Return the stored document if no operations were added to it since it was inserted.
+        let stored_document = {
+            let connection = self.connection().await;
+            select_stored_document(&connection, id)?
+        };
+
+        if let Some(document) = stored_document {
+            if document.view_id() == &current_view_id(&operations) {
+                return Ok(Some(document));
+            }
+        }
+
+        Ok(Some({ &operations }.try_into()?))
+    }
+
+    /// Get a document by it's document view id, returned document has been materialised to the
+    /// state specified by the passed document view id.
+    async fn get_document_by_view_id(
+        &self,
+        id: &DocumentViewId,
+    ) -> Result<Option<Document>, DocumentStorageError> {
+        // Document views are immutable, a stored view can be returned without materialising it.
+        let stored_document = {
+            let connection = self.connection().await;
+            select_stored_document_view(&connection, id)?
+        };
+
+        if stored_document.is_some() {
+            return Ok(stored_document);
+        }
+
+        let operation_id = id.iter().next().unwrap();
+        let document_id = match self.get_document_id_by_operation_id(operation_id).await? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let operations = self.get_operations_by_document_id(&document_id).await?;
+
+        if operations.is_empty() {
+            return Ok(None);
+        }
+
+        let document_builder: DocumentBuilder = (&operations).into();
+        let (document, _) = document_builder.build_to_view_id(id.clone())?;
+        Ok(Some(document))
+    }
+
+    /// Get all documents which contain data following the schema specified by the passed schema id.
+    async fn get_documents_by_schema(
+        &self,
+        schema_id: &SchemaId,
+    ) -> Result<Vec<Document>, DocumentStorageError> {
+        let mut operations_by_document: HashMap<_, Vec<_>> = HashMap::new();
+
+        let operations = self.get_operations_by_schema_id(schema_id).await?;
+
+        for operation in &operations {
+            let document_id = WithId::<DocumentId>::id(operation);
+            operations_by_document
+                .entry(document_id)
+                .or_default()
+                .push(operation);
+        }
+
+        let documents = operations_by_document
+            .into_values()
+            .filter_map(|operations| operations.try_into().ok())
+            .collect();
+
+        Ok(documents)
+    }
+
+    /// Stream all documents which follow the passed schema.
+    ///
+    /// The ids of the documents are read from the database in pages of `STREAM_PAGE_SIZE`
+    /// documents, only the operations of one page are held in memory at once.
+    fn stream_documents_by_schema<'a>(
+        &'a self,
+        schema_id: &'a SchemaId,
+    ) -> BoxStream<'a, Result<Document, DocumentStorageError>>
+    where
+        Self: Sync,
+    {
+        stream_pages(move |after| async move {
+            let document_ids = {
+                let connection = self.connection().await;
+                select_document_ids_page(&connection, schema_id, &after)?
+            };
+
+            let mut documents = Vec::with_capacity(document_ids.len());
+            for document_id in &document_ids {
+                let document_id = DocumentId::from_str(document_id).map_err(fatal)?;
+                let operations = self.get_operations_by_document_id(&document_id).await?;
+
+                // Documents which can't be built are skipped, like in `get_documents_by_schema`.
+                if let Ok(document) = (&operations).try_into() {
+                    documents.push(document);
+                }
+            }
+
+            Ok((documents, document_ids))
+        })
+    }
+
+    /// Insert a document and it's current view into the store.
+    ///
+    /// Both are written together inside a savepoint. Subscribers are notified when the view wasn't
+    /// stored before, once the surrounding transaction was committed.
+    async fn insert_document(&self, document: &Document) -> Result<(), DocumentStorageError> {
+        let mut connection = self.connection().await;
+        let tx = connection.savepoint().map_err(fatal)?;
+
+        let document_view = document.view();
+        let is_new_view = match &document_view {
+            Some(document_view) => {
+                insert_document_view_rows(&tx, document_view, document.id(), document.schema_id())?
+            }
+            None => false,
+        };
+
+        // Stored documents are updated to their new view, their schema and author never change.
+        let rows_affected = tx
+            .execute(
+                "INSERT INTO documents
+                    (document_id, document_view_id, schema_id, author, is_deleted)
+                VALUES
+                    (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (document_id) DO UPDATE SET
+                    document_view_id = excluded.document_view_id,
+                    is_deleted = excluded.is_deleted
+                WHERE
+                    schema_id = excluded.schema_id AND author = excluded.author",
+                params![
+                    document.id().as_str(),
+                    document.view_id().to_string(),
+                    document.schema_id().to_string(),
+                    document.author().to_string(),
+                    document.is_deleted(),
+                ],
+            )
+            .map_err(fatal)?;
+
+        if rows_affected == 0 {
+            return Err(DocumentStorageError::DocumentInsertionError(
+                document.id().to_owned(),
+            ));
+        }
+
+        tx.commit().map_err(fatal)?;
+
+        if let (Some(document_view), true) = (document_view, is_new_view) {
+            connection.send_event(
+                &self.events,
+                StoreEvent::DocumentViewUpdated {
+                    view_id: document_view.id().to_owned(),
+                    schema_id: document.schema_id().to_owned(),
+                    document_id: document.id().to_owned(),
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Insert a document view into the store.
+    ///
+    /// Subscribers are notified when the view wasn't stored before, once the surrounding transaction
+    /// was committed.
+    async fn insert_document_view(
+        &self,
+        document_view: &DocumentView,
+        document_id: &DocumentId,
+        schema_id: &SchemaId,
+    ) -> Result<(), DocumentStorageError> {
+        let mut connection = self.connection().await;
+        let tx = connection.savepoint().map_err(fatal)?;
+        let is_new_view = insert_document_view_rows(&tx, document_view, document_id, schema_id)?;
+        tx.commit().map_err(fatal)?;
+
+        if is_new_view {
+            connection.send_event(
+                &self.events,
+                StoreEvent::DocumentViewUpdated {
+                    view_id: document_view.id().to_owned(),
+                    schema_id: schema_id.to_owned(),
+                    document_id: document_id.to_owned(),
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Remove a stored document and all of it's stored views.
+    ///
+    /// The document, it's views and their fields are removed together inside a savepoint.
+    async fn remove_document(&self, document_id: &DocumentId) -> Result<(), DocumentStorageError> {
+        let mut connection = self.connection().await;
+        let tx = connection.savepoint().map_err(fatal)?;
+
+        tx.execute(
+            "DELETE FROM document_view_fields WHERE document_view_id IN (
+                SELECT document_view_id FROM document_views WHERE document_id = ?1
+            )",
+            params![document_id.as_str()],
+        )
+        .map_err(fatal)?;
+
+        tx.execute(
+            "DELETE FROM document_views WHERE document_id = ?1",
+            params![document_id.as_str()],
+        )
+        .map_err(fatal)?;
+
+        tx.execute(
+            "DELETE FROM documents WHERE document_id = ?1",
+            params![document_id.as_str()],
+        )
+        .map_err(fatal)?;
+
+        tx.commit().map_err(fatal)?;
+
+        Ok(())
+    }
+
+    /// Get all stored views of a document.
+    async fn get_document_views(
+        &self,
+        document_id: &DocumentId,
+    ) -> Result<Vec<DocumentView>, DocumentStorageError> {
+        let connection = self.connection().await;
+        let mut statement = connection
+            .prepare("SELECT document_view_id FROM document_views WHERE document_id = ?1")
+            .map_err(fatal)?;
+
+        let view_ids = statement
+            .query_map([document_id.as_str()], |row| row.get(0))
+            .map_err(fatal)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(fatal)?;
+
+        view_ids
+            .iter()
+            .map(|view_id| {
+                let fields = select_document_view_fields(&connection, view_id)?;
+                let view_id = DocumentViewId::from_str(view_id).map_err(fatal)?;
+                Ok(DocumentView::new(&view_id, &fields))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use futures::{FutureExt, StreamExt, TryStreamExt};
+    use rstest::rstest;
+
+    use crate::document::traits::AsDocument;
+    use crate::document::{
+        Document, DocumentId, DocumentViewFields, DocumentViewId, DocumentViewValue,
+    };
+    use crate::identity::KeyPair;
+    use crate::operation::{OperationAction, OperationBuilder, OperationId, OperationValue};
+    use crate::schema::SchemaId;
+    use crate::storage_provider::error::DocumentStorageError;
+    use crate::storage_provider::events::{EventFilter, StoreEvent};
+    use crate::storage_provider::sqlite::utils::STREAM_PAGE_SIZE;
+    use crate::storage_provider::sqlite::SqliteStore;
+    use crate::storage_provider::traits::{
+        DocumentStore, OperationStore, SubscriptionStore, TransactionStore,
+    };
+    use crate::test_utils::constants::{self, test_fields};
+    use crate::test_utils::fixtures::{
+        populate_store_config, random_document_id, random_operation_id, schema_id,
+    };
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+
+    #[rstest]
+    #[tokio::test]
+    async fn gets_one_document(
+        #[from(populate_store_config)]
+        #[with(1, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (_, documents) = populate_store(&store, &config).await;
+        let document_id = documents[0].clone();
+
+        let document = store.get_document(&document_id).await.unwrap().unwrap();
+
+        for (key, value) in test_fields() {
+            assert!(document.get(key).is_some());
+            assert_eq!(document.get(key).unwrap(), &value);
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn document_does_not_exist(
+        random_document_id: DocumentId,
+        #[from(populate_store_config)]
+        #[with(1, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        populate_store(&store, &config).await;
+        let document = store.get_document(&random_document_id).await.unwrap();
+
+        assert!(document.is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn updates_a_document(
+        schema_id: SchemaId,
+        #[from(random_operation_id)] operation_id: OperationId,
+        #[from(populate_store_config)]
+        #[with(1, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+
+        let public_key = key_pairs[0].public_key();
+        let document_id = documents[0].clone();
+        let create_operation_id: OperationId = document_id.as_str().parse().unwrap();
+
+        let field_to_update = ("age", OperationValue::Integer(29));
+        let update_operation = OperationBuilder::new(&schema_id)
+            .action(OperationAction::Update)
+            .previous(&create_operation_id.clone().into())
+            .fields(std::slice::from_ref(&field_to_update))
+            .build()
+            .unwrap();
+
+        store
+            .insert_operation(&operation_id, &public_key, &update_operation, &document_id)
+            .await
+            .unwrap();
+
+        let document = store.get_document(&document_id).await.unwrap().unwrap();
+
+        assert!(document.get(field_to_update.0).is_some());
+        assert_eq!(document.get(field_to_update.0).unwrap(), &field_to_update.1);
+
+        // The document can still be materialised at it's first view.
+        let view_id = DocumentViewId::new(&[create_operation_id]);
+        let document = store
+            .get_document_by_view_id(&view_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(document.view_id(), &view_id);
+        assert_ne!(document.get(field_to_update.0).unwrap(), &field_to_update.1);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn gets_documents_by_schema(
+        #[from(populate_store_config)]
+        #[with(10, 2, 1, false, constants::schema())]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        populate_store(&store, &config).await;
+
+        let schema_id = SchemaId::from_str(constants::SCHEMA_ID).unwrap();
+        let schema_documents = store.get_documents_by_schema(&schema_id).await.unwrap();
+
+        assert_eq!(schema_documents.len(), 2);
+
+        let schema_documents = store
+            .get_documents_by_schema(&SchemaId::SchemaDefinition(1))
+            .await
+            .unwrap();
+
+        assert_eq!(schema_documents.len(), 0);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn streams_documents_in_pages() {
+        let store = SqliteStore::in_memory().unwrap();
+        let schema_id = constants::schema().id().to_owned();
+        let public_key = KeyPair::new().public_key();
+        let num = STREAM_PAGE_SIZE * 2 + 1;
+
+        let create_operation = OperationBuilder::new(&schema_id)
+            .fields(&test_fields())
+            .build()
+            .unwrap();
+
+        let mut document_ids = Vec::new();
+        for _ in 0..num {
+            let operation_id = random_operation_id();
+            let document_id: DocumentId = operation_id.as_str().parse().unwrap();
+            store
+                .insert_operation(&operation_id, &public_key, &create_operation, &document_id)
+                .await
+                .unwrap();
+            document_ids.push(document_id);
+        }
+
+        // A document without a CREATE operation can't be built and is skipped.
+        let update_operation = OperationBuilder::new(&schema_id)
+            .action(OperationAction::Update)
+            .previous(&random_operation_id().into())
+            .fields(&[("age", OperationValue::Integer(29))])
+            .build()
+            .unwrap();
+        store
+            .insert_operation(
+                &random_operation_id(),
+                &public_key,
+                &update_operation,
+                &random_document_id(),
+            )
+            .await
+            .unwrap();
+
+        let documents: Vec<Document> = store
+            .stream_documents_by_schema(&schema_id)
+            .try_collect()
+            .await
+            .unwrap();
+        let streamed_ids: Vec<DocumentId> = documents
+            .iter()
+            .map(|document| document.id().to_owned())
+            .collect();
+
+        document_ids.sort();
+        assert_eq!(streamed_ids, document_ids);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn inserts_and_gets_document_views(
+        #[from(populate_store_config)]
+        #[with(1, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (_, documents) = populate_store(&store, &config).await;
+        let document_id = documents[0].clone();
+        let document = store.get_document(&document_id).await.unwrap().unwrap();
+
+        store.insert_document(&document).await.unwrap();
+
+        // All values survive the round trip through the database.
+        let document_views = store.get_document_views(&document_id).await.unwrap();
+        assert_eq!(document_views, vec![document.view().unwrap()]);
+
+        // Inserting the same view again doesn't change anything.
+        store
+            .insert_document_view(
+                &document.view().unwrap(),
+                &document_id,
+                document.schema_id(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_document_views(&document_id).await.unwrap().len(),
+            1
+        );
+
+        // Stored documents can't change their schema.
+        let other_schema_document = Document::new(
+            document.id(),
+            document.fields(),
+            &SchemaId::SchemaDefinition(1),
+            document.view_id(),
+            document.author(),
+        );
+        assert!(matches!(
+            store.insert_document(&other_schema_document).await,
+            Err(DocumentStorageError::DocumentInsertionError(_))
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn returns_stored_document_while_up_to_date(
+        schema_id: SchemaId,
+        #[from(random_operation_id)] operation_id: OperationId,
+        #[from(populate_store_config)]
+        #[with(1, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let document_id = documents[0].clone();
+        let mut document = store.get_document(&document_id).await.unwrap().unwrap();
+
+        // Change the fields of the materialised document before storing it, like this we can tell
+        // if the stored document is returned or if it was materialised again.
+        let view_id = document.view_id().to_owned();
+        let mut fields = DocumentViewFields::new();
+        fields.insert(
+            "age",
+            DocumentViewValue::new(&operation_id, &OperationValue::Integer(100)),
+        );
+        document.update_view(&view_id, Some(&fields));
+        store.insert_document(&document).await.unwrap();
+
+        let stored_document = store.get_document(&document_id).await.unwrap().unwrap();
+        assert_eq!(
+            stored_document.get("age"),
+            Some(&OperationValue::Integer(100))
+        );
+
+        let stored_document = store
+            .get_document_by_view_id(&view_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored_document.get("age"),
+            Some(&OperationValue::Integer(100))
+        );
+        assert_eq!(stored_document.author(), document.author());
+
+        // After a new operation was added the document is materialised again.
+        let update_operation = OperationBuilder::new(&schema_id)
+            .action(OperationAction::Update)
+            .previous(&view_id)
+            .fields(&[("age", OperationValue::Integer(29))])
+            .build()
+            .unwrap();
+        store
+            .insert_operation(
+                &operation_id,
+                &key_pairs[0].public_key(),
+                &update_operation,
+                &document_id,
+            )
+            .await
+            .unwrap();
+
+        let document = store.get_document(&document_id).await.unwrap().unwrap();
+        assert_eq!(document.get("age"), Some(&OperationValue::Integer(29)));
+        assert_eq!(document.view_id(), &DocumentViewId::new(&[operation_id]));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn notifies_about_inserted_document_views(
+        #[from(populate_store_config)]
+        #[with(2, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (_, documents) = populate_store(&store, &config).await;
+        let document = store.get_document(&documents[0]).await.unwrap().unwrap();
+
+        let mut events = store.subscribe(EventFilter::new().document_id(document.id()));
+        store.insert_document(&document).await.unwrap();
+
+        assert_eq!(
+            events.next().await,
+            Some(StoreEvent::DocumentViewUpdated {
+                view_id: document.view_id().to_owned(),
+                schema_id: document.schema_id().to_owned(),
+                document_id: document.id().to_owned(),
+            })
+        );
+
+        // Inserting a view which is already stored doesn't notify again.
+        store
+            .insert_document_view(
+                &document.view().unwrap(),
+                document.id(),
+                document.schema_id(),
+            )
+            .await
+            .unwrap();
+        assert!(events.next().now_or_never().is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn notifies_about_document_views_after_commit(
+        #[from(populate_store_config)]
+        #[with(2, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (_, documents) = populate_store(&store, &config).await;
+        let document = store.get_document(&documents[0]).await.unwrap().unwrap();
+        let mut events = store.subscribe(EventFilter::new().document_id(document.id()));
+
+        // Views inserted through a transaction which was rolled back are never announced.
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction.insert_document(&document).await.unwrap();
+        store.rollback_transaction(transaction).await.unwrap();
+        assert!(events.next().now_or_never().is_none());
+
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction.insert_document(&document).await.unwrap();
+        assert!(events.next().now_or_never().is_none());
+
+        store.commit_transaction(transaction).await.unwrap();
+        assert_eq!(
+            events.next().await,
+            Some(StoreEvent::DocumentViewUpdated {
+                view_id: document.view_id().to_owned(),
+                schema_id: document.schema_id().to_owned(),
+                document_id: document.id().to_owned(),
+            })
+        );
+    }
+}
@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::convert::TryFrom;
+
+use async_trait::async_trait;
+use log::debug;
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::entry::decode::decode_entry;
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{EncodedEntry, Entry, LogId, SeqNum};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::EncodedOperation;
+use crate::storage_provider::error::EntryStorageError;
+use crate::storage_provider::sqlite::utils::{decode_u64, encode_u64, is_constraint_violation};
+use crate::storage_provider::sqlite::{SqliteEntry, SqliteStore};
+use crate::storage_provider::traits::EntryStore;
+
+const SELECT_ENTRY: &str = "SELECT entry_bytes, payload_bytes FROM entries";
+
+/// Read the entry bytes and optional payload bytes from a row.
+fn entry_from_row(row: &Row) -> rusqlite::Result<(Vec<u8>, Option<Vec<u8>>)> {
+    Ok((row.get(0)?, row.get(1)?))
+}
+
+/// Decode the raw bytes of an entry and its payload retrieved from the database.
+fn decode_row(
+    (entry_bytes, payload_bytes): (Vec<u8>, Option<Vec<u8>>),
+) -> Result<SqliteEntry, EntryStorageError> {
+    let encoded_entry = EncodedEntry::from_bytes(&entry_bytes);
+    let entry =
+        decode_entry(&encoded_entry).map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+    Ok(SqliteEntry {
+        entry,
+        encoded_entry,
+        payload: payload_bytes.map(|bytes| EncodedOperation::from_bytes(&bytes)),
+    })
+}
+
+/// Implement `EntryStore` trait on `SqliteStore`
+#[async_trait]
+impl EntryStore for SqliteStore {
+    type Entry = SqliteEntry;
+
+    /// Insert an `Entry` to the store in it's encoded and decoded form. Optionally also store it's encoded
+    /// operation.
+    async fn insert_entry(
+        &self,
+        entry: &Entry,
+        encoded_entry: &EncodedEntry,
+        operation: Option<&EncodedOperation>,
+    ) -> Result<(), EntryStorageError> {
+        debug!("Inserting entry: {} into store", encoded_entry.hash());
+
+        let connection = self.connection().await;
+        connection
+            .execute(
+                "INSERT INTO entries
+                    (entry_hash, public_key, log_id, seq_num, entry_bytes, payload_bytes)
+                VALUES
+                    (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    encoded_entry.hash().as_str(),
+                    entry.public_key().to_string(),
+                    encode_u64(entry.log_id().as_u64()),
+                    encode_u64(entry.seq_num().as_u64()),
+                    encoded_entry.into_bytes(),
+                    operation.map(|operation| operation.into_bytes()),
+                ],
+            )
+            .map_err(|err| match is_constraint_violation(&err) {
+                true => EntryStorageError::Custom(format!(
+                    "Entry {} or another entry at seq num {} of log {} already exists",
+                    encoded_entry.hash(),
+                    entry.seq_num().as_u64(),
+                    entry.log_id().as_u64()
+                )),
+                false => EntryStorageError::Custom(err.to_string()),
+            })?;
+
+        Ok(())
+    }
+
+    /// Remove the payload of an `Entry` while keeping the `Entry` itself.
+    async fn remove_payload(&self, hash: &Hash) -> Result<(), EntryStorageError> {
+        debug!("Removing payload of entry: {} from store", hash);
+
+        let connection = self.connection().await;
+        connection
+            .execute(
+                "UPDATE entries SET payload_bytes = NULL WHERE entry_hash = ?1",
+                params![hash.as_str()],
+            )
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Remove an `Entry` together with it's payload.
+    async fn remove_entry(&self, hash: &Hash) -> Result<(), EntryStorageError> {
+        debug!("Removing entry: {} from store", hash);
+
+        let connection = self.connection().await;
+        connection
+            .execute(
+                "DELETE FROM entries WHERE entry_hash = ?1",
+                params![hash.as_str()],
+            )
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Record that a `PublicKey`'s log was pruned below the passed sequence number.
+    async fn insert_pruned_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        seq_num: &SeqNum,
+    ) -> Result<(), EntryStorageError> {
+        let connection = self.connection().await;
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO pruned_logs (public_key, log_id, seq_num)
+                VALUES (?1, ?2, ?3)",
+                params![
+                    public_key.to_string(),
+                    encode_u64(log_id.as_u64()),
+                    encode_u64(seq_num.as_u64()),
+                ],
+            )
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Get the sequence number below which a `PublicKey`'s log was pruned.
+    async fn get_pruned_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+    ) -> Result<Option<SeqNum>, EntryStorageError> {
+        let connection = self.connection().await;
+        let seq_num: Option<String> = connection
+            .query_row(
+                "SELECT seq_num FROM pruned_logs WHERE public_key = ?1 AND log_id = ?2",
+                params![public_key.to_string(), encode_u64(log_id.as_u64())],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        seq_num
+            .map(|seq_num| {
+                let seq_num = decode_u64(&seq_num).map_err(EntryStorageError::Custom)?;
+                SeqNum::new(seq_num).map_err(|err| EntryStorageError::Custom(err.to_string()))
+            })
+            .transpose()
+    }
+
+    /// Get the total size of all payloads stored in the logs of a `PublicKey`, in bytes.
+    ///
+    /// The total is kept up to date by triggers on the entries table.
+    async fn get_payload_size_by_public_key(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<u64, EntryStorageError> {
+        let connection = self.connection().await;
+        let size: Option<i64> = connection
+            .query_row(
+                "SELECT size FROM payload_sizes WHERE public_key = ?1",
+                params![public_key.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        Ok(size.unwrap_or_default() as u64)
+    }
+
+    /// Get an `Entry` by it's `Hash`.
+    async fn get_entry(&self, hash: &Hash) -> Result<Option<SqliteEntry>, EntryStorageError> {
+        let connection = self.connection().await;
+        let row = connection
+            .query_row(
+                &format!("{} WHERE entry_hash = ?1", SELECT_ENTRY),
+                params![hash.as_str()],
+                entry_from_row,
+            )
+            .optional()
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        row.map(decode_row).transpose()
+    }
+
+    /// Get an `Entry` at sequence position within a `PublicKey`'s log.
+    async fn get_entry_at_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        seq_num: &SeqNum,
+    ) -> Result<Option<SqliteEntry>, EntryStorageError> {
+        let connection = self.connection().await;
+        let row = connection
+            .query_row(
+                &format!(
+                    "{} WHERE public_key = ?1 AND log_id = ?2 AND seq_num = ?3",
+                    SELECT_ENTRY
+                ),
+                params![
+                    public_key.to_string(),
+                    encode_u64(log_id.as_u64()),
+                    encode_u64(seq_num.as_u64()),
+                ],
+                entry_from_row,
+            )
+            .optional()
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        row.map(decode_row).transpose()
+    }
+
+    /// Get the latest `Entry` of `PublicKey`'s log.
+    async fn get_latest_entry(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+    ) -> Result<Option<SqliteEntry>, EntryStorageError> {
+        let connection = self.connection().await;
+        let row = connection
+            .query_row(
+                &format!(
+                    "{} WHERE public_key = ?1 AND log_id = ?2 ORDER BY seq_num DESC LIMIT 1",
+                    SELECT_ENTRY
+                ),
+                params![public_key.to_string(), encode_u64(log_id.as_u64())],
+                entry_from_row,
+            )
+            .optional()
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        row.map(decode_row).transpose()
+    }
+
+    /// Get up to `limit` entries of `PublicKey`'s log, starting at `from` sequence number.
+    async fn get_entries_from(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        from: &SeqNum,
+        limit: usize,
+    ) -> Result<Vec<SqliteEntry>, EntryStorageError> {
+        let connection = self.connection().await;
+        let mut statement = connection
+            .prepare(&format!(
+                "{} WHERE public_key = ?1 AND log_id = ?2 AND seq_num >= ?3
+                ORDER BY seq_num ASC LIMIT ?4",
+                SELECT_ENTRY
+            ))
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        let rows = statement
+            .query_map(
+                params![
+                    public_key.to_string(),
+                    encode_u64(log_id.as_u64()),
+                    encode_u64(from.as_u64()),
+                    // SQLite treats negative limits as no limit.
+                    i64::try_from(limit).unwrap_or(-1),
+                ],
+                entry_from_row,
+            )
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| EntryStorageError::Custom(err.to_string()))?;
+
+        rows.into_iter().map(decode_row).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::api::prune_log;
+    use crate::entry::decode::decode_entry;
+    use crate::entry::traits::{AsEncodedEntry, AsEntry};
+    use crate::entry::{EncodedEntry, LogId, SeqNum};
+    use crate::operation::EncodedOperation;
+    use crate::storage_provider::sqlite::SqliteStore;
+    use crate::storage_provider::traits::EntryStore;
+    use crate::test_utils::fixtures::{encoded_entry, encoded_operation, populate_store_config};
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+
+    #[rstest]
+    #[tokio::test]
+    async fn insert_get_entry(encoded_entry: EncodedEntry, encoded_operation: EncodedOperation) {
+        let store = SqliteStore::in_memory().unwrap();
+
+        // Insert an entry into the store.
+        let entry = decode_entry(&encoded_entry).unwrap();
+        assert!(store
+            .insert_entry(&entry, &encoded_entry, Some(&encoded_operation))
+            .await
+            .is_ok());
+
+        // Get an entry at a specific seq number from an authors log.
+        let entry_at_seq_num = store
+            .get_entry_at_seq_num(entry.public_key(), entry.log_id(), entry.seq_num())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(entry_at_seq_num.seq_num(), entry.seq_num());
+        assert_eq!(entry_at_seq_num.hash(), encoded_entry.hash());
+        assert_eq!(entry_at_seq_num.payload(), Some(&encoded_operation));
+
+        // Get the same entry by it's hash.
+        let entry_by_hash = store.get_entry(&encoded_entry.hash()).await.unwrap();
+        assert_eq!(entry_by_hash, Some(entry_at_seq_num));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_latest_entry(encoded_entry: EncodedEntry) {
+        let store = SqliteStore::in_memory().unwrap();
+        let entry = decode_entry(&encoded_entry).unwrap();
+
+        // Before an entry is inserted the latest entry should be none.
+        assert!(store
+            .get_latest_entry(entry.public_key(), entry.log_id())
+            .await
+            .unwrap()
+            .is_none());
+
+        // Insert an entry into the store.
+        assert!(store
+            .insert_entry(&entry, &encoded_entry, None)
+            .await
+            .is_ok());
+
+        let fetched_entry = store
+            .get_latest_entry(entry.public_key(), entry.log_id())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(fetched_entry.hash(), encoded_entry.hash());
+        assert!(fetched_entry.payload().is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_entries_from_populated_store(
+        #[from(populate_store_config)]
+        #[with(12, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        // Sequence numbers above 9 need to be ordered numerically, not by their string value.
+        let latest_entry = store
+            .get_latest_entry(&public_key, &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest_entry.seq_num(), &SeqNum::new(12).unwrap());
+
+        for seq_num in 1..=12 {
+            let entry = store
+                .get_entry_at_seq_num(
+                    &public_key,
+                    &LogId::default(),
+                    &SeqNum::new(seq_num).unwrap(),
+                )
+                .await
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(entry.seq_num().as_u64(), seq_num);
+            assert!(entry.payload().is_some());
+            assert_eq!(
+                store.get_entry(&entry.hash()).await.unwrap().unwrap(),
+                entry
+            );
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_paginated_entries(
+        #[from(populate_store_config)]
+        #[with(13, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        let entries = store
+            .get_entries_from(&public_key, &LogId::default(), &SeqNum::new(9).unwrap(), 3)
+            .await
+            .unwrap();
+        let seq_nums: Vec<u64> = entries
+            .iter()
+            .map(|entry| entry.seq_num().as_u64())
+            .collect();
+        assert_eq!(seq_nums, vec![9, 10, 11]);
+
+        // Read the whole log in pages of five entries.
+        let mut seq_nums = Vec::new();
+        let mut cursor = None;
+        let mut no_of_pages = 0;
+
+        loop {
+            let page = store
+                .get_paginated_entries(&public_key, &LogId::default(), cursor.as_ref(), 5)
+                .await
+                .unwrap();
+
+            no_of_pages += 1;
+            seq_nums.extend(page.entries.iter().map(|entry| entry.seq_num().as_u64()));
+
+            match page.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(no_of_pages, 3);
+        assert_eq!(seq_nums, (1..=13).collect::<Vec<u64>>());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn prunes_log(
+        #[from(populate_store_config)]
+        #[with(12, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+        let log_id = LogId::default();
+
+        assert!(store
+            .get_pruned_seq_num(&public_key, &log_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        prune_log(
+            &store,
+            &public_key,
+            &log_id,
+            &SeqNum::new(10).unwrap(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            store
+                .get_pruned_seq_num(&public_key, &log_id)
+                .await
+                .unwrap(),
+            Some(SeqNum::new(10).unwrap())
+        );
+
+        // Only the skiplink targets of the following entries are kept, without their payloads.
+        let entries = store
+            .get_entries_from(&public_key, &log_id, &SeqNum::default(), 20)
+            .await
+            .unwrap();
+        let seq_nums: Vec<u64> = entries
+            .iter()
+            .map(|entry| entry.seq_num().as_u64())
+            .collect();
+        assert_eq!(seq_nums, vec![1, 4, 8, 9, 10, 11, 12]);
+
+        for entry in entries {
+            assert_eq!(entry.payload().is_some(), entry.seq_num().as_u64() >= 10);
+        }
+    }
+}
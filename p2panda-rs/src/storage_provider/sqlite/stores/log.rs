@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_trait::async_trait;
+use log::debug;
+use rusqlite::{params, OptionalExtension};
+
+use crate::document::DocumentId;
+use crate::entry::LogId;
+use crate::identity::PublicKey;
+use crate::schema::SchemaId;
+use crate::storage_provider::error::LogStorageError;
+use crate::storage_provider::sqlite::utils::{decode_u64, encode_u64, is_constraint_violation};
+use crate::storage_provider::sqlite::SqliteStore;
+use crate::storage_provider::traits::LogStore;
+
+/// Implement the `LogStore` trait on SqliteStore
+#[async_trait]
+impl LogStore for SqliteStore {
+    /// Insert a log into the store.
+    async fn insert_log(
+        &self,
+        log_id: &LogId,
+        public_key: &PublicKey,
+        schema: &SchemaId,
+        document: &DocumentId,
+    ) -> Result<bool, LogStorageError> {
+        debug!(
+            "Inserting log {} into store for {}",
+            log_id.as_u64(),
+            public_key
+        );
+
+        let connection = self.connection().await;
+        let rows_affected = connection
+            .execute(
+                "INSERT INTO logs (public_key, log_id, document_id, schema_id)
+                VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    public_key.to_string(),
+                    encode_u64(log_id.as_u64()),
+                    document.to_string(),
+                    schema.to_string(),
+                ],
+            )
+            .map_err(|err| match is_constraint_violation(&err) {
+                true => LogStorageError::Custom(format!(
+                    "Log {} of {} already exists",
+                    log_id.as_u64(),
+                    public_key
+                )),
+                false => LogStorageError::Custom(err.to_string()),
+            })?;
+
+        Ok(rows_affected == 1)
+    }
+
+    /// Get the `LogId` for a `PublicKey` and `DocumentId`.
+    async fn get_log_id(
+        &self,
+        public_key: &PublicKey,
+        document_id: &DocumentId,
+    ) -> Result<Option<LogId>, LogStorageError> {
+        let connection = self.connection().await;
+        let log_id: Option<String> = connection
+            .query_row(
+                "SELECT log_id FROM logs WHERE public_key = ?1 AND document_id = ?2",
+                params![public_key.to_string(), document_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| LogStorageError::Custom(err.to_string()))?;
+
+        log_id
+            .map(|log_id| decode_u64(&log_id).map(LogId::new))
+            .transpose()
+            .map_err(LogStorageError::Custom)
+    }
+
+    /// Get the latest used `LogId` for a `PublicKey`.
+    async fn latest_log_id(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<LogId>, LogStorageError> {
+        let connection = self.connection().await;
+        let log_id: Option<String> = connection
+            .query_row(
+                "SELECT MAX(log_id) FROM logs WHERE public_key = ?1",
+                params![public_key.to_string()],
+                |row| row.get(0),
+            )
+            .map_err(|err| LogStorageError::Custom(err.to_string()))?;
+
+        log_id
+            .map(|log_id| decode_u64(&log_id).map(LogId::new))
+            .transpose()
+            .map_err(LogStorageError::Custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::document::DocumentId;
+    use crate::entry::LogId;
+    use crate::identity::KeyPair;
+    use crate::schema::SchemaId;
+    use crate::storage_provider::sqlite::SqliteStore;
+    use crate::storage_provider::traits::LogStore;
+    use crate::test_utils::fixtures::{document_id, key_pair, random_document_id, schema_id};
+
+    #[rstest]
+    #[tokio::test]
+    async fn insert_get_log(key_pair: KeyPair, schema_id: SchemaId, document_id: DocumentId) {
+        let store = SqliteStore::in_memory().unwrap();
+        let public_key = key_pair.public_key();
+
+        // Insert a log into the store.
+        assert!(store
+            .insert_log(&LogId::default(), &public_key, &schema_id, &document_id)
+            .await
+            .unwrap());
+
+        // Get a log_id from the store by public_key and document_id.
+        let log_id = store.get_log_id(&public_key, &document_id).await.unwrap();
+        assert_eq!(log_id, Some(LogId::default()));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_latest_log_id(
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        #[from(random_document_id)] document_id_1: DocumentId,
+        #[from(random_document_id)] document_id_2: DocumentId,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+
+        let public_key = key_pair.public_key();
+        let log_id = store.latest_log_id(&public_key).await.unwrap();
+        assert_eq!(log_id, None);
+
+        store
+            .insert_log(&LogId::default(), &public_key, &schema_id, &document_id_1)
+            .await
+            .unwrap();
+
+        let log_id = store.latest_log_id(&public_key).await.unwrap();
+        assert_eq!(log_id, Some(LogId::default()));
+
+        // Log ids are compared numerically, not by their string representation.
+        store
+            .insert_log(&LogId::new(10), &public_key, &schema_id, &document_id_2)
+            .await
+            .unwrap();
+
+        let log_id = store.latest_log_id(&public_key).await.unwrap();
+        assert_eq!(log_id, Some(LogId::new(10)));
+    }
+}
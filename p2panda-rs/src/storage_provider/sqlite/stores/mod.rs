@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+mod document;
+mod entry;
+mod log;
+mod operation;
+mod subscription;
+mod transaction;
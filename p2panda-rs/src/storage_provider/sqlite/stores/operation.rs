@@ -0,0 +1,653 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use log::debug;
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+
+use crate::document::{DocumentId, DocumentViewId};
+use crate::identity::PublicKey;
+use crate::operation::traits::AsOperation;
+use crate::operation::{
+    Operation, OperationAction, OperationFields, OperationId, OperationVersion,
+};
+use crate::schema::SchemaId;
+use crate::storage_provider::error::OperationStorageError;
+use crate::storage_provider::sqlite::utils::{
+    decode_value, encode_value, stream_pages, STREAM_PAGE_SIZE,
+};
+use crate::storage_provider::sqlite::{SqliteOperation, SqliteStore};
+use crate::storage_provider::traits::OperationStore;
+use crate::storage_provider::utils::{referenced_document_views, referenced_documents};
+
+/// Raw column values of a row in the operations table.
+type OperationRow = (String, String, String, String, u64, u64, Option<String>);
+
+/// Select operations matching the passed filter and reconstruct them together with their fields.
+fn query_operations(
+    connection: &Connection,
+    filter: &str,
+    param: &dyn ToSql,
+) -> Result<Vec<SqliteOperation>, OperationStorageError> {
+    query_operations_page(connection, filter, param, "", None)
+}
+
+/// Select operations matching the passed filter ordered by their id, starting after the passed
+/// operation id and reading at most `limit` operations.
+fn query_operations_page(
+    connection: &Connection,
+    filter: &str,
+    param: &dyn ToSql,
+    after: &str,
+    limit: Option<usize>,
+) -> Result<Vec<SqliteOperation>, OperationStorageError> {
+    let fatal = |err: rusqlite::Error| OperationStorageError::FatalStorageError(err.to_string());
+
+    // A negative limit returns all rows.
+    let limit = limit.map_or(-1, |limit| limit as i64);
+
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT
+                operation_id, public_key, document_id, schema_id, action, version, previous
+            FROM
+                operations
+            WHERE
+                ({}) AND operation_id > ?2
+            ORDER BY
+                operation_id
+            LIMIT
+                ?3",
+            filter
+        ))
+        .map_err(fatal)?;
+
+    let rows = statement
+        .query_map(params![param, after, limit], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ))
+        })
+        .map_err(fatal)?
+        .collect::<Result<Vec<OperationRow>, _>>()
+        .map_err(fatal)?;
+
+    let mut fields_statement = connection
+        .prepare(
+            "SELECT name, field_type, value FROM operation_fields
+            WHERE operation_id = ?1
+            ORDER BY name",
+        )
+        .map_err(fatal)?;
+
+    rows.into_iter()
+        .map(|row| {
+            let fields = fields_statement
+                .query_map([&row.0], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(fatal)?
+                .collect::<Result<Vec<(String, String, String)>, _>>()
+                .map_err(fatal)?;
+
+            decode_operation(row, fields).map_err(OperationStorageError::FatalStorageError)
+        })
+        .collect()
+}
+
+/// Returns a page of streamed operations together with their ids.
+fn operation_page(operations: Vec<SqliteOperation>) -> (Vec<SqliteOperation>, Vec<String>) {
+    let ids = operations
+        .iter()
+        .map(|operation| operation.id.as_str().to_owned())
+        .collect();
+
+    (operations, ids)
+}
+
+/// Decode the raw values of an operation and its fields retrieved from the database.
+fn decode_operation(
+    (id, public_key, document_id, schema_id, action, version, previous): OperationRow,
+    field_rows: Vec<(String, String, String)>,
+) -> Result<SqliteOperation, String> {
+    let action = OperationAction::try_from(action).map_err(|err| err.to_string())?;
+
+    let version = match version {
+        1 => OperationVersion::V1,
+        _ => return Err(format!("Unsupported operation version {}", version)),
+    };
+
+    let previous = previous
+        .map(|previous| DocumentViewId::from_str(&previous))
+        .transpose()
+        .map_err(|err| err.to_string())?;
+
+    // Only CREATE and UPDATE operations contain fields.
+    let fields = match action {
+        OperationAction::Delete => None,
+        _ => {
+            let mut fields = OperationFields::new();
+            for (name, field_type, value) in field_rows {
+                fields
+                    .insert(&name, decode_value(&field_type, &value)?)
+                    .map_err(|err| err.to_string())?;
+            }
+            Some(fields)
+        }
+    };
+
+    Ok(SqliteOperation {
+        id: OperationId::from_str(&id).map_err(|err| err.to_string())?,
+        operation: Operation {
+            version,
+            action,
+            schema_id: SchemaId::from_str(&schema_id).map_err(|err| err.to_string())?,
+            previous,
+            fields,
+        },
+        public_key: PublicKey::new(&public_key).map_err(|err| err.to_string())?,
+        document_id: DocumentId::from_str(&document_id).map_err(|err| err.to_string())?,
+    })
+}
+
+#[async_trait]
+impl OperationStore for SqliteStore {
+    type Operation = SqliteOperation;
+
+    /// Insert an `Operation` into the store.
+    ///
+    /// The operation and all of it's fields are written together inside a savepoint, this also
+    /// works when an outer transaction is in progress.
+    async fn insert_operation(
+        &self,
+        id: &OperationId,
+        public_key: &PublicKey,
+        operation: &Operation,
+        document_id: &DocumentId,
+    ) -> Result<(), OperationStorageError> {
+        debug!(
+            "Inserting {} operation: {} into store",
+            operation.action().as_str(),
+            id,
+        );
+
+        let fatal =
+            |err: rusqlite::Error| OperationStorageError::FatalStorageError(err.to_string());
+
+        let mut connection = self.connection().await;
+        let tx = connection.savepoint().map_err(fatal)?;
+
+        let is_duplicate_id = tx
+            .query_row(
+                "SELECT 1 FROM operations WHERE operation_id = ?1",
+                params![id.as_str()],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(fatal)?
+            .is_some();
+
+        if is_duplicate_id {
+            return Err(OperationStorageError::InsertionError(id.clone()));
+        }
+
+        tx.execute(
+            "INSERT INTO operations
+                (operation_id, public_key, document_id, schema_id, action, version, previous)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id.as_str(),
+                public_key.to_string(),
+                document_id.to_string(),
+                operation.schema_id().to_string(),
+                operation.action().as_u64(),
+                operation.version().as_u64(),
+                operation.previous().map(|previous| previous.to_string()),
+            ],
+        )
+        .map_err(fatal)?;
+
+        if let Some(fields) = operation.fields() {
+            for (name, value) in fields.iter() {
+                tx.execute(
+                    "INSERT INTO operation_fields (operation_id, name, field_type, value)
+                    VALUES (?1, ?2, ?3, ?4)",
+                    params![id.as_str(), name, value.field_type(), encode_value(value)],
+                )
+                .map_err(fatal)?;
+            }
+        }
+
+        let document_targets = referenced_documents(operation)
+            .iter()
+            .map(|document_id| (document_id.to_string(), false))
+            .collect::<Vec<_>>();
+        let view_targets = referenced_document_views(operation)
+            .iter()
+            .map(|view_id| (view_id.to_string(), true))
+            .collect::<Vec<_>>();
+
+        for (target, is_pinned) in document_targets.into_iter().chain(view_targets) {
+            tx.execute(
+                "INSERT OR IGNORE INTO operation_relations (target, is_pinned, operation_id)
+                VALUES (?1, ?2, ?3)",
+                params![target, is_pinned, id.as_str()],
+            )
+            .map_err(fatal)?;
+        }
+
+        tx.commit().map_err(fatal)?;
+
+        Ok(())
+    }
+
+    /// Remove all `Operations` of a single `Document`.
+    ///
+    /// The operations, their fields and relations are removed together inside a savepoint.
+    async fn remove_operations_by_document_id(
+        &self,
+        id: &DocumentId,
+    ) -> Result<(), OperationStorageError> {
+        debug!("Removing operations of document: {} from store", id);
+
+        let fatal =
+            |err: rusqlite::Error| OperationStorageError::FatalStorageError(err.to_string());
+
+        let mut connection = self.connection().await;
+        let tx = connection.savepoint().map_err(fatal)?;
+
+        for table in ["operation_fields", "operation_relations"] {
+            tx.execute(
+                &format!(
+                    "DELETE FROM {} WHERE operation_id IN (
+                        SELECT operation_id FROM operations WHERE document_id = ?1
+                    )",
+                    table
+                ),
+                params![id.as_str()],
+            )
+            .map_err(fatal)?;
+        }
+
+        tx.execute(
+            "DELETE FROM operations WHERE document_id = ?1",
+            params![id.as_str()],
+        )
+        .map_err(fatal)?;
+
+        tx.commit().map_err(fatal)?;
+
+        Ok(())
+    }
+
+    /// Get an `Operation` identified by it's `OperationId`, returns `None` if no `Operation` was found.
+    async fn get_operation(
+        &self,
+        id: &OperationId,
+    ) -> Result<Option<SqliteOperation>, OperationStorageError> {
+        let connection = self.connection().await;
+        let mut operations = query_operations(&connection, "operation_id = ?1", &id.as_str())?;
+        Ok(operations.pop())
+    }
+
+    /// Get the `DocumentId` for an `Operation`.
+    async fn get_document_id_by_operation_id(
+        &self,
+        id: &OperationId,
+    ) -> Result<Option<DocumentId>, OperationStorageError> {
+        let connection = self.connection().await;
+        let document_id: Option<String> = connection
+            .query_row(
+                "SELECT document_id FROM operations WHERE operation_id = ?1",
+                params![id.as_str()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| OperationStorageError::FatalStorageError(err.to_string()))?;
+
+        document_id
+            .map(|document_id| DocumentId::from_str(&document_id))
+            .transpose()
+            .map_err(|err| OperationStorageError::FatalStorageError(err.to_string()))
+    }
+
+    /// Get all `Operations` for a single `Document`.
+    async fn get_operations_by_document_id(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<SqliteOperation>, OperationStorageError> {
+        let connection = self.connection().await;
+        query_operations(&connection, "document_id = ?1", &id.to_string())
+    }
+
+    /// Get all `Operations` for a certain `Schema`.
+    async fn get_operations_by_schema_id(
+        &self,
+        id: &SchemaId,
+    ) -> Result<Vec<SqliteOperation>, OperationStorageError> {
+        let connection = self.connection().await;
+        query_operations(&connection, "schema_id = ?1", &id.to_string())
+    }
+
+    /// Stream all `Operations` for a single `Document`.
+    ///
+    /// The operations are read from the database in pages of `STREAM_PAGE_SIZE` operations.
+    fn stream_operations_by_document_id<'a>(
+        &'a self,
+        id: &'a DocumentId,
+    ) -> BoxStream<'a, Result<SqliteOperation, OperationStorageError>>
+    where
+        Self: Sync,
+        Self::Operation: Send,
+    {
+        stream_pages(move |after| async move {
+            let connection = self.connection().await;
+            let operations = query_operations_page(
+                &connection,
+                "document_id = ?1",
+                &id.to_string(),
+                &after,
+                Some(STREAM_PAGE_SIZE),
+            )?;
+            Ok(operation_page(operations))
+        })
+    }
+
+    /// Stream all `Operations` for a certain `Schema`.
+    ///
+    /// The operations are read from the database in pages of `STREAM_PAGE_SIZE` operations.
+    fn stream_operations_by_schema_id<'a>(
+        &'a self,
+        id: &'a SchemaId,
+    ) -> BoxStream<'a, Result<SqliteOperation, OperationStorageError>>
+    where
+        Self: Sync,
+        Self::Operation: Send,
+    {
+        stream_pages(move |after| async move {
+            let connection = self.connection().await;
+            let operations = query_operations_page(
+                &connection,
+                "schema_id = ?1",
+                &id.to_string(),
+                &after,
+                Some(STREAM_PAGE_SIZE),
+            )?;
+            Ok(operation_page(operations))
+        })
+    }
+
+    /// Get the number of `Documents` created with a certain `Schema`.
+    ///
+    /// The count is kept up to date by triggers on the operations table.
+    async fn count_documents_by_schema_id(
+        &self,
+        id: &SchemaId,
+    ) -> Result<usize, OperationStorageError> {
+        let connection = self.connection().await;
+        let count: Option<i64> = connection
+            .query_row(
+                "SELECT count FROM document_counts WHERE schema_id = ?1",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| OperationStorageError::FatalStorageError(err.to_string()))?;
+
+        Ok(count.unwrap_or_default() as usize)
+    }
+
+    /// Get all `Operations` which point at a `Document` with a relation or relation list field.
+    async fn get_operations_referencing_document(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<SqliteOperation>, OperationStorageError> {
+        let connection = self.connection().await;
+        query_operations(
+            &connection,
+            "operation_id IN (
+                SELECT operation_id FROM operation_relations WHERE target = ?1 AND is_pinned = 0
+            )",
+            &id.to_string(),
+        )
+    }
+
+    /// Get all `Operations` which point at a `DocumentView` with a pinned relation or pinned
+    /// relation list field.
+    async fn get_operations_referencing_view(
+        &self,
+        id: &DocumentViewId,
+    ) -> Result<Vec<SqliteOperation>, OperationStorageError> {
+        let connection = self.connection().await;
+        query_operations(
+            &connection,
+            "operation_id IN (
+                SELECT operation_id FROM operation_relations WHERE target = ?1 AND is_pinned = 1
+            )",
+            &id.to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use rstest::rstest;
+
+    use crate::document::DocumentId;
+    use crate::entry::traits::AsEncodedEntry;
+    use crate::entry::LogId;
+    use crate::identity::PublicKey;
+    use crate::operation::traits::{AsOperation, WithPublicKey};
+    use crate::operation::{Operation, OperationId};
+    use crate::storage_provider::sqlite::utils::STREAM_PAGE_SIZE;
+    use crate::storage_provider::sqlite::{SqliteOperation, SqliteStore};
+    use crate::storage_provider::traits::{EntryStore, OperationStore};
+    use crate::storage_provider::utils::{referenced_document_views, referenced_documents};
+    use crate::test_utils::constants;
+    use crate::test_utils::fixtures::{
+        create_operation, delete_operation, document_id, operation_id, populate_store_config,
+        public_key, random_document_id, random_operation_id, random_previous_operations,
+        update_operation,
+    };
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+    use crate::WithId;
+
+    #[rstest]
+    #[case::create_operation(create_operation(constants::test_fields(), constants::schema().id().to_owned()))]
+    #[case::update_operation(update_operation(constants::test_fields(), constants::HASH.parse().unwrap(), constants::schema().id().to_owned()))]
+    #[case::update_operation_many_prev_ops(update_operation(constants::test_fields(), random_previous_operations(12), constants::schema().id().to_owned()))]
+    #[case::delete_operation(delete_operation(constants::HASH.parse().unwrap(), constants::schema().id().to_owned()))]
+    #[case::delete_operation_many_prev_ops(delete_operation(random_previous_operations(12), constants::schema().id().to_owned()))]
+    #[tokio::test]
+    async fn insert_get_operations(
+        #[case] operation: Operation,
+        #[from(public_key)] public_key: PublicKey,
+        operation_id: OperationId,
+        document_id: DocumentId,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+
+        let result = store
+            .insert_operation(&operation_id, &public_key, &operation, &document_id)
+            .await;
+        assert!(result.is_ok());
+
+        // Request the previously inserted operation by it's id, all values should survive the
+        // round trip through the database.
+        let returned_operation = store.get_operation(&operation_id).await.unwrap().unwrap();
+
+        assert_eq!(returned_operation.public_key(), &public_key);
+        assert_eq!(returned_operation.action(), operation.action());
+        assert_eq!(returned_operation.version(), operation.version());
+        assert_eq!(returned_operation.schema_id(), operation.schema_id());
+        assert_eq!(returned_operation.previous(), operation.previous());
+        assert_eq!(returned_operation.fields(), operation.fields());
+        assert_eq!(
+            WithId::<OperationId>::id(&returned_operation),
+            &operation_id
+        );
+        assert_eq!(WithId::<DocumentId>::id(&returned_operation), &document_id);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn insert_operation_twice(
+        #[from(create_operation)] operation: Operation,
+        public_key: PublicKey,
+        operation_id: OperationId,
+        document_id: DocumentId,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+
+        assert!(store
+            .insert_operation(&operation_id, &public_key, &operation, &document_id)
+            .await
+            .is_ok());
+
+        assert_eq!(
+            store.insert_operation(&operation_id, &public_key, &operation, &document_id).await.unwrap_err().to_string(),
+            format!("Error occured when inserting an operation with id OperationId(Hash(\"{}\")) into storage", operation_id.as_str())
+        )
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_operations_by_document_id(
+        #[from(populate_store_config)]
+        #[with(5, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+
+        let public_key = key_pairs[0].public_key();
+
+        let latest_entry = store
+            .get_latest_entry(&public_key, &LogId::default())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let document_id = store
+            .get_document_id_by_operation_id(&latest_entry.hash().into())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let operations_by_document_id = store
+            .get_operations_by_document_id(&document_id)
+            .await
+            .unwrap();
+
+        assert_eq!(operations_by_document_id.len(), 5);
+
+        let operations_by_schema_id = store
+            .get_operations_by_schema_id(config.schema.id())
+            .await
+            .unwrap();
+
+        assert_eq!(operations_by_schema_id.len(), 5);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn gets_referencing_operations(
+        #[from(create_operation)] operation: Operation,
+        public_key: PublicKey,
+        operation_id: OperationId,
+        #[from(random_document_id)] document_id: DocumentId,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        store
+            .insert_operation(&operation_id, &public_key, &operation, &document_id)
+            .await
+            .unwrap();
+
+        // The default test fields point at two documents and three document views.
+        for target in referenced_documents(&operation) {
+            let operations = store
+                .get_operations_referencing_document(&target)
+                .await
+                .unwrap();
+            assert_eq!(operations.len(), 1);
+            assert_eq!(WithId::<OperationId>::id(&operations[0]), &operation_id);
+        }
+
+        for target in referenced_document_views(&operation) {
+            let operations = store
+                .get_operations_referencing_view(&target)
+                .await
+                .unwrap();
+            assert_eq!(operations.len(), 1);
+        }
+
+        assert!(store
+            .get_operations_referencing_document(&document_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[rstest]
+    #[case::full_pages(STREAM_PAGE_SIZE * 2)]
+    #[case::partial_page(STREAM_PAGE_SIZE * 2 + 1)]
+    #[tokio::test]
+    async fn streams_operations_in_pages(
+        #[case] num: usize,
+        #[from(create_operation)] operation: Operation,
+        public_key: PublicKey,
+        #[from(random_document_id)] document_id: DocumentId,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+
+        for _ in 0..num {
+            store
+                .insert_operation(
+                    &random_operation_id(),
+                    &public_key,
+                    &operation,
+                    &document_id,
+                )
+                .await
+                .unwrap();
+        }
+
+        let operation_ids = |operations: Vec<SqliteOperation>| -> Vec<OperationId> {
+            operations
+                .iter()
+                .map(|operation| WithId::<OperationId>::id(operation).to_owned())
+                .collect()
+        };
+
+        let expected = operation_ids(
+            store
+                .get_operations_by_document_id(&document_id)
+                .await
+                .unwrap(),
+        );
+        assert_eq!(expected.len(), num);
+
+        let streamed: Vec<SqliteOperation> = store
+            .stream_operations_by_document_id(&document_id)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(operation_ids(streamed), expected);
+
+        let streamed: Vec<SqliteOperation> = store
+            .stream_operations_by_schema_id(&operation.schema_id())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(operation_ids(streamed), expected);
+    }
+}
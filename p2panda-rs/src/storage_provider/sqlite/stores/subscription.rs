@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use futures::stream::BoxStream;
+
+use crate::storage_provider::events::{EventFilter, StoreEvent};
+use crate::storage_provider::sqlite::SqliteStore;
+use crate::storage_provider::traits::SubscriptionStore;
+
+/// Subscriptions are held in memory, they only receive events sent via this handle to the
+/// database or it's clones.
+impl SubscriptionStore for SqliteStore {
+    /// Subscribe to events matching the filter.
+    fn subscribe(&self, filter: EventFilter) -> BoxStream<'static, StoreEvent> {
+        self.events.subscribe(filter)
+    }
+
+    /// Send an event to all matching subscribers.
+    fn notify(&self, event: StoreEvent) {
+        self.events.send(&event)
+    }
+}
@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::lock::Mutex;
+use log::debug;
+
+use crate::storage_provider::error::TransactionError;
+use crate::storage_provider::sqlite::provider::{ConnectionHandle, TransactionConnection};
+use crate::storage_provider::sqlite::SqliteStore;
+use crate::storage_provider::traits::TransactionStore;
+
+/// Transactions map directly onto SQLite transactions on the shared connection.
+///
+/// A transaction handle owns the connection until it is finished, this keeps writes of other
+/// users of the store out of it.
+#[async_trait]
+impl TransactionStore for SqliteStore {
+    type Transaction = SqliteStore;
+
+    /// Start a new transaction, waiting for other transactions to finish first.
+    async fn begin_transaction(&self) -> Result<SqliteStore, TransactionError> {
+        let connection = match &self.connection {
+            ConnectionHandle::Shared(connection) => connection.clone().lock_owned().await,
+            ConnectionHandle::Transaction(_) => return Err(TransactionError::NestedTransaction),
+        };
+
+        debug!("Beginning transaction");
+
+        connection
+            .execute_batch("BEGIN")
+            .map_err(|err| TransactionError::Custom(err.to_string()))?;
+
+        Ok(SqliteStore {
+            connection: ConnectionHandle::Transaction(Arc::new(Mutex::new(Some(
+                TransactionConnection {
+                    connection,
+                    events: Vec::new(),
+                },
+            )))),
+            events: self.events.clone(),
+        })
+    }
+
+    /// Commit a transaction, release the connection and notify subscribers about it's writes.
+    async fn commit_transaction(&self, transaction: SqliteStore) -> Result<(), TransactionError> {
+        let mut connection = match transaction.connection {
+            ConnectionHandle::Transaction(connection) => connection.lock().await.take(),
+            ConnectionHandle::Shared(_) => None,
+        }
+        .ok_or(TransactionError::NoTransaction)?;
+
+        debug!("Committing transaction");
+
+        // The transaction is rolled back when the connection is released without a successful
+        // commit.
+        connection
+            .connection
+            .execute_batch("COMMIT")
+            .map_err(|err| TransactionError::Custom(err.to_string()))?;
+
+        for event in std::mem::take(&mut connection.events) {
+            transaction.events.send(&event);
+        }
+
+        Ok(())
+    }
+
+    /// Rollback a transaction and release the connection.
+    async fn rollback_transaction(&self, transaction: SqliteStore) -> Result<(), TransactionError> {
+        let connection = match transaction.connection {
+            ConnectionHandle::Transaction(connection) => connection.lock().await.take(),
+            ConnectionHandle::Shared(_) => None,
+        }
+        .ok_or(TransactionError::NoTransaction)?;
+
+        debug!("Rolling back transaction");
+
+        connection
+            .connection
+            .execute_batch("ROLLBACK")
+            .map_err(|err| TransactionError::Custom(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+    use rstest::rstest;
+
+    use crate::document::DocumentId;
+    use crate::entry::decode::decode_entry;
+    use crate::entry::traits::AsEncodedEntry;
+    use crate::entry::{EncodedEntry, LogId};
+    use crate::identity::KeyPair;
+    use crate::operation::{Operation, OperationId};
+    use crate::schema::SchemaId;
+    use crate::storage_provider::error::TransactionError;
+    use crate::storage_provider::sqlite::SqliteStore;
+    use crate::storage_provider::traits::{EntryStore, LogStore, OperationStore, TransactionStore};
+    use crate::test_utils::fixtures::{
+        document_id, encoded_entry, key_pair, operation, operation_id, schema_id,
+    };
+
+    #[rstest]
+    #[tokio::test]
+    async fn commit_and_rollback(
+        encoded_entry: EncodedEntry,
+        operation: Operation,
+        operation_id: OperationId,
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        document_id: DocumentId,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let entry = decode_entry(&encoded_entry).unwrap();
+        let public_key = key_pair.public_key();
+
+        // Writes are discarded on rollback, including operations which were written inside
+        // their own savepoint.
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction
+            .insert_log(&LogId::default(), &public_key, &schema_id, &document_id)
+            .await
+            .unwrap();
+        transaction
+            .insert_entry(&entry, &encoded_entry, None)
+            .await
+            .unwrap();
+        transaction
+            .insert_operation(&operation_id, &public_key, &operation, &document_id)
+            .await
+            .unwrap();
+        store.rollback_transaction(transaction).await.unwrap();
+
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(store
+            .get_log_id(&public_key, &document_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(store.get_operation(&operation_id).await.unwrap().is_none());
+
+        // Writes are persisted on commit.
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction
+            .insert_entry(&entry, &encoded_entry, None)
+            .await
+            .unwrap();
+        transaction
+            .insert_operation(&operation_id, &public_key, &operation, &document_id)
+            .await
+            .unwrap();
+        store.commit_transaction(transaction).await.unwrap();
+
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_some());
+        assert!(store.get_operation(&operation_id).await.unwrap().is_some());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn transaction_owns_connection(
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        document_id: DocumentId,
+    ) {
+        let store = SqliteStore::in_memory().unwrap();
+        let public_key = key_pair.public_key();
+
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction
+            .insert_log(&LogId::default(), &public_key, &schema_id, &document_id)
+            .await
+            .unwrap();
+
+        // Other users of the store wait for the transaction to finish, their queries can't become
+        // part of it.
+        assert!(store
+            .get_log_id(&public_key, &document_id)
+            .now_or_never()
+            .is_none());
+        assert!(store.begin_transaction().now_or_never().is_none());
+
+        // Dropping the handle rolls the transaction back and releases the connection.
+        drop(transaction);
+
+        assert_eq!(
+            store.get_log_id(&public_key, &document_id).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn transaction_handles() {
+        let store = SqliteStore::in_memory().unwrap();
+
+        assert!(matches!(
+            store.commit_transaction(store.clone()).await,
+            Err(TransactionError::NoTransaction)
+        ));
+        assert!(matches!(
+            store.rollback_transaction(store.clone()).await,
+            Err(TransactionError::NoTransaction)
+        ));
+
+        let transaction = store.begin_transaction().await.unwrap();
+        assert!(matches!(
+            transaction.begin_transaction().await,
+            Err(TransactionError::NestedTransaction)
+        ));
+
+        // Finished transactions can't be committed again.
+        let handle = transaction.clone();
+        store.rollback_transaction(transaction).await.unwrap();
+        assert!(matches!(
+            store.commit_transaction(handle).await,
+            Err(TransactionError::NoTransaction)
+        ));
+    }
+}
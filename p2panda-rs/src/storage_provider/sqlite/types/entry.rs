@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{EncodedEntry, Entry, LogId, SeqNum, Signature};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::EncodedOperation;
+use crate::storage_provider::traits::EntryWithOperation;
+
+/// An entry retrieved from the SQLite store, together with its encoded form and optional payload.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SqliteEntry {
+    /// The decoded entry.
+    pub(crate) entry: Entry,
+
+    /// The encoded entry.
+    pub(crate) encoded_entry: EncodedEntry,
+
+    /// The optional entry payload, which is an encoded operation.
+    pub(crate) payload: Option<EncodedOperation>,
+}
+
+impl SqliteEntry {
+    /// Returns the encoded operation of this entry if it was stored.
+    pub fn payload(&self) -> Option<&EncodedOperation> {
+        self.payload.as_ref()
+    }
+}
+
+impl EntryWithOperation for SqliteEntry {
+    fn payload(&self) -> Option<&EncodedOperation> {
+        self.payload.as_ref()
+    }
+}
+
+impl AsEntry for SqliteEntry {
+    fn backlink(&self) -> Option<&Hash> {
+        self.entry.backlink()
+    }
+
+    fn skiplink(&self) -> Option<&Hash> {
+        self.entry.skiplink()
+    }
+
+    fn seq_num(&self) -> &SeqNum {
+        self.entry.seq_num()
+    }
+
+    fn log_id(&self) -> &LogId {
+        self.entry.log_id()
+    }
+
+    fn public_key(&self) -> &PublicKey {
+        self.entry.public_key()
+    }
+
+    fn payload_size(&self) -> u64 {
+        self.entry.payload_size()
+    }
+
+    fn payload_hash(&self) -> &Hash {
+        self.entry.payload_hash()
+    }
+
+    fn signature(&self) -> &Signature {
+        self.entry.signature()
+    }
+}
+
+impl AsEncodedEntry for SqliteEntry {
+    fn hash(&self) -> Hash {
+        self.encoded_entry.hash()
+    }
+
+    fn into_bytes(&self) -> Vec<u8> {
+        self.encoded_entry.into_bytes()
+    }
+
+    fn size(&self) -> u64 {
+        self.encoded_entry.size()
+    }
+}
@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+mod entry;
+mod operation;
+
+pub use entry::SqliteEntry;
+pub use operation::SqliteOperation;
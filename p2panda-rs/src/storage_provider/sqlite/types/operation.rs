@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::document::{DocumentId, DocumentViewId};
+use crate::identity::PublicKey;
+use crate::operation::traits::{AsOperation, WithPublicKey};
+use crate::operation::{
+    Operation, OperationAction, OperationFields, OperationId, OperationVersion,
+};
+use crate::schema::SchemaId;
+use crate::WithId;
+
+/// An operation retrieved from the SQLite store with its id, author and document id.
+#[derive(Debug, Clone)]
+pub struct SqliteOperation {
+    /// Identifier of the operation.
+    pub(crate) id: OperationId,
+
+    /// The operation itself.
+    pub(crate) operation: Operation,
+
+    /// Public key of the author of this operation.
+    pub(crate) public_key: PublicKey,
+
+    /// Identifier of the document this operation belongs to.
+    pub(crate) document_id: DocumentId,
+}
+
+impl WithPublicKey for SqliteOperation {
+    /// Returns the public key of the author of this operation.
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+}
+
+impl WithId<OperationId> for SqliteOperation {
+    /// Returns the identifier for this operation.
+    fn id(&self) -> &OperationId {
+        &self.id
+    }
+}
+
+impl WithId<DocumentId> for SqliteOperation {
+    /// Returns the identifier of the document this operation belongs to.
+    fn id(&self) -> &DocumentId {
+        &self.document_id
+    }
+}
+
+impl AsOperation for SqliteOperation {
+    /// Returns action type of operation.
+    fn action(&self) -> OperationAction {
+        self.operation.action.to_owned()
+    }
+
+    /// Returns schema id of operation.
+    fn schema_id(&self) -> SchemaId {
+        self.operation.schema_id.to_owned()
+    }
+
+    /// Returns version of operation.
+    fn version(&self) -> OperationVersion {
+        self.operation.version.to_owned()
+    }
+
+    /// Returns application data fields of operation.
+    fn fields(&self) -> Option<OperationFields> {
+        self.operation.fields.clone()
+    }
+
+    /// Returns vector of this operation's previous operation ids.
+    fn previous(&self) -> Option<DocumentViewId> {
+        self.operation.previous.clone()
+    }
+}
@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Conversions between p2panda types and their representation in SQLite columns and paging of
+//! streamed query results.
+use std::future::Future;
+use std::str::FromStr;
+
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+
+use crate::document::{DocumentId, DocumentViewId};
+use crate::operation::{
+    OperationValue, PinnedRelation, PinnedRelationList, Relation, RelationList, TextDelta,
+};
+
+/// Encode an u64 as a zero-padded string.
+///
+/// SQLite integers are signed 64 bit values and can't hold every `LogId` or `SeqNum`. Padding the
+/// decimal representation keeps lexicographic ordering of the column equal to numeric ordering.
+pub fn encode_u64(value: u64) -> String {
+    format!("{:020}", value)
+}
+
+/// Decode an u64 previously encoded with `encode_u64`.
+pub fn decode_u64(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid u64 value in database: {}", value))
+}
+
+/// Returns true if a query failed because it violated a constraint of the database schema, for
+/// example by inserting a row with an already existing primary key.
+pub fn is_constraint_violation(err: &rusqlite::Error) -> bool {
+    matches!(
+        err,
+        rusqlite::Error::SqliteFailure(failure, _)
+            if failure.code == rusqlite::ErrorCode::ConstraintViolation
+    )
+}
+
+/// Encode an operation value into a string, the field type is stored alongside it.
+pub fn encode_value(value: &OperationValue) -> String {
+    match value {
+        OperationValue::Boolean(value) => value.to_string(),
+        OperationValue::Bytes(value) => hex::encode(value),
+        OperationValue::Integer(value) => value.to_string(),
+        OperationValue::Float(value) => value.to_string(),
+        OperationValue::String(value) => value.to_owned(),
+        OperationValue::Relation(relation) => relation.document_id().to_string(),
+        OperationValue::RelationList(list) => list
+            .iter()
+            .map(|document_id| document_id.to_string())
+            .collect::<Vec<String>>()
+            .join(","),
+        OperationValue::PinnedRelation(relation) => relation.view_id().to_string(),
+        OperationValue::PinnedRelationList(list) => list
+            .iter()
+            .map(|view_id| view_id.to_string())
+            .collect::<Vec<String>>()
+            .join(","),
+        OperationValue::Text(delta) => {
+            let mut bytes = Vec::new();
+            // Unwrap as serializing into a vector doesn't fail.
+            ciborium::ser::into_writer(delta, &mut bytes).unwrap();
+            hex::encode(bytes)
+        }
+    }
+}
+
+/// Decode an operation value from its field type and string representation.
+pub fn decode_value(field_type: &str, value: &str) -> Result<OperationValue, String> {
+    let invalid =
+        |err: &dyn std::fmt::Display| format!("Invalid {} value in database: {}", field_type, err);
+
+    let value = match field_type {
+        "bool" => OperationValue::Boolean(bool::from_str(value).map_err(|err| invalid(&err))?),
+        "bytes" => OperationValue::Bytes(hex::decode(value).map_err(|err| invalid(&err))?),
+        "int" => OperationValue::Integer(i64::from_str(value).map_err(|err| invalid(&err))?),
+        "float" => OperationValue::Float(f64::from_str(value).map_err(|err| invalid(&err))?),
+        "str" => OperationValue::String(value.to_owned()),
+        "relation" => OperationValue::Relation(Relation::new(
+            DocumentId::from_str(value).map_err(|err| invalid(&err))?,
+        )),
+        "relation_list" => OperationValue::RelationList(RelationList::new(
+            split_list(value)
+                .map(DocumentId::from_str)
+                .collect::<Result<Vec<DocumentId>, _>>()
+                .map_err(|err| invalid(&err))?,
+        )),
+        "pinned_relation" => OperationValue::PinnedRelation(PinnedRelation::new(
+            DocumentViewId::from_str(value).map_err(|err| invalid(&err))?,
+        )),
+        "pinned_relation_list" => OperationValue::PinnedRelationList(PinnedRelationList::new(
+            split_list(value)
+                .map(DocumentViewId::from_str)
+                .collect::<Result<Vec<DocumentViewId>, _>>()
+                .map_err(|err| invalid(&err))?,
+        )),
+        "text" => {
+            let bytes = hex::decode(value).map_err(|err| invalid(&err))?;
+            let delta: TextDelta =
+                ciborium::de::from_reader(bytes.as_slice()).map_err(|err| invalid(&err))?;
+            OperationValue::Text(delta)
+        }
+        _ => return Err(format!("Unknown field type in database: {}", field_type)),
+    };
+
+    Ok(value)
+}
+
+/// Number of rows read from the database at once by streaming queries.
+pub const STREAM_PAGE_SIZE: usize = 100;
+
+/// Streams the items of a query page by page.
+///
+/// Rows are ordered by a unique text column. `query_page` is called with the value of this column
+/// in the last row of the previous page, or an empty string for the first page, and reads at most
+/// `STREAM_PAGE_SIZE` of the following rows. It returns the items of the page together with the
+/// column values of all rows it read, rows can be skipped by not returning an item for them. The
+/// stream ends after the first page with less rows or the first error.
+pub fn stream_pages<'a, T, E, F, Fut>(query_page: F) -> BoxStream<'a, Result<T, E>>
+where
+    T: Send + 'a,
+    E: Send + 'a,
+    F: Fn(String) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<(Vec<T>, Vec<String>), E>> + Send + 'a,
+{
+    stream::try_unfold(Some(String::new()), move |cursor| {
+        let page = cursor.map(&query_page);
+
+        async move {
+            let (items, keys) = match page {
+                Some(page) => page.await?,
+                None => return Ok(None),
+            };
+
+            let cursor = match keys.len() < STREAM_PAGE_SIZE {
+                true => None,
+                false => keys.last().cloned(),
+            };
+
+            Ok(Some((items, cursor)))
+        }
+    })
+    .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+    .try_flatten()
+    .boxed()
+}
+
+/// Split a comma-separated list, an empty string represents an empty list.
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').filter(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::operation::{OperationValue, RelationList, TextDelta, TextEdit};
+    use crate::test_utils::constants;
+
+    use super::{decode_u64, decode_value, encode_u64, encode_value};
+
+    #[rstest]
+    #[case(0)]
+    #[case(17)]
+    #[case(u64::MAX)]
+    fn u64_round_trip(#[case] value: u64) {
+        assert_eq!(decode_u64(&encode_u64(value)).unwrap(), value);
+    }
+
+    #[test]
+    fn u64_ordering() {
+        assert!(encode_u64(9) < encode_u64(10));
+        assert!(encode_u64(u64::MAX - 1) < encode_u64(u64::MAX));
+    }
+
+    #[test]
+    fn value_round_trip() {
+        let mut values: Vec<OperationValue> = constants::test_fields()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        values.push(OperationValue::RelationList(RelationList::new(vec![])));
+        values.push(OperationValue::Float(f64::MIN_POSITIVE));
+        values.push(OperationValue::String("comma, separated".into()));
+        values.push(OperationValue::Text(TextDelta::new(vec![
+            TextEdit::insert(0, "Hello"),
+            TextEdit::new(1, 2, "ä, "),
+        ])));
+
+        for value in values {
+            let decoded = decode_value(value.field_type(), &encode_value(&value)).unwrap();
+            assert_eq!(decoded, value);
+        }
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert!(decode_value("int", "twelve").is_err());
+        assert!(decode_value("relation", "abc").is_err());
+        assert!(decode_value("unknown", "").is_err());
+    }
+}
@@ -1,12 +1,19 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::collections::BTreeSet;
+
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 
 use crate::document::traits::AsDocument;
-use crate::document::{DocumentId, DocumentViewId};
+use crate::document::{DocumentId, DocumentView, DocumentViewId};
 use crate::schema::SchemaId;
 use crate::storage_provider::error::DocumentStorageError;
+use crate::storage_provider::pagination::DocumentPage;
+use crate::storage_provider::query::{evaluate_query, matches_filter, DocumentQuery, Filter};
 use crate::storage_provider::traits::OperationStore;
+use crate::storage_provider::utils::stream_from_vec;
+use crate::WithId;
 
 /// Interface for querying `Documents` from the store.
 ///
@@ -18,10 +25,14 @@ use crate::storage_provider::traits::OperationStore;
 /// outlined in these traits offers a simple API for querying documents by their id or schema. As each
 /// node implementation may approach materialising documents in different ways, it is expected that the
 /// storage API for documents will be expanded to suit their needs.
+///
+/// Materialised documents and document views can be persisted with `insert_document` and
+/// `insert_document_view`, implementations can then serve queries from these instead of reducing
+/// the operation graph every time.
 #[async_trait]
 pub trait DocumentStore: OperationStore {
     /// Associated type representing an `Entry` retrieved from storage.
-    type Document: AsDocument;
+    type Document: AsDocument + Send;
 
     /// Get a document by it's `DocumentId`.
     ///
@@ -52,4 +63,150 @@ pub trait DocumentStore: OperationStore {
         &self,
         schema_id: &SchemaId,
     ) -> Result<Vec<Self::Document>, DocumentStorageError>;
+
+    /// Stream all documents which follow the passed schema.
+    ///
+    /// Returns a stream yielding the same documents as `get_documents_by_schema`, this allows
+    /// implementations to read them from disk in chunks instead of holding all of them in memory.
+    /// Errors are yielded as items of the stream.
+    ///
+    /// The default implementation streams the result of `get_documents_by_schema`.
+    fn stream_documents_by_schema<'a>(
+        &'a self,
+        schema_id: &'a SchemaId,
+    ) -> BoxStream<'a, Result<Self::Document, DocumentStorageError>>
+    where
+        Self: Sync,
+    {
+        stream_from_vec(self.get_documents_by_schema(schema_id))
+    }
+
+    /// Insert a document into the store.
+    ///
+    /// Replaces a previously stored state of the same document. If the document is not deleted
+    /// it's current view is inserted as well, as if passed to `insert_document_view`.
+    ///
+    /// Errors when a fatal storage error occurs.
+    async fn insert_document(&self, document: &Self::Document) -> Result<(), DocumentStorageError>;
+
+    /// Insert a materialised view of a document into the store.
+    ///
+    /// Document views are immutable, inserting a view which already exists has no effect.
+    ///
+    /// Errors when a fatal storage error occurs.
+    async fn insert_document_view(
+        &self,
+        document_view: &DocumentView,
+        document_id: &DocumentId,
+        schema_id: &SchemaId,
+    ) -> Result<(), DocumentStorageError>;
+
+    /// Remove a stored document and all of it's stored views.
+    ///
+    /// Removing an unknown document has no effect. Errors when a fatal storage error occurs.
+    async fn remove_document(&self, document_id: &DocumentId) -> Result<(), DocumentStorageError>;
+
+    /// Get all views of a document which were inserted into the store.
+    ///
+    /// Returns a result containing a collection of document views in no particular order, can be
+    /// empty if no views were inserted for this document. Errors when a fatal storage error
+    /// occurs.
+    async fn get_document_views(
+        &self,
+        document_id: &DocumentId,
+    ) -> Result<Vec<DocumentView>, DocumentStorageError>;
+
+    /// Get a page of documents matching a query.
+    ///
+    /// Returns a result containing the documents of the requested page, ordered as defined in
+    /// the query, and a cursor for requesting the following page. Deleted documents are never
+    /// returned. Errors when a fatal storage error occurs.
+    ///
+    /// The default implementation applies the query with `evaluate_query` to all documents
+    /// returned from `get_documents_by_schema`.
+    async fn query_documents(
+        &self,
+        query: &DocumentQuery,
+    ) -> Result<DocumentPage<Self::Document>, DocumentStorageError> {
+        let documents = self.get_documents_by_schema(query.schema_id()).await?;
+        Ok(evaluate_query(query, documents))
+    }
+
+    /// Get all documents which currently point at the passed document with a relation or relation
+    /// list field.
+    ///
+    /// Returns a result containing a collection of documents in no particular order. Documents
+    /// which referred to the passed document in an earlier view only or which are deleted are not
+    /// included. Errors when a fatal storage error occurs.
+    ///
+    /// The default implementation looks up the referencing documents via
+    /// `get_operations_referencing_document`.
+    async fn get_documents_referencing_document(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<Self::Document>, DocumentStorageError> {
+        let filter = Filter::Contains(id.to_owned());
+
+        let document_ids: BTreeSet<DocumentId> = self
+            .get_operations_referencing_document(id)
+            .await?
+            .iter()
+            .map(|operation| WithId::<DocumentId>::id(operation).to_owned())
+            .collect();
+
+        let mut documents = Vec::new();
+        for document_id in document_ids {
+            if let Some(document) = self.get_document(&document_id).await? {
+                if references(&document, &filter) {
+                    documents.push(document);
+                }
+            }
+        }
+
+        Ok(documents)
+    }
+
+    /// Get all documents which currently point at the passed document view with a pinned relation
+    /// or pinned relation list field.
+    ///
+    /// Returns a result containing a collection of documents in no particular order. Documents
+    /// which referred to the passed view in an earlier view only or which are deleted are not
+    /// included. Errors when a fatal storage error occurs.
+    ///
+    /// The default implementation looks up the referencing documents via
+    /// `get_operations_referencing_view`.
+    async fn get_documents_referencing_view(
+        &self,
+        id: &DocumentViewId,
+    ) -> Result<Vec<Self::Document>, DocumentStorageError> {
+        let filter = Filter::ContainsView(id.to_owned());
+
+        let document_ids: BTreeSet<DocumentId> = self
+            .get_operations_referencing_view(id)
+            .await?
+            .iter()
+            .map(|operation| WithId::<DocumentId>::id(operation).to_owned())
+            .collect();
+
+        let mut documents = Vec::new();
+        for document_id in document_ids {
+            if let Some(document) = self.get_document(&document_id).await? {
+                if references(&document, &filter) {
+                    documents.push(document);
+                }
+            }
+        }
+
+        Ok(documents)
+    }
+}
+
+/// Returns true if any field of the document matches the passed relation filter.
+fn references<D: AsDocument>(document: &D, filter: &Filter) -> bool {
+    match document.fields() {
+        Some(fields) => fields
+            .iter()
+            .any(|(_, view_value)| matches_filter(view_value.value(), filter)),
+        None => false,
+    }
 }
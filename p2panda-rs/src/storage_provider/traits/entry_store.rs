@@ -9,6 +9,13 @@ use crate::hash::Hash;
 use crate::identity::PublicKey;
 use crate::operation::EncodedOperation;
 use crate::storage_provider::error::EntryStorageError;
+use crate::storage_provider::pagination::{EntryCursor, EntryPage};
+
+/// Trait representing an entry retrieved from storage which might still carry it's payload.
+pub trait EntryWithOperation {
+    /// Returns the encoded operation of this entry, `None` if it was never stored or removed.
+    fn payload(&self) -> Option<&EncodedOperation>;
+}
 
 /// Storage interface for storing and querying `Entries`.
 ///
@@ -20,7 +27,7 @@ use crate::storage_provider::error::EntryStorageError;
 #[async_trait]
 pub trait EntryStore {
     /// Associated type representing an `Entry` retrieved from storage.
-    type Entry: AsEntry + AsEncodedEntry + Clone;
+    type Entry: AsEntry + AsEncodedEntry + EntryWithOperation + Clone + Send;
 
     /// Insert an `Entry` to the store in it's encoded and decoded form. Optionally also store it's encoded
     /// operation.
@@ -37,6 +44,46 @@ pub trait EntryStore {
         encoded_operation: Option<&EncodedOperation>,
     ) -> Result<(), EntryStorageError>;
 
+    /// Remove the payload of an `Entry` while keeping the `Entry` itself.
+    ///
+    /// The entry still contains the hash and size of it's payload, logs can therefore still be
+    /// verified after payloads were removed. Removing the payload of an unknown or already
+    /// stripped `Entry` has no effect.
+    ///
+    /// Returns an error if a fatal storage error occurred.
+    async fn remove_payload(&self, hash: &Hash) -> Result<(), EntryStorageError>;
+
+    /// Remove an `Entry` together with it's payload.
+    ///
+    /// This breaks the chain of backlinks in a log and should only be used for pruning entries
+    /// which are not required for verifying the log anymore. Removing an unknown `Entry` has no
+    /// effect.
+    ///
+    /// Returns an error if a fatal storage error occurred.
+    async fn remove_entry(&self, hash: &Hash) -> Result<(), EntryStorageError>;
+
+    /// Record that a `PublicKey`'s log was pruned below the passed sequence number.
+    ///
+    /// Replaces a previously recorded sequence number of the same log.
+    ///
+    /// Returns an error if a fatal storage error occurred.
+    async fn insert_pruned_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        seq_num: &SeqNum,
+    ) -> Result<(), EntryStorageError>;
+
+    /// Get the sequence number below which a `PublicKey`'s log was pruned.
+    ///
+    /// Returns a result containing the sequence number passed to `insert_pruned_seq_num`, `None`
+    /// if the log was never pruned. Errors when a fatal storage error occurs.
+    async fn get_pruned_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+    ) -> Result<Option<SeqNum>, EntryStorageError>;
+
     /// Get an `Entry` at sequence position within a `PublicKey`'s log.
     ///
     /// Returns a result containing an `Entry` or if no `Entry` could be found at this `PublicKey`,
@@ -48,6 +95,17 @@ pub trait EntryStore {
         seq_num: &SeqNum,
     ) -> Result<Option<Self::Entry>, EntryStorageError>;
 
+    /// Get the total size of all payloads stored in the logs of a `PublicKey`, in bytes.
+    ///
+    /// Payloads removed with `remove_payload` or `remove_entry` don't count towards this size.
+    /// This is checked before every published `Entry` when a quota is configured, implementers
+    /// should keep a running total instead of reading all `Entries` of the `PublicKey`. Errors
+    /// when a fatal storage error occurs.
+    async fn get_payload_size_by_public_key(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<u64, EntryStorageError>;
+
     /// Get an `Entry` by it's `Hash`.
     ///
     /// Returns a result containing an `Entry` wrapped in an option. If no `Entry` could
@@ -65,4 +123,110 @@ pub trait EntryStore {
         public_key: &PublicKey,
         log_id: &LogId,
     ) -> Result<Option<Self::Entry>, EntryStorageError>;
+
+    /// Get up to `limit` entries of a `PublicKey`'s log, starting at `from` sequence number.
+    ///
+    /// Entries are returned ordered by their sequence number. Sequence numbers which are not
+    /// present in the store are skipped. Returns an empty vector if no entries were found. Errors
+    /// when a fatal storage error occurs.
+    async fn get_entries_from(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        from: &SeqNum,
+        limit: usize,
+    ) -> Result<Vec<Self::Entry>, EntryStorageError>;
+
+    /// Get a page of up to `limit` entries of a `PublicKey`'s log.
+    ///
+    /// Pass `None` as cursor to request the first page, every following page is requested with
+    /// the cursor returned in the previous page. The last page of a log returns no cursor.
+    /// Requesting a page with a limit of zero returns no entries and the passed cursor.
+    ///
+    /// The default implementation is built on top of `get_entries_from`.
+    async fn get_paginated_entries(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        cursor: Option<&EntryCursor>,
+        limit: usize,
+    ) -> Result<EntryPage<Self::Entry>, EntryStorageError> {
+        if limit == 0 {
+            return Ok(EntryPage {
+                entries: Vec::new(),
+                next_cursor: cursor.cloned(),
+            });
+        }
+
+        let from = match cursor {
+            Some(cursor) => match cursor.seq_num().clone().next() {
+                Some(seq_num) => seq_num,
+                // There can't be any entries following the maximum sequence number.
+                None => {
+                    return Ok(EntryPage {
+                        entries: Vec::new(),
+                        next_cursor: None,
+                    })
+                }
+            },
+            None => SeqNum::default(),
+        };
+
+        // Request one more entry than needed to find out if there is a following page.
+        let mut entries = self
+            .get_entries_from(public_key, log_id, &from, limit.saturating_add(1))
+            .await?;
+
+        let has_next_page = entries.len() > limit;
+        entries.truncate(limit);
+
+        let next_cursor = match has_next_page {
+            true => entries
+                .last()
+                .map(|entry| EntryCursor::new(entry.seq_num().to_owned())),
+            false => None,
+        };
+
+        Ok(EntryPage {
+            entries,
+            next_cursor,
+        })
+    }
+
+    /// Get the certificate pool of an `Entry` at sequence position within a `PublicKey`'s log.
+    ///
+    /// The certificate pool contains all entries required to verify the entry without knowing
+    /// the full log, these are the entries on the path to the first entry following the lipmaa
+    /// link of every entry. The entry itself is not part of it's certificate pool. Entries are
+    /// returned ordered by their sequence number, see `entry::validate::validate_certificate_pool`
+    /// for verifying an entry against it.
+    ///
+    /// Errors when an entry of the certificate pool is missing or a fatal storage error occurs.
+    async fn get_certificate_pool(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        seq_num: &SeqNum,
+    ) -> Result<Vec<Self::Entry>, EntryStorageError> {
+        let mut certificate_pool = Vec::new();
+        let mut current_seq_num = *seq_num;
+
+        while !current_seq_num.is_first() {
+            // The lipmaa entry is the target of the skiplink or, if both are the same, the backlink.
+            current_seq_num = current_seq_num
+                .skiplink_seq_num()
+                .expect("All entries except the first one have a lipmaa link");
+
+            let entry = self
+                .get_entry_at_seq_num(public_key, log_id, &current_seq_num)
+                .await?
+                .ok_or_else(|| EntryStorageError::CertPoolEntryMissing(current_seq_num.as_u64()))?;
+
+            certificate_pool.push(entry);
+        }
+
+        certificate_pool.reverse();
+
+        Ok(certificate_pool)
+    }
 }
@@ -15,12 +15,21 @@
 //! well as any historic views which need to be retained, can be seen as a caching
 //! layer on top of the persisted, immutable operations. Efficient storage and
 //! querying of documents should be considered by implementers of these traits.
+//!
+//! Writes which belong together, like storing an entry and it's operation, are grouped into
+//! atomic transactions via the [`TransactionStore`].
+//!
+//! Applications can subscribe to changes of the stored data via the [`SubscriptionStore`].
 mod document_store;
 mod entry_store;
 mod log_store;
 mod operation_store;
+mod subscription_store;
+mod transaction_store;
 
 pub use document_store::DocumentStore;
-pub use entry_store::EntryStore;
+pub use entry_store::{EntryStore, EntryWithOperation};
 pub use log_store::LogStore;
 pub use operation_store::OperationStore;
+pub use subscription_store::SubscriptionStore;
+pub use transaction_store::TransactionStore;
@@ -1,13 +1,15 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 
-use crate::document::DocumentId;
+use crate::document::{DocumentId, DocumentViewId};
 use crate::identity::PublicKey;
 use crate::operation::traits::{AsOperation, WithPublicKey};
 use crate::operation::{Operation, OperationId};
 use crate::schema::SchemaId;
 use crate::storage_provider::error::OperationStorageError;
+use crate::storage_provider::utils::stream_from_vec;
 use crate::WithId;
 
 /// Storage interface for storing and querying `Operations`.
@@ -42,6 +44,18 @@ pub trait OperationStore {
         document_id: &DocumentId,
     ) -> Result<(), OperationStorageError>;
 
+    /// Remove all `Operations` of a single `Document`.
+    ///
+    /// This is used to purge the data of deleted `Documents`, the `Entries` the `Operations`
+    /// arrived on are not touched. Removing the `Operations` of an unknown `Document` has no
+    /// effect.
+    ///
+    /// Returns an error if a fatal storage error occurred.
+    async fn remove_operations_by_document_id(
+        &self,
+        id: &DocumentId,
+    ) -> Result<(), OperationStorageError>;
+
     /// Get an `Operation` identified by it's `OperationId`, returns `None` if no `Operation` was found.
     ///
     /// Returns an error if a fatal storage error occurred.
@@ -75,4 +89,72 @@ pub trait OperationStore {
         &self,
         id: &SchemaId,
     ) -> Result<Vec<Self::Operation>, OperationStorageError>;
+
+    /// Get the number of `Documents` created with a certain `Schema`.
+    ///
+    /// `Documents` are counted by their CREATE `Operation`, removing the `Operations` of a
+    /// `Document` decreases the count. This is checked before every published CREATE `Operation`
+    /// when a quota is configured, implementers should keep a running count instead of reading
+    /// all `Operations` of the `Schema`. Errors if a fatal storage error occurred.
+    async fn count_documents_by_schema_id(
+        &self,
+        id: &SchemaId,
+    ) -> Result<usize, OperationStorageError>;
+
+    /// Stream all `Operations` for a single `Document`.
+    ///
+    /// Returns a stream yielding the same `Operations` as `get_operations_by_document_id`, this
+    /// allows implementations to read them from disk in chunks instead of holding all of them in
+    /// memory. Errors are yielded as items of the stream.
+    ///
+    /// The default implementation streams the result of `get_operations_by_document_id`.
+    fn stream_operations_by_document_id<'a>(
+        &'a self,
+        id: &'a DocumentId,
+    ) -> BoxStream<'a, Result<Self::Operation, OperationStorageError>>
+    where
+        Self: Sync,
+        Self::Operation: Send,
+    {
+        stream_from_vec(self.get_operations_by_document_id(id))
+    }
+
+    /// Stream all `Operations` for a certain `Schema`.
+    ///
+    /// Returns a stream yielding the same `Operations` as `get_operations_by_schema_id`, this
+    /// allows implementations to read them from disk in chunks instead of holding all of them in
+    /// memory. Errors are yielded as items of the stream.
+    ///
+    /// The default implementation streams the result of `get_operations_by_schema_id`.
+    fn stream_operations_by_schema_id<'a>(
+        &'a self,
+        id: &'a SchemaId,
+    ) -> BoxStream<'a, Result<Self::Operation, OperationStorageError>>
+    where
+        Self: Sync,
+        Self::Operation: Send,
+    {
+        stream_from_vec(self.get_operations_by_schema_id(id))
+    }
+
+    /// Get all `Operations` which point at a `Document` with a relation or relation list field.
+    ///
+    /// Returns a result containing a vector of `Operations` in no particular order. If no
+    /// `Operation` refers to this `Document` then an empty vector is returned. Errors if a fatal
+    /// storage error ocurred.
+    async fn get_operations_referencing_document(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<Self::Operation>, OperationStorageError>;
+
+    /// Get all `Operations` which point at a `DocumentView` with a pinned relation or pinned
+    /// relation list field.
+    ///
+    /// Returns a result containing a vector of `Operations` in no particular order. If no
+    /// `Operation` refers to this `DocumentView` then an empty vector is returned. Errors if a
+    /// fatal storage error ocurred.
+    async fn get_operations_referencing_view(
+        &self,
+        id: &DocumentViewId,
+    ) -> Result<Vec<Self::Operation>, OperationStorageError>;
 }
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use futures::stream::BoxStream;
+
+use crate::storage_provider::events::{EventFilter, StoreEvent};
+
+/// Interface for subscribing to changes of the data in a store.
+///
+/// Applications built on top of a store, like an API serving queries to clients, can react to new
+/// data arriving instead of polling the store for it. Events about published entries and
+/// operations are sent via `notify` by `publish` after they were committed, stores send events
+/// about inserted document views themselves. Events about document views inserted through a
+/// transaction are only sent once it was committed, they are discarded on rollback.
+///
+/// Subscribers are expected to keep up with the events, implementers may drop events for
+/// subscribers which don't. `EventBroadcaster` can be used to implement this trait.
+pub trait SubscriptionStore {
+    /// Returns a stream of all events matching the filter which are sent after this call.
+    ///
+    /// The subscription ends when the stream is dropped.
+    fn subscribe(&self, filter: EventFilter) -> BoxStream<'static, StoreEvent>;
+
+    /// Send an event to all subscribers whose filter matches it.
+    fn notify(&self, event: StoreEvent);
+}
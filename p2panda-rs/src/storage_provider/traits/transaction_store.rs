@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_trait::async_trait;
+
+use crate::storage_provider::error::TransactionError;
+use crate::storage_provider::traits::{DocumentStore, EntryStore, LogStore, OperationStore};
+
+/// Storage interface for grouping writes into atomic transactions.
+///
+/// Publishing an entry requires inserting a log, an entry and an operation. If only some of these
+/// writes succeed the store ends up in an inconsistent state, for example a log containing an
+/// entry without it's operation. Implementers of this trait guarantee that all writes performed
+/// through a transaction handle are persisted together when it is committed, or not at all when
+/// it is rolled back or dropped.
+///
+/// Every caller gets it's own transaction handle, writes performed directly on the store or
+/// through other handles never become part of it.
+#[async_trait]
+pub trait TransactionStore {
+    /// Handle to a transaction in progress.
+    ///
+    /// Reads through the handle see all writes performed through it before, these writes only
+    /// become visible to other users of the store after the transaction was committed.
+    type Transaction: EntryStore + LogStore + OperationStore + DocumentStore + Send + Sync;
+
+    /// Start a new transaction.
+    ///
+    /// Returns an error when called on a transaction handle, transactions can't be nested.
+    async fn begin_transaction(&self) -> Result<Self::Transaction, TransactionError>;
+
+    /// Persist all writes performed through the transaction.
+    ///
+    /// Returns an error if the passed handle is not a transaction or it was already finished.
+    async fn commit_transaction(
+        &self,
+        transaction: Self::Transaction,
+    ) -> Result<(), TransactionError>;
+
+    /// Discard all writes performed through the transaction.
+    ///
+    /// Dropping a transaction handle without committing it has the same effect.
+    async fn rollback_transaction(
+        &self,
+        transaction: Self::Transaction,
+    ) -> Result<(), TransactionError>;
+}
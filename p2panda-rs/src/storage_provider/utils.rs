@@ -1,6 +1,183 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 //! Utility methods and types for the storage provider module.
+use std::collections::HashSet;
+use std::future::Future;
+
+use futures::future;
+use futures::stream::{self, BoxStream, StreamExt};
+
+use crate::document::{DocumentId, DocumentViewId};
+use crate::operation::traits::AsOperation;
+use crate::operation::{OperationId, OperationValue};
+use crate::WithId;
 
 /// A custom `Result` type to be able to dynamically propagate `Error` types.
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+/// Returns the id of the latest view of a document from all of it's operations.
+///
+/// The latest view is made up of the graph tips, all operations which are not referred to in the
+/// `previous` field of any other operation. This is much cheaper than materialising the document
+/// and can be used to check if a stored document is still up-to-date.
+pub fn current_view_id<T: AsOperation + WithId<OperationId>>(operations: &[T]) -> DocumentViewId {
+    let previous: HashSet<OperationId> = operations
+        .iter()
+        .filter_map(|operation| operation.previous())
+        .flat_map(|previous| previous.graph_tips().to_vec())
+        .collect();
+
+    let graph_tips: Vec<OperationId> = operations
+        .iter()
+        .map(|operation| WithId::<OperationId>::id(operation).to_owned())
+        .filter(|operation_id| !previous.contains(operation_id))
+        .collect();
+
+    DocumentViewId::new(&graph_tips)
+}
+
+/// Returns the ids of all documents an operation points at with a relation or relation list field.
+pub fn referenced_documents<T: AsOperation>(operation: &T) -> Vec<DocumentId> {
+    let mut document_ids = Vec::new();
+
+    if let Some(fields) = operation.fields() {
+        for (_, value) in fields.iter() {
+            match value {
+                OperationValue::Relation(relation) => {
+                    document_ids.push(relation.document_id().to_owned())
+                }
+                OperationValue::RelationList(relations) => {
+                    document_ids.extend(relations.iter().cloned())
+                }
+                _ => (),
+            }
+        }
+    }
+
+    document_ids.sort();
+    document_ids.dedup();
+    document_ids
+}
+
+/// Returns the ids of all document views an operation points at with a pinned relation or pinned
+/// relation list field.
+pub fn referenced_document_views<T: AsOperation>(operation: &T) -> Vec<DocumentViewId> {
+    let mut view_ids = Vec::new();
+
+    if let Some(fields) = operation.fields() {
+        for (_, value) in fields.iter() {
+            match value {
+                OperationValue::PinnedRelation(relation) => {
+                    view_ids.push(relation.view_id().to_owned())
+                }
+                OperationValue::PinnedRelationList(relations) => {
+                    view_ids.extend(relations.iter().cloned())
+                }
+                _ => (),
+            }
+        }
+    }
+
+    view_ids.sort();
+    view_ids.dedup();
+    view_ids
+}
+
+/// Turns a query returning a collection into a stream of it's items.
+///
+/// The query is only run when the stream is polled for the first time. If it fails the error is
+/// the only item of the stream.
+pub fn stream_from_vec<'a, T, E, F>(query: F) -> BoxStream<'a, std::result::Result<T, E>>
+where
+    T: Send + 'a,
+    E: Send + 'a,
+    F: Future<Output = std::result::Result<Vec<T>, E>> + Send + 'a,
+{
+    stream::once(query)
+        .flat_map(|result| match result {
+            Ok(items) => stream::iter(items.into_iter().map(Ok)).left_stream(),
+            Err(err) => stream::once(future::ready(Err(err))).right_stream(),
+        })
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use futures::TryStreamExt;
+    use rstest::rstest;
+
+    use crate::document::traits::AsDocument;
+    use crate::document::Document;
+    use crate::operation::Operation;
+    use crate::storage_provider::traits::OperationStore;
+    use crate::test_utils::fixtures::{document_id, document_view_id, operation};
+    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::{
+        current_view_id, referenced_document_views, referenced_documents, stream_from_vec,
+    };
+
+    #[rstest]
+    #[case::create_only(1, false)]
+    #[case::updates(5, false)]
+    #[case::deleted(5, true)]
+    #[tokio::test]
+    async fn current_view_id_matches_materialised_document(
+        #[case] no_of_entries: usize,
+        #[case] with_delete: bool,
+    ) {
+        let store = MemoryStore::default();
+        let config = PopulateStoreConfig {
+            no_of_entries,
+            no_of_logs: 1,
+            no_of_public_keys: 1,
+            with_delete,
+            ..PopulateStoreConfig::default()
+        };
+        let (_, document_ids) = populate_store(&store, &config).await;
+
+        let operations = store
+            .get_operations_by_document_id(&document_ids[0])
+            .await
+            .unwrap();
+        let document = Document::try_from(&operations).unwrap();
+
+        assert_eq!(&current_view_id(&operations), document.view_id());
+    }
+
+    #[rstest]
+    fn finds_referenced_documents_and_views(operation: Operation) {
+        assert_eq!(
+            referenced_documents(&operation),
+            vec![
+                document_id("00209a2149589672fa1ac2348e48b4c56fc208a0eff44938464dd2091850f444a323"),
+                document_id("0020b177ec1bf26dfb3b7010d473e6d44713b29b765b99c6e60ecbfae742de496543"),
+            ]
+        );
+
+        assert_eq!(referenced_document_views(&operation).len(), 3);
+        assert!(
+            referenced_document_views(&operation).contains(&document_view_id(vec![
+                "00204f0dd3a1b8205b6d4ce3fd4c158bb91c9e131bd842e727164ea220b5b6d09346",
+            ]))
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_query_results() {
+        let items: Vec<u64> = stream_from_vec(async { Ok::<_, String>(vec![1, 2, 3]) })
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(items, vec![1, 2, 3]);
+
+        let result: Result<Vec<u64>, String> =
+            stream_from_vec(async { Err::<Vec<u64>, _>("Fatal storage error".to_string()) })
+                .try_collect()
+                .await;
+        assert_eq!(result, Err("Fatal storage error".to_string()));
+    }
+}
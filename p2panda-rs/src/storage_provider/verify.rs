@@ -0,0 +1,481 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Consistency checks for the data held by a store.
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::document::DocumentId;
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{LogId, SeqNum};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::encode::encode_operation;
+use crate::operation::traits::AsOperation;
+use crate::operation::{Operation, OperationId};
+use crate::storage_provider::error::VerifyStoreError;
+use crate::storage_provider::traits::{EntryStore, EntryWithOperation, LogStore, OperationStore};
+use crate::WithId;
+
+/// Number of entries requested from the store at once when verifying a log.
+const VERIFY_PAGE_SIZE: usize = 100;
+
+/// Inconsistency found in the data of a store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// An entry is missing in a log which should be contiguous up to it's latest entry.
+    MissingEntry {
+        /// Public key of the log's author.
+        public_key: PublicKey,
+
+        /// Log the entry is missing in.
+        log_id: LogId,
+
+        /// Sequence number of the missing entry.
+        seq_num: SeqNum,
+    },
+
+    /// The skiplink target of an entry is missing or it's hash differs from the one encoded in
+    /// the entry.
+    MissingSkiplink {
+        /// Public key of the log's author.
+        public_key: PublicKey,
+
+        /// Log of the entry.
+        log_id: LogId,
+
+        /// Sequence number of the entry whose skiplink target is missing.
+        seq_num: SeqNum,
+    },
+
+    /// The payload or the stored operation of an entry don't match the entry's payload hash.
+    PayloadHashMismatch {
+        /// Hash of the entry.
+        hash: Hash,
+    },
+
+    /// The operation of an entry which still holds it's payload is missing.
+    MissingOperation {
+        /// Hash of the entry.
+        hash: Hash,
+    },
+
+    /// An operation points at a previous operation which is not part of the same document.
+    UnresolvedPrevious {
+        /// Id of the operation pointing at the missing operation.
+        operation_id: OperationId,
+
+        /// Id of the missing operation.
+        previous: OperationId,
+    },
+
+    /// A document does not have exactly one CREATE operation.
+    InvalidCreateCount {
+        /// Id of the document.
+        document_id: DocumentId,
+
+        /// Number of CREATE operations found for the document.
+        count: usize,
+    },
+}
+
+/// Result of checking a store with `verify_store`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreReport {
+    violations: Vec<Violation>,
+}
+
+impl StoreReport {
+    /// Returns all inconsistencies found in the store.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Returns true if no inconsistencies were found.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Check the logs of the passed public keys and the documents they contributed to for
+/// inconsistencies.
+///
+/// The following properties are verified:
+///
+/// - Every log is contiguous from it's first entry up to it's latest one
+/// - The skiplink target of every entry is present
+/// - The payload and the stored operation of every entry match it's payload hash
+/// - Every entry still holding a payload has a stored operation
+/// - The `previous` operations of every operation are part of the same document
+/// - Every document has exactly one CREATE operation
+///
+/// Logs pruned with `prune_log` are only checked from their pruned sequence number on. Entries
+/// without a payload are expected after pruning or purging and are not reported.
+///
+/// Errors only when a storage error occurs, inconsistencies are returned in the report.
+pub async fn verify_store<S: EntryStore + LogStore + OperationStore + Sync>(
+    store: &S,
+    public_keys: &[PublicKey],
+) -> Result<StoreReport, VerifyStoreError> {
+    let mut violations = Vec::new();
+    let mut document_ids = BTreeSet::new();
+
+    for public_key in public_keys {
+        let latest_log_id = match store.latest_log_id(public_key).await? {
+            Some(log_id) => log_id,
+            None => continue,
+        };
+
+        for log_id in 0..=latest_log_id.as_u64() {
+            let log_id = LogId::new(log_id);
+            verify_log(
+                store,
+                public_key,
+                &log_id,
+                &mut violations,
+                &mut document_ids,
+            )
+            .await?;
+        }
+    }
+
+    for document_id in document_ids {
+        verify_document(store, &document_id, &mut violations).await?;
+    }
+
+    Ok(StoreReport { violations })
+}
+
+/// Check a single log and the operations of it's entries, collecting the ids of all documents
+/// they belong to.
+async fn verify_log<S: EntryStore + OperationStore + Sync>(
+    store: &S,
+    public_key: &PublicKey,
+    log_id: &LogId,
+    violations: &mut Vec<Violation>,
+    document_ids: &mut BTreeSet<DocumentId>,
+) -> Result<(), VerifyStoreError> {
+    let mut entries = BTreeMap::new();
+    let mut cursor = None;
+
+    loop {
+        let page = store
+            .get_paginated_entries(public_key, log_id, cursor.as_ref(), VERIFY_PAGE_SIZE)
+            .await?;
+
+        for entry in page.entries {
+            entries.insert(entry.seq_num().as_u64(), entry);
+        }
+
+        match page.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+
+    // Entries below the pruned sequence number are allowed to be missing.
+    let pruned_seq_num = store
+        .get_pruned_seq_num(public_key, log_id)
+        .await?
+        .map(|seq_num| seq_num.as_u64())
+        .unwrap_or(1);
+
+    if let Some(latest_seq_num) = entries.keys().last() {
+        for seq_num in pruned_seq_num..*latest_seq_num {
+            if !entries.contains_key(&seq_num) {
+                violations.push(Violation::MissingEntry {
+                    public_key: public_key.to_owned(),
+                    log_id: log_id.to_owned(),
+                    // Unwrap as the range never contains zero.
+                    seq_num: SeqNum::new(seq_num).unwrap(),
+                });
+            }
+        }
+    }
+
+    for entry in entries.values() {
+        if entry.seq_num().as_u64() >= pruned_seq_num {
+            if let (Some(skiplink), Some(skiplink_seq_num)) =
+                (entry.skiplink(), entry.seq_num_skiplink())
+            {
+                let is_present = matches!(
+                    entries.get(&skiplink_seq_num.as_u64()),
+                    Some(target) if &target.hash() == skiplink
+                );
+
+                if !is_present {
+                    violations.push(Violation::MissingSkiplink {
+                        public_key: public_key.to_owned(),
+                        log_id: log_id.to_owned(),
+                        seq_num: entry.seq_num().to_owned(),
+                    });
+                }
+            }
+        }
+
+        let hash = entry.hash();
+        let mut payload_matches = match entry.payload() {
+            Some(payload) => &payload.hash() == entry.payload_hash(),
+            None => true,
+        };
+
+        match store.get_operation(&hash.clone().into()).await? {
+            Some(operation) => {
+                document_ids.insert(WithId::<DocumentId>::id(&operation).to_owned());
+
+                // The stored operation is expected to encode to the original payload.
+                payload_matches &= match encode_operation(&Operation::from(&operation)) {
+                    Ok(encoded_operation) => &encoded_operation.hash() == entry.payload_hash(),
+                    Err(_) => false,
+                };
+            }
+            None => {
+                if entry.payload().is_some() {
+                    violations.push(Violation::MissingOperation { hash: hash.clone() });
+                }
+            }
+        }
+
+        if !payload_matches {
+            violations.push(Violation::PayloadHashMismatch { hash });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the operation graph of a single document.
+async fn verify_document<S: OperationStore>(
+    store: &S,
+    document_id: &DocumentId,
+    violations: &mut Vec<Violation>,
+) -> Result<(), VerifyStoreError> {
+    let operations = store.get_operations_by_document_id(document_id).await?;
+
+    let operation_ids: HashSet<&OperationId> =
+        operations.iter().map(WithId::<OperationId>::id).collect();
+
+    let count = operations
+        .iter()
+        .filter(|operation| operation.is_create())
+        .count();
+
+    if count != 1 {
+        violations.push(Violation::InvalidCreateCount {
+            document_id: document_id.to_owned(),
+            count,
+        });
+    }
+
+    for operation in &operations {
+        if let Some(previous) = operation.previous() {
+            for previous_id in previous.iter() {
+                if !operation_ids.contains(previous_id) {
+                    violations.push(Violation::UnresolvedPrevious {
+                        operation_id: WithId::<OperationId>::id(operation).to_owned(),
+                        previous: previous_id.to_owned(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::api::prune_log;
+    use crate::entry::traits::AsEncodedEntry;
+    use crate::entry::{LogId, SeqNum};
+    use crate::hash::Hash;
+    use crate::identity::PublicKey;
+    use crate::operation::OperationId;
+    use crate::storage_provider::traits::EntryStore;
+    use crate::test_utils::fixtures::populate_store_config;
+    use crate::test_utils::memory_store::helpers::{
+        populate_store, remove_entries, remove_operations, PopulateStoreConfig,
+    };
+    use crate::test_utils::memory_store::MemoryStore;
+
+    use super::{verify_store, Violation};
+
+    async fn entry_hash(store: &MemoryStore, public_key: &PublicKey, seq_num: u64) -> Hash {
+        store
+            .get_entry_at_seq_num(
+                public_key,
+                &LogId::default(),
+                &SeqNum::new(seq_num).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap()
+            .hash()
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn verifies_consistent_store(
+        #[from(populate_store_config)]
+        #[with(10, 2, 2, true)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_keys: Vec<PublicKey> = key_pairs
+            .iter()
+            .map(|key_pair| key_pair.public_key())
+            .collect();
+
+        let report = verify_store(&store, &public_keys).await.unwrap();
+        assert!(report.is_ok());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn verifies_pruned_log(
+        #[from(populate_store_config)]
+        #[with(20, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        prune_log(
+            &store,
+            &public_key,
+            &LogId::default(),
+            &SeqNum::new(15).unwrap(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        let report = verify_store(&store, &[public_key]).await.unwrap();
+        assert_eq!(report.violations(), &[]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn detects_missing_entries(
+        #[from(populate_store_config)]
+        #[with(10, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        // Entry 4 is the skiplink target of entry 8.
+        remove_entries(&store, &public_key, &[(0, 4)]);
+
+        let report = verify_store(&store, &[public_key]).await.unwrap();
+        assert_eq!(
+            report.violations(),
+            &[
+                Violation::MissingEntry {
+                    public_key,
+                    log_id: LogId::default(),
+                    seq_num: SeqNum::new(4).unwrap(),
+                },
+                Violation::MissingSkiplink {
+                    public_key,
+                    log_id: LogId::default(),
+                    seq_num: SeqNum::new(8).unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn detects_missing_operations(
+        #[from(populate_store_config)]
+        #[with(10, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+        let missing_hash = entry_hash(&store, &public_key, 5).await;
+        let next_hash = entry_hash(&store, &public_key, 6).await;
+
+        remove_operations(&store, &public_key, &[(0, 5)]);
+
+        let report = verify_store(&store, &[public_key]).await.unwrap();
+        assert_eq!(
+            report.violations(),
+            &[
+                Violation::MissingOperation {
+                    hash: missing_hash.clone(),
+                },
+                Violation::UnresolvedPrevious {
+                    operation_id: next_hash.into(),
+                    previous: missing_hash.into(),
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn detects_missing_create_operation(
+        #[from(populate_store_config)]
+        #[with(3, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+        let create_id: OperationId = entry_hash(&store, &public_key, 1).await.into();
+
+        remove_operations(&store, &public_key, &[(0, 1)]);
+
+        let report = verify_store(&store, &[public_key]).await.unwrap();
+        assert!(report
+            .violations()
+            .contains(&Violation::InvalidCreateCount {
+                document_id: documents[0].to_owned(),
+                count: 0,
+            }));
+        assert!(report
+            .violations()
+            .contains(&Violation::UnresolvedPrevious {
+                operation_id: entry_hash(&store, &public_key, 2).await.into(),
+                previous: create_id,
+            }));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn detects_payload_hash_mismatch(
+        #[from(populate_store_config)]
+        #[with(5, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+        let first_hash = entry_hash(&store, &public_key, 2).await;
+        let second_hash = entry_hash(&store, &public_key, 3).await;
+
+        // Swap the payloads of two entries.
+        {
+            let mut entries = store.entries.lock().unwrap();
+            let first_payload = entries.get(&first_hash).unwrap().payload.clone();
+            let second_payload = entries.get(&second_hash).unwrap().payload.clone();
+            entries.get_mut(&first_hash).unwrap().payload = second_payload;
+            entries.get_mut(&second_hash).unwrap().payload = first_payload;
+        }
+
+        let report = verify_store(&store, &[public_key]).await.unwrap();
+        assert_eq!(
+            report.violations(),
+            &[
+                Violation::PayloadHashMismatch { hash: first_hash },
+                Violation::PayloadHashMismatch { hash: second_hash },
+            ]
+        );
+    }
+}
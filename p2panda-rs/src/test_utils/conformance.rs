@@ -0,0 +1,959 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Conformance tests for implementations of the `storage_provider` traits.
+//!
+//! Every function checks the expected behaviour of all methods of one storage trait, including
+//! their error cases, and panics on the first deviation. They are generic over the store and
+//! expect to be called with an empty store.
+//!
+//! Use `store_conformance_tests!` to generate a test for every trait inside a test module of a
+//! custom store implementation, the passed expression is evaluated once per test to create a new
+//! store:
+//!
+//! ```ignore
+//! #[cfg(test)]
+//! mod tests {
+//!     p2panda_rs::store_conformance_tests!(MyStore::new());
+//! }
+//! ```
+use std::future::Future;
+
+use futures::{FutureExt, StreamExt, TryStreamExt};
+
+use crate::document::traits::AsDocument;
+use crate::document::{DocumentId, DocumentViewId};
+use crate::entry::decode::decode_entry;
+use crate::entry::traits::{AsEncodedEntry, AsEntry};
+use crate::entry::{EncodedEntry, LogId, SeqNum};
+use crate::identity::KeyPair;
+use crate::operation::traits::WithPublicKey;
+use crate::operation::{
+    Operation, OperationAction, OperationBuilder, OperationId, OperationValue, PinnedRelation,
+    Relation,
+};
+use crate::schema::SchemaId;
+use crate::storage_provider::error::{EntryStorageError, OperationStorageError};
+use crate::storage_provider::events::{EventFilter, StoreEvent};
+use crate::storage_provider::query::{Direction, DocumentQueryBuilder, Filter, OrderBy};
+use crate::storage_provider::traits::{
+    DocumentStore, EntryStore, EntryWithOperation, LogStore, OperationStore, SubscriptionStore,
+    TransactionStore,
+};
+use crate::test_utils::constants;
+use crate::test_utils::fixtures::{random_document_id, random_document_view_id, random_hash};
+use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+use crate::WithId;
+
+/// Generates a test for every conformance check in this module.
+///
+/// Takes an expression which creates a new, empty store implementing all storage traits. The
+/// checks run on a runtime provided by this crate, crates using the macro don't need to depend on
+/// an async runtime themselves.
+#[macro_export]
+macro_rules! store_conformance_tests {
+    ($store:expr) => {
+        #[test]
+        fn conforms_to_log_store() {
+            $crate::test_utils::conformance::block_on(async {
+                $crate::test_utils::conformance::log_store(&$store).await;
+            });
+        }
+
+        #[test]
+        fn conforms_to_entry_store() {
+            $crate::test_utils::conformance::block_on(async {
+                $crate::test_utils::conformance::entry_store(&$store).await;
+            });
+        }
+
+        #[test]
+        fn conforms_to_operation_store() {
+            $crate::test_utils::conformance::block_on(async {
+                $crate::test_utils::conformance::operation_store(&$store).await;
+            });
+        }
+
+        #[test]
+        fn conforms_to_document_store() {
+            $crate::test_utils::conformance::block_on(async {
+                $crate::test_utils::conformance::document_store(&$store).await;
+            });
+        }
+
+        #[test]
+        fn conforms_to_transaction_store() {
+            $crate::test_utils::conformance::block_on(async {
+                $crate::test_utils::conformance::transaction_store(&$store).await;
+            });
+        }
+
+        #[test]
+        fn conforms_to_subscription_store() {
+            $crate::test_utils::conformance::block_on(async {
+                $crate::test_utils::conformance::subscription_store(&$store);
+            });
+        }
+    };
+}
+
+/// Runs a conformance check to completion on a new single-threaded tokio runtime.
+///
+/// Used by `store_conformance_tests!`.
+#[doc(hidden)]
+pub fn block_on<F: Future>(check: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed building runtime for conformance tests")
+        .block_on(check)
+}
+
+/// Populate the store with one log of the default test key pair containing `no_of_entries`.
+///
+/// Returns the key pair and the id of the document the log belongs to.
+async fn populate_one_log<S>(store: &S, no_of_entries: usize) -> (KeyPair, DocumentId)
+where
+    S: EntryStore + LogStore + OperationStore + TransactionStore + SubscriptionStore,
+{
+    let config = PopulateStoreConfig {
+        no_of_entries,
+        no_of_logs: 1,
+        no_of_public_keys: 1,
+        ..PopulateStoreConfig::default()
+    };
+
+    let (mut key_pairs, mut documents) = populate_store(store, &config).await;
+    (key_pairs.remove(0), documents.remove(0))
+}
+
+/// Check inserting and querying logs.
+pub async fn log_store<S: LogStore>(store: &S) {
+    let public_key = KeyPair::new().public_key();
+    let schema_id = constants::schema().id().to_owned();
+    let first_document_id = random_document_id();
+    let second_document_id = random_document_id();
+
+    // Nothing is known about a new public key.
+    assert_eq!(store.latest_log_id(&public_key).await.unwrap(), None);
+    assert_eq!(
+        store
+            .get_log_id(&public_key, &first_document_id)
+            .await
+            .unwrap(),
+        None
+    );
+
+    assert!(store
+        .insert_log(&LogId::new(0), &public_key, &schema_id, &first_document_id)
+        .await
+        .unwrap());
+    assert!(store
+        .insert_log(&LogId::new(1), &public_key, &schema_id, &second_document_id)
+        .await
+        .unwrap());
+
+    assert_eq!(
+        store
+            .get_log_id(&public_key, &first_document_id)
+            .await
+            .unwrap(),
+        Some(LogId::new(0))
+    );
+    assert_eq!(
+        store
+            .get_log_id(&public_key, &second_document_id)
+            .await
+            .unwrap(),
+        Some(LogId::new(1))
+    );
+    assert_eq!(
+        store.latest_log_id(&public_key).await.unwrap(),
+        Some(LogId::new(1))
+    );
+
+    // Existing logs are not overwritten.
+    assert!(store
+        .insert_log(
+            &LogId::new(0),
+            &public_key,
+            &schema_id,
+            &random_document_id()
+        )
+        .await
+        .is_err());
+    assert_eq!(
+        store
+            .get_log_id(&public_key, &first_document_id)
+            .await
+            .unwrap(),
+        Some(LogId::new(0))
+    );
+
+    // Logs are kept apart per public key.
+    let other_public_key = KeyPair::new().public_key();
+    assert_eq!(store.latest_log_id(&other_public_key).await.unwrap(), None);
+    assert_eq!(
+        store
+            .get_log_id(&other_public_key, &first_document_id)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+/// Check inserting, querying and removing entries and their payloads.
+pub async fn entry_store<S>(store: &S)
+where
+    S: EntryStore + LogStore + OperationStore + TransactionStore + SubscriptionStore + Sync,
+{
+    let (key_pair, _) = populate_one_log(store, 13).await;
+    let public_key = key_pair.public_key();
+    let log_id = LogId::default();
+
+    let latest_entry = store
+        .get_latest_entry(&public_key, &log_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(latest_entry.seq_num(), &SeqNum::new(13).unwrap());
+    assert!(store
+        .get_latest_entry(&public_key, &LogId::new(1))
+        .await
+        .unwrap()
+        .is_none());
+
+    // Entries can be found by their position in the log and by their hash.
+    for seq_num in 1..=13 {
+        let seq_num = SeqNum::new(seq_num).unwrap();
+        let entry = store
+            .get_entry_at_seq_num(&public_key, &log_id, &seq_num)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.seq_num(), &seq_num);
+        assert_eq!(entry.public_key(), &public_key);
+        assert!(entry.payload().is_some());
+
+        let entry_by_hash = store.get_entry(&entry.hash()).await.unwrap().unwrap();
+        assert_eq!(entry_by_hash.seq_num(), &seq_num);
+    }
+    assert!(store
+        .get_entry_at_seq_num(&public_key, &log_id, &SeqNum::new(14).unwrap())
+        .await
+        .unwrap()
+        .is_none());
+    assert!(store.get_entry(&random_hash()).await.unwrap().is_none());
+
+    // Existing entries are not overwritten.
+    let first_entry = store
+        .get_entry_at_seq_num(&public_key, &log_id, &SeqNum::default())
+        .await
+        .unwrap()
+        .unwrap();
+    let encoded_entry = EncodedEntry::from_bytes(&first_entry.into_bytes());
+    let entry = decode_entry(&encoded_entry).unwrap();
+    assert!(store
+        .insert_entry(&entry, &encoded_entry, None)
+        .await
+        .is_err());
+    assert!(store
+        .get_entry(&first_entry.hash())
+        .await
+        .unwrap()
+        .unwrap()
+        .payload()
+        .is_some());
+
+    // The running total of payload sizes covers all entries of the public key.
+    let payload_size: u64 = store
+        .get_entries_from(&public_key, &log_id, &SeqNum::default(), 13)
+        .await
+        .unwrap()
+        .iter()
+        .map(|entry| entry.payload_size())
+        .sum();
+    assert_eq!(
+        store
+            .get_payload_size_by_public_key(&public_key)
+            .await
+            .unwrap(),
+        payload_size
+    );
+    assert_eq!(
+        store
+            .get_payload_size_by_public_key(&KeyPair::new().public_key())
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Ranges of entries are returned ordered by sequence number.
+    for (from, limit, expected_seq_nums) in [
+        (1, 3, vec![1, 2, 3]),
+        (9, 3, vec![9, 10, 11]),
+        (12, 10, vec![12, 13]),
+        (14, 10, vec![]),
+        (1, 0, vec![]),
+    ] {
+        let seq_nums: Vec<u64> = store
+            .get_entries_from(&public_key, &log_id, &SeqNum::new(from).unwrap(), limit)
+            .await
+            .unwrap()
+            .iter()
+            .map(|entry| entry.seq_num().as_u64())
+            .collect();
+        assert_eq!(seq_nums, expected_seq_nums);
+    }
+
+    // Pages of a log follow each other until the last page which has no cursor.
+    let mut cursor = None;
+    let mut page_sizes = Vec::new();
+    loop {
+        let page = store
+            .get_paginated_entries(&public_key, &log_id, cursor.as_ref(), 5)
+            .await
+            .unwrap();
+        page_sizes.push(page.entries.len());
+
+        match page.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+    assert_eq!(page_sizes, vec![5, 5, 3]);
+
+    // The largest possible limit returns the whole log in one page.
+    let page = store
+        .get_paginated_entries(&public_key, &log_id, None, usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(page.entries.len(), 13);
+    assert!(page.next_cursor.is_none());
+
+    let certificate_pool: Vec<u64> = store
+        .get_certificate_pool(&public_key, &log_id, &SeqNum::new(13).unwrap())
+        .await
+        .unwrap()
+        .iter()
+        .map(|entry| entry.seq_num().as_u64())
+        .collect();
+    assert_eq!(certificate_pool, vec![1, 4]);
+
+    // Removing a payload keeps the entry.
+    let fifth_entry = store
+        .get_entry_at_seq_num(&public_key, &log_id, &SeqNum::new(5).unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    store.remove_payload(&fifth_entry.hash()).await.unwrap();
+    let fifth_entry = store.get_entry(&fifth_entry.hash()).await.unwrap().unwrap();
+    assert!(fifth_entry.payload().is_none());
+    store.remove_payload(&random_hash()).await.unwrap();
+    store.remove_payload(&fifth_entry.hash()).await.unwrap();
+    let payload_size = payload_size - fifth_entry.payload_size();
+    assert_eq!(
+        store
+            .get_payload_size_by_public_key(&public_key)
+            .await
+            .unwrap(),
+        payload_size
+    );
+
+    // Removing an entry required by a certificate pool makes it fail.
+    let fourth_entry = store
+        .get_entry_at_seq_num(&public_key, &log_id, &SeqNum::new(4).unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    store.remove_entry(&fourth_entry.hash()).await.unwrap();
+    assert!(store
+        .get_entry(&fourth_entry.hash())
+        .await
+        .unwrap()
+        .is_none());
+    assert!(matches!(
+        store
+            .get_certificate_pool(&public_key, &log_id, &SeqNum::new(13).unwrap())
+            .await,
+        Err(EntryStorageError::CertPoolEntryMissing(4))
+    ));
+    store.remove_entry(&random_hash()).await.unwrap();
+    store.remove_entry(&fifth_entry.hash()).await.unwrap();
+    assert_eq!(
+        store
+            .get_payload_size_by_public_key(&public_key)
+            .await
+            .unwrap(),
+        payload_size - fourth_entry.payload_size()
+    );
+
+    // Removed entries are skipped in ranges.
+    let seq_nums: Vec<u64> = store
+        .get_entries_from(&public_key, &log_id, &SeqNum::new(3).unwrap(), 3)
+        .await
+        .unwrap()
+        .iter()
+        .map(|entry| entry.seq_num().as_u64())
+        .collect();
+    assert_eq!(seq_nums, vec![3, 6, 7]);
+
+    // The recorded pruned sequence number is replaced on every insert.
+    assert_eq!(
+        store
+            .get_pruned_seq_num(&public_key, &log_id)
+            .await
+            .unwrap(),
+        None
+    );
+    store
+        .insert_pruned_seq_num(&public_key, &log_id, &SeqNum::new(5).unwrap())
+        .await
+        .unwrap();
+    store
+        .insert_pruned_seq_num(&public_key, &log_id, &SeqNum::new(3).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .get_pruned_seq_num(&public_key, &log_id)
+            .await
+            .unwrap(),
+        Some(SeqNum::new(3).unwrap())
+    );
+    assert_eq!(
+        store
+            .get_pruned_seq_num(&public_key, &LogId::new(1))
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+/// Check inserting, querying and removing operations.
+pub async fn operation_store<S>(store: &S)
+where
+    S: OperationStore + Sync,
+    S::Operation: Send,
+{
+    let public_key = KeyPair::new().public_key();
+    let schema_id = constants::schema().id().to_owned();
+    let create_id: OperationId = random_hash().into();
+    let document_id = DocumentId::new(&create_id);
+    let referenced_document_id = random_document_id();
+    let referenced_view_id = random_document_view_id();
+
+    let create = OperationBuilder::new(&schema_id)
+        .fields(&[
+            (
+                "relation",
+                OperationValue::Relation(Relation::new(referenced_document_id.clone())),
+            ),
+            (
+                "pinned_relation",
+                OperationValue::PinnedRelation(PinnedRelation::new(referenced_view_id.clone())),
+            ),
+        ])
+        .build()
+        .unwrap();
+
+    let update_id: OperationId = random_hash().into();
+    let update = OperationBuilder::new(&schema_id)
+        .action(OperationAction::Update)
+        .previous(&DocumentViewId::new(std::slice::from_ref(&create_id)))
+        .fields(&[(
+            "relation",
+            OperationValue::Relation(Relation::new(random_document_id())),
+        )])
+        .build()
+        .unwrap();
+
+    store
+        .insert_operation(&create_id, &public_key, &create, &document_id)
+        .await
+        .unwrap();
+    store
+        .insert_operation(&update_id, &public_key, &update, &document_id)
+        .await
+        .unwrap();
+
+    // Inserting an operation twice fails.
+    assert!(matches!(
+        store
+            .insert_operation(&create_id, &public_key, &create, &document_id)
+            .await,
+        Err(OperationStorageError::InsertionError(id)) if id == create_id
+    ));
+
+    let stored_create = store.get_operation(&create_id).await.unwrap().unwrap();
+    assert_eq!(WithId::<OperationId>::id(&stored_create), &create_id);
+    assert_eq!(WithId::<DocumentId>::id(&stored_create), &document_id);
+    assert_eq!(stored_create.public_key(), &public_key);
+    assert_eq!(Operation::from(&stored_create), create);
+
+    let stored_update = store.get_operation(&update_id).await.unwrap().unwrap();
+    assert_eq!(Operation::from(&stored_update), update);
+    assert!(store
+        .get_operation(&random_hash().into())
+        .await
+        .unwrap()
+        .is_none());
+
+    assert_eq!(
+        store
+            .get_document_id_by_operation_id(&update_id)
+            .await
+            .unwrap(),
+        Some(document_id.clone())
+    );
+    assert_eq!(
+        store
+            .get_document_id_by_operation_id(&random_hash().into())
+            .await
+            .unwrap(),
+        None
+    );
+
+    assert_eq!(
+        store
+            .get_operations_by_document_id(&document_id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(store
+        .get_operations_by_document_id(&random_document_id())
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        store
+            .stream_operations_by_document_id(&document_id)
+            .try_collect::<Vec<S::Operation>>()
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    assert_eq!(
+        store
+            .get_operations_by_schema_id(&schema_id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(store
+        .get_operations_by_schema_id(&SchemaId::SchemaDefinition(1))
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        store
+            .stream_operations_by_schema_id(&schema_id)
+            .try_collect::<Vec<S::Operation>>()
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // Only the CREATE operation refers to the referenced document and view.
+    let referencing: Vec<OperationId> = store
+        .get_operations_referencing_document(&referenced_document_id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|operation| WithId::<OperationId>::id(operation).to_owned())
+        .collect();
+    assert_eq!(referencing, vec![create_id.clone()]);
+
+    let referencing: Vec<OperationId> = store
+        .get_operations_referencing_view(&referenced_view_id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|operation| WithId::<OperationId>::id(operation).to_owned())
+        .collect();
+    assert_eq!(referencing, vec![create_id.clone()]);
+    assert!(store
+        .get_operations_referencing_view(&random_document_view_id())
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Documents are counted by their CREATE operation.
+    assert_eq!(
+        store
+            .count_documents_by_schema_id(&schema_id)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        store
+            .count_documents_by_schema_id(&SchemaId::SchemaDefinition(1))
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Removing the operations of a document leaves no trace of them.
+    store
+        .remove_operations_by_document_id(&document_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .count_documents_by_schema_id(&schema_id)
+            .await
+            .unwrap(),
+        0
+    );
+    assert!(store.get_operation(&create_id).await.unwrap().is_none());
+    assert!(store
+        .get_operations_by_document_id(&document_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(store
+        .get_operations_referencing_document(&referenced_document_id)
+        .await
+        .unwrap()
+        .is_empty());
+    store
+        .remove_operations_by_document_id(&random_document_id())
+        .await
+        .unwrap();
+}
+
+/// Check querying documents and inserting and removing their materialised views.
+pub async fn document_store<S>(store: &S)
+where
+    S: DocumentStore
+        + EntryStore
+        + LogStore
+        + OperationStore
+        + TransactionStore
+        + SubscriptionStore
+        + Sync,
+{
+    let (key_pair, document_id) = populate_one_log(store, 3).await;
+    let public_key = key_pair.public_key();
+    let schema_id = constants::schema().id().to_owned();
+
+    let mut view_ids = Vec::new();
+    for seq_num in 1..=3 {
+        let entry = store
+            .get_entry_at_seq_num(
+                &public_key,
+                &LogId::default(),
+                &SeqNum::new(seq_num).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        view_ids.push(DocumentViewId::new(&[entry.hash().into()]));
+    }
+
+    // Documents are materialised at their latest view.
+    let document = store.get_document(&document_id).await.unwrap().unwrap();
+    assert_eq!(document.id(), &document_id);
+    assert_eq!(document.view_id(), &view_ids[2]);
+    assert_eq!(document.schema_id(), &schema_id);
+    assert!(store
+        .get_document(&random_document_id())
+        .await
+        .unwrap()
+        .is_none());
+
+    // Every view of a document can be requested.
+    for view_id in &view_ids {
+        let document = store
+            .get_document_by_view_id(view_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(document.id(), &document_id);
+        assert_eq!(document.view_id(), view_id);
+    }
+    assert!(store
+        .get_document_by_view_id(&random_document_view_id())
+        .await
+        .unwrap()
+        .is_none());
+
+    assert_eq!(
+        store
+            .get_documents_by_schema(&schema_id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(store
+        .get_documents_by_schema(&SchemaId::SchemaDefinition(1))
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        store
+            .stream_documents_by_schema(&schema_id)
+            .try_collect::<Vec<S::Document>>()
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Views are inserted once, inserting a document inserts it's current view.
+    assert!(store
+        .get_document_views(&document_id)
+        .await
+        .unwrap()
+        .is_empty());
+    let first_document = store
+        .get_document_by_view_id(&view_ids[0])
+        .await
+        .unwrap()
+        .unwrap();
+    for _ in 0..2 {
+        store
+            .insert_document_view(&first_document.view().unwrap(), &document_id, &schema_id)
+            .await
+            .unwrap();
+    }
+    store.insert_document(&document).await.unwrap();
+
+    let mut stored_view_ids: Vec<DocumentViewId> = store
+        .get_document_views(&document_id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|view| view.id().to_owned())
+        .collect();
+    stored_view_ids.sort();
+    let mut expected_view_ids = vec![view_ids[0].clone(), view_ids[2].clone()];
+    expected_view_ids.sort();
+    assert_eq!(stored_view_ids, expected_view_ids);
+
+    // The stored view contains the materialised fields.
+    let stored_document = store.get_document(&document_id).await.unwrap().unwrap();
+    assert_eq!(stored_document.fields(), document.fields());
+
+    // Removing a document removes all of it's views.
+    store.remove_document(&document_id).await.unwrap();
+    assert!(store
+        .get_document_views(&document_id)
+        .await
+        .unwrap()
+        .is_empty());
+    store.remove_document(&random_document_id()).await.unwrap();
+
+    // Create a second document pointing at a target document and view.
+    let target_document_id = random_document_id();
+    let target_view_id = random_document_view_id();
+    let mut fields = constants::test_fields();
+    for (name, value) in fields.iter_mut() {
+        match *name {
+            "age" => *value = OperationValue::Integer(99),
+            "profile_picture" => {
+                *value = OperationValue::Relation(Relation::new(target_document_id.clone()))
+            }
+            "past_event" => {
+                *value = OperationValue::PinnedRelation(PinnedRelation::new(target_view_id.clone()))
+            }
+            _ => (),
+        }
+    }
+    let create_id: OperationId = random_hash().into();
+    let referencing_document_id = DocumentId::new(&create_id);
+    let create = OperationBuilder::new(&schema_id)
+        .fields(&fields)
+        .build()
+        .unwrap();
+    store
+        .insert_operation(&create_id, &public_key, &create, &referencing_document_id)
+        .await
+        .unwrap();
+
+    // Queries return the matching documents in their requested order, page by page.
+    let schema = constants::schema();
+    let query = DocumentQueryBuilder::new(&schema)
+        .filter("age", Filter::GreaterThan(OperationValue::Integer(28)))
+        .build()
+        .unwrap();
+    let page = store.query_documents(&query).await.unwrap();
+    assert_eq!(page.documents.len(), 1);
+    assert_eq!(page.documents[0].id(), &referencing_document_id);
+
+    let query = DocumentQueryBuilder::new(&schema)
+        .order_by(OrderBy::Field("age".into()), Direction::Descending)
+        .limit(1)
+        .build()
+        .unwrap();
+    let page = store.query_documents(&query).await.unwrap();
+    assert_eq!(page.documents.len(), 1);
+    assert_eq!(page.documents[0].id(), &referencing_document_id);
+
+    let query = DocumentQueryBuilder::new(&schema)
+        .order_by(OrderBy::Field("age".into()), Direction::Descending)
+        .limit(1)
+        .after(&page.next_cursor.unwrap())
+        .build()
+        .unwrap();
+    let page = store.query_documents(&query).await.unwrap();
+    assert_eq!(page.documents.len(), 1);
+    assert_eq!(page.documents[0].id(), &document_id);
+    assert!(page.next_cursor.is_none());
+
+    // Documents are found by the documents and views their relations point at.
+    let referencing: Vec<DocumentId> = store
+        .get_documents_referencing_document(&target_document_id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|document| document.id().to_owned())
+        .collect();
+    assert_eq!(referencing, vec![referencing_document_id.clone()]);
+    assert!(store
+        .get_documents_referencing_document(&random_document_id())
+        .await
+        .unwrap()
+        .is_empty());
+
+    let referencing: Vec<DocumentId> = store
+        .get_documents_referencing_view(&target_view_id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|document| document.id().to_owned())
+        .collect();
+    assert_eq!(referencing, vec![referencing_document_id.clone()]);
+    assert!(store
+        .get_documents_referencing_view(&random_document_view_id())
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Documents which don't point at the target anymore are not returned.
+    let update_id: OperationId = random_hash().into();
+    let update = OperationBuilder::new(&schema_id)
+        .action(OperationAction::Update)
+        .previous(&DocumentViewId::new(std::slice::from_ref(&create_id)))
+        .fields(&[(
+            "profile_picture",
+            OperationValue::Relation(Relation::new(random_document_id())),
+        )])
+        .build()
+        .unwrap();
+    store
+        .insert_operation(&update_id, &public_key, &update, &referencing_document_id)
+        .await
+        .unwrap();
+    assert!(store
+        .get_documents_referencing_document(&target_document_id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+/// Check that transactions group writes and discard them when rolled back or dropped.
+pub async fn transaction_store<S: TransactionStore + LogStore>(store: &S) {
+    let public_key = KeyPair::new().public_key();
+    let schema_id = constants::schema().id().to_owned();
+    let committed_document_id = random_document_id();
+    let rolled_back_document_id = random_document_id();
+    let dropped_document_id = random_document_id();
+
+    // Writes are visible through the transaction handle and persisted on commit.
+    let transaction = store.begin_transaction().await.unwrap();
+    transaction
+        .insert_log(
+            &LogId::new(0),
+            &public_key,
+            &schema_id,
+            &committed_document_id,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        transaction
+            .get_log_id(&public_key, &committed_document_id)
+            .await
+            .unwrap(),
+        Some(LogId::new(0))
+    );
+    store.commit_transaction(transaction).await.unwrap();
+
+    // Writes are discarded on rollback.
+    let transaction = store.begin_transaction().await.unwrap();
+    transaction
+        .insert_log(
+            &LogId::new(1),
+            &public_key,
+            &schema_id,
+            &rolled_back_document_id,
+        )
+        .await
+        .unwrap();
+    store.rollback_transaction(transaction).await.unwrap();
+
+    // Writes are discarded when the handle is dropped without committing it.
+    let transaction = store.begin_transaction().await.unwrap();
+    transaction
+        .insert_log(
+            &LogId::new(1),
+            &public_key,
+            &schema_id,
+            &dropped_document_id,
+        )
+        .await
+        .unwrap();
+    drop(transaction);
+
+    assert_eq!(
+        store
+            .get_log_id(&public_key, &committed_document_id)
+            .await
+            .unwrap(),
+        Some(LogId::new(0))
+    );
+    assert_eq!(
+        store
+            .get_log_id(&public_key, &rolled_back_document_id)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        store
+            .get_log_id(&public_key, &dropped_document_id)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+/// Check that events are delivered to subscribers with a matching filter.
+pub fn subscription_store<S: SubscriptionStore>(store: &S) {
+    let document_id = random_document_id();
+    let event = StoreEvent::DocumentViewUpdated {
+        view_id: random_document_view_id(),
+        schema_id: constants::schema().id().to_owned(),
+        document_id: document_id.clone(),
+    };
+
+    // Events sent before subscribing are not delivered.
+    store.notify(event.clone());
+
+    let mut matching = store.subscribe(EventFilter::new().document_id(&document_id));
+    let mut other = store.subscribe(EventFilter::new().document_id(&random_document_id()));
+
+    store.notify(event.clone());
+
+    assert_eq!(matching.next().now_or_never(), Some(Some(event)));
+    assert!(matching.next().now_or_never().is_none());
+    assert!(other.next().now_or_never().is_none());
+}
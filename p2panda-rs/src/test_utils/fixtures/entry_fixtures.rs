@@ -2,18 +2,25 @@
 
 use std::convert::TryInto;
 
-use bamboo_rs_core_ed25519_yasmf::entry::MAX_ENTRY_SIZE;
+use bamboo_rs_core_ed25519_yasmf::entry::{is_lipmaa_required, MAX_ENTRY_SIZE};
 use bamboo_rs_core_ed25519_yasmf::{Signature as BambooSignature, YasmfHash};
 use lipmaa_link::is_skip_link;
 use rstest::fixture;
 use varu64::encode as varu64_encode;
 
-use crate::entry::encode::{encode_entry, sign_entry};
-use crate::entry::{EncodedEntry, Entry};
+use crate::document::DocumentViewId;
+use crate::entry::encode::{encode_entry, sign_and_encode_entry, sign_entry};
+use crate::entry::traits::AsEncodedEntry;
+use crate::entry::{EncodedEntry, Entry, LogId, SeqNum};
 use crate::hash::{Blake3ArrayVec, Hash};
 use crate::identity::KeyPair;
+use crate::operation::encode::encode_operation;
 use crate::operation::EncodedOperation;
-use crate::test_utils::fixtures::{encoded_operation, key_pair, random_hash};
+use crate::schema::Schema;
+use crate::test_utils::constants::test_fields;
+use crate::test_utils::fixtures::{
+    create_operation, encoded_operation, key_pair, random_hash, update_operation,
+};
 
 /// Creates an `Entry`.
 ///
@@ -174,3 +181,65 @@ pub fn entry_signed_encoded_unvalidated(
 
     EncodedEntry::from_bytes(&entry_bytes[..next_byte_num])
 }
+
+/// Helper method for signing and encoding the next entry of a log, containing a CREATE operation
+/// or an UPDATE operation when `previous` is set.
+///
+/// `log` holds the hashes of all entries already in the log, ordered by their sequence number.
+/// The sequence number, backlink and skiplink of the new entry are derived from it.
+pub fn log_entry(
+    schema: &Schema,
+    key_pair: &KeyPair,
+    log: &[Hash],
+    previous: Option<&Hash>,
+) -> (EncodedEntry, EncodedOperation) {
+    let operation = match previous {
+        Some(previous) => update_operation(
+            test_fields(),
+            DocumentViewId::from(previous.to_owned()),
+            schema.id().to_owned(),
+        ),
+        None => create_operation(test_fields(), schema.id().to_owned()),
+    };
+
+    let seq_num = SeqNum::new(log.len() as u64 + 1).unwrap();
+    let backlink = log.last();
+    let skiplink = match is_lipmaa_required(seq_num.as_u64()) {
+        true => seq_num
+            .skiplink_seq_num()
+            .map(|skiplink_seq_num| &log[skiplink_seq_num.as_u64() as usize - 1]),
+        false => None,
+    };
+
+    let encoded_operation = encode_operation(&operation).unwrap();
+    let encoded_entry = sign_and_encode_entry(
+        &LogId::default(),
+        &seq_num,
+        skiplink,
+        backlink,
+        &encoded_operation,
+        key_pair,
+    )
+    .unwrap();
+
+    (encoded_entry, encoded_operation)
+}
+
+/// Helper method for signing and encoding a log of `num` entries, containing a CREATE operation
+/// followed by UPDATE operations which each point at the operation of the entry before.
+pub fn log_entries(
+    schema: &Schema,
+    key_pair: &KeyPair,
+    num: usize,
+) -> Vec<(EncodedEntry, EncodedOperation)> {
+    let mut log: Vec<Hash> = Vec::new();
+    let mut entries = Vec::new();
+
+    for _ in 0..num {
+        let entry = log_entry(schema, key_pair, &log, log.last());
+        log.push(entry.0.hash());
+        entries.push(entry);
+    }
+
+    entries
+}
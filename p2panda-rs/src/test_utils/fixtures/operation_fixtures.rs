@@ -6,7 +6,7 @@ use crate::document::{DocumentId, DocumentViewId};
 use crate::entry::encode::{encode_entry, sign_entry};
 use crate::entry::{Entry, LogId, SeqNum};
 use crate::hash::Hash;
-use crate::identity::KeyPair;
+use crate::identity::{KeyPair, PublicKey};
 use crate::operation::encode::{encode_operation, encode_plain_operation};
 use crate::operation::plain::PlainOperation;
 use crate::operation::traits::AsOperation;
@@ -259,3 +259,38 @@ pub fn delete_operation(
 ) -> Operation {
     operation(None, Some(previous), schema_id)
 }
+
+/// Indexes of the operations in `previous` and the written fields of every operation in a
+/// document.
+pub type OperationGraph<'a> = Vec<(Vec<usize>, Vec<(&'a str, OperationValue)>)>;
+
+/// Helper method for easily constructing the operations of a document following the passed graph.
+///
+/// Operations without `previous` are CREATE operations, all others are UPDATE operations. All
+/// operations are published by the same random public key. The operation ids are random, so
+/// concurrent branches are sorted differently every time.
+pub fn graph_operations(
+    schema_id: &SchemaId,
+    graph: OperationGraph,
+) -> Vec<(OperationId, Operation, PublicKey)> {
+    let public_key = KeyPair::new().public_key();
+    let mut operations: Vec<(OperationId, Operation, PublicKey)> = Vec::new();
+
+    for (previous, fields) in graph {
+        let operation = match previous.is_empty() {
+            true => create_operation(fields, schema_id.to_owned()),
+            false => {
+                let previous: Vec<OperationId> = previous
+                    .iter()
+                    .map(|index| operations[*index].0.clone())
+                    .collect();
+
+                update_operation(fields, DocumentViewId::new(&previous), schema_id.to_owned())
+            }
+        };
+
+        operations.push((random_operation_id(), operation, public_key));
+    }
+
+    operations
+}
@@ -41,6 +41,7 @@ pub fn schema_field_type(
         OperationValue::RelationList(_) => FieldType::RelationList(schema_id),
         OperationValue::PinnedRelation(_) => FieldType::PinnedRelation(schema_id),
         OperationValue::PinnedRelationList(_) => FieldType::PinnedRelationList(schema_id),
+        OperationValue::Text(_) => FieldType::Text,
     }
 }
 
@@ -13,7 +13,9 @@ use crate::operation::encode::encode_operation;
 use crate::operation::traits::Actionable;
 use crate::operation::{Operation, OperationAction, OperationBuilder, OperationValue};
 use crate::schema::Schema;
-use crate::storage_provider::traits::{EntryStore, LogStore, OperationStore};
+use crate::storage_provider::traits::{
+    EntryStore, LogStore, OperationStore, SubscriptionStore, TransactionStore,
+};
 use crate::storage_provider::utils::Result;
 use crate::test_utils::constants;
 
@@ -91,7 +93,9 @@ pub fn many_key_pairs(no_of_public_keys: usize) -> Vec<KeyPair> {
 /// Passed parameters define what the store should contain. The first entry in each log contains a
 /// valid CREATE operation following entries contain UPDATE operations. If the with_delete flag is set
 /// to true the last entry in all logs contain be a DELETE operation.
-pub async fn populate_store<S: EntryStore + LogStore + OperationStore>(
+pub async fn populate_store<
+    S: EntryStore + LogStore + OperationStore + TransactionStore + SubscriptionStore,
+>(
     store: &S,
     config: &PopulateStoreConfig,
 ) -> (Vec<KeyPair>, Vec<DocumentId>) {
@@ -147,7 +151,9 @@ pub async fn populate_store<S: EntryStore + LogStore + OperationStore>(
 }
 
 /// Helper method for publishing an operation encoded on an entry to a store.
-pub async fn send_to_store<S: EntryStore + LogStore + OperationStore>(
+pub async fn send_to_store<
+    S: EntryStore + LogStore + OperationStore + TransactionStore + SubscriptionStore,
+>(
     store: &S,
     operation: &Operation,
     schema: &Schema,
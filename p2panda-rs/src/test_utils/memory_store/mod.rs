@@ -11,3 +11,10 @@ mod types;
 
 pub use provider::MemoryStore;
 pub use types::{PublishedOperation, StorageEntry};
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryStore;
+
+    crate::store_conformance_tests!(MemoryStore::default());
+}
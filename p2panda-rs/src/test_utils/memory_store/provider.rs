@@ -3,16 +3,19 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-use crate::document::DocumentId;
-use crate::entry::LogId;
+use crate::document::{Document, DocumentId, DocumentView, DocumentViewId};
+use crate::entry::{LogId, SeqNum};
 use crate::hash::Hash;
 use crate::identity::PublicKey;
 use crate::operation::OperationId;
 use crate::schema::SchemaId;
+use crate::storage_provider::events::EventBroadcaster;
+use crate::test_utils::memory_store::stores::{MemoryTransaction, Write};
 use crate::test_utils::memory_store::{PublishedOperation, StorageEntry};
 
 type PublickeyLogId = String;
 type Log = (PublicKey, LogId, SchemaId, DocumentId);
+type StoredDocumentView = (DocumentId, SchemaId, DocumentView);
 
 /// An in-memory implementation of p2panda storage traits.
 ///
@@ -25,6 +28,97 @@ pub struct MemoryStore {
     /// Stored entries
     pub entries: Arc<Mutex<HashMap<Hash, StorageEntry>>>,
 
+    /// Sequence numbers below which logs were pruned
+    pub pruned_seq_nums: Arc<Mutex<HashMap<PublickeyLogId, SeqNum>>>,
+
     /// Stored operations
     pub operations: Arc<Mutex<HashMap<OperationId, PublishedOperation>>>,
+
+    /// Stored materialised documents
+    pub documents: Arc<Mutex<HashMap<DocumentId, Document>>>,
+
+    /// Stored materialised document views
+    pub document_views: Arc<Mutex<HashMap<DocumentViewId, StoredDocumentView>>>,
+
+    /// Index of operations pointing at a document with a relation or relation list field
+    pub document_relations: Arc<Mutex<HashMap<DocumentId, Vec<OperationId>>>>,
+
+    /// Index of operations pointing at a document view with a pinned relation or pinned relation
+    /// list field
+    pub view_relations: Arc<Mutex<HashMap<DocumentViewId, Vec<OperationId>>>>,
+
+    /// Running total of the stored payload bytes of every public key
+    pub payload_sizes: Arc<Mutex<HashMap<PublicKey, u64>>>,
+
+    /// Running total of the documents created with every schema
+    pub document_counts: Arc<Mutex<HashMap<SchemaId, usize>>>,
+
+    /// Subscribers to changes of the stored data
+    pub events: EventBroadcaster,
+
+    /// Transaction this store is a handle to, `None` for the store itself
+    pub(crate) transaction: Option<Arc<MemoryTransaction>>,
+
+    /// Lock held while the writes of a transaction are applied
+    pub(crate) commit_lock: Arc<futures::lock::Mutex<()>>,
+}
+
+impl MemoryStore {
+    /// Returns a copy of all stored values.
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            logs: self.logs.lock().unwrap().clone(),
+            entries: self.entries.lock().unwrap().clone(),
+            pruned_seq_nums: self.pruned_seq_nums.lock().unwrap().clone(),
+            operations: self.operations.lock().unwrap().clone(),
+            documents: self.documents.lock().unwrap().clone(),
+            document_views: self.document_views.lock().unwrap().clone(),
+            document_relations: self.document_relations.lock().unwrap().clone(),
+            view_relations: self.view_relations.lock().unwrap().clone(),
+            payload_sizes: self.payload_sizes.lock().unwrap().clone(),
+            document_counts: self.document_counts.lock().unwrap().clone(),
+        }
+    }
+
+    /// Returns a store holding the values of the passed snapshot.
+    ///
+    /// The returned store doesn't share any state with the store the snapshot was taken from, it
+    /// has no subscribers.
+    pub(crate) fn from_snapshot(snapshot: Snapshot) -> Self {
+        Self {
+            logs: Arc::new(Mutex::new(snapshot.logs)),
+            entries: Arc::new(Mutex::new(snapshot.entries)),
+            pruned_seq_nums: Arc::new(Mutex::new(snapshot.pruned_seq_nums)),
+            operations: Arc::new(Mutex::new(snapshot.operations)),
+            documents: Arc::new(Mutex::new(snapshot.documents)),
+            document_views: Arc::new(Mutex::new(snapshot.document_views)),
+            document_relations: Arc::new(Mutex::new(snapshot.document_relations)),
+            view_relations: Arc::new(Mutex::new(snapshot.view_relations)),
+            payload_sizes: Arc::new(Mutex::new(snapshot.payload_sizes)),
+            document_counts: Arc::new(Mutex::new(snapshot.document_counts)),
+            ..Self::default()
+        }
+    }
+
+    /// Records a write when this store is a handle to a transaction.
+    pub(crate) fn record(&self, write: Write) {
+        if let Some(transaction) = &self.transaction {
+            transaction.record(write);
+        }
+    }
+}
+
+/// Copy of all values stored in a `MemoryStore` at one point in time.
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+    logs: HashMap<PublickeyLogId, Log>,
+    entries: HashMap<Hash, StorageEntry>,
+    pruned_seq_nums: HashMap<PublickeyLogId, SeqNum>,
+    operations: HashMap<OperationId, PublishedOperation>,
+    documents: HashMap<DocumentId, Document>,
+    document_views: HashMap<DocumentViewId, StoredDocumentView>,
+    document_relations: HashMap<DocumentId, Vec<OperationId>>,
+    view_relations: HashMap<DocumentViewId, Vec<OperationId>>,
+    payload_sizes: HashMap<PublicKey, u64>,
+    document_counts: HashMap<SchemaId, usize>,
 }
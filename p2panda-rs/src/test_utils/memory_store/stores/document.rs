@@ -1,20 +1,26 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::convert::TryInto;
 
 use async_trait::async_trait;
 
-use crate::document::{Document, DocumentBuilder, DocumentId, DocumentViewId};
+use crate::document::traits::AsDocument;
+use crate::document::{Document, DocumentBuilder, DocumentId, DocumentView, DocumentViewId};
 use crate::operation::traits::AsOperation;
 use crate::schema::SchemaId;
 use crate::storage_provider::error::DocumentStorageError;
+use crate::storage_provider::events::StoreEvent;
 use crate::storage_provider::traits::{DocumentStore, OperationStore};
+use crate::storage_provider::utils::current_view_id;
+use crate::test_utils::memory_store::stores::Write;
 use crate::test_utils::memory_store::MemoryStore;
 use crate::WithId;
 
-/// This implementation of the DocumentStore trait does not use a caching layer to persist
-/// document state. Each document is materialised from it's contained operations on each query.
+/// This implementation of the DocumentStore trait materialises documents from their operations
+/// on each query unless a materialised state was inserted before. Stored documents are only
+/// returned while they are at their latest view and stored document views are returned directly.
 /// This means that any document state can be queried as long as it's operations are persisted in
 /// the store without requiring a preparatory materialisation step. It also means this
 /// implementation is very inefficient and is only recommended for use in test environments.
@@ -34,6 +40,13 @@ impl DocumentStore for MemoryStore {
             return Ok(None);
         }
 
+        // Return the stored document if no operations were added to it since it was inserted.
+        if let Some(document) = self.documents.lock().unwrap().get(id) {
+            if document.view_id() == &current_view_id(&operations) {
+                return Ok(Some(document.to_owned()));
+            }
+        }
+
         Ok(Some({ &operations }.try_into()?))
     }
 
@@ -43,6 +56,11 @@ impl DocumentStore for MemoryStore {
         &self,
         id: &DocumentViewId,
     ) -> Result<Option<Document>, DocumentStorageError> {
+        // Document views are immutable, a stored view can be returned without materialising it.
+        if let Some(document) = self.get_stored_document_view(id) {
+            return Ok(Some(document));
+        }
+
         let operation_id = id.iter().next().unwrap();
         let document_id = match self.get_document_id_by_operation_id(operation_id).await? {
             Some(id) => id,
@@ -89,25 +107,154 @@ impl DocumentStore for MemoryStore {
 
         Ok(documents)
     }
+
+    /// Insert a document and it's current view into the store.
+    ///
+    /// Subscribers are notified when the view wasn't stored before.
+    async fn insert_document(&self, document: &Document) -> Result<(), DocumentStorageError> {
+        // Stored documents are updated to their new view, their schema and author never change.
+        if let Some(stored) = self.documents.lock().unwrap().get(document.id()) {
+            if stored.schema_id() != document.schema_id() || stored.author() != document.author() {
+                return Err(DocumentStorageError::DocumentInsertionError(
+                    document.id().to_owned(),
+                ));
+            }
+        }
+
+        if let Some(document_view) = document.view() {
+            self.insert_document_view(&document_view, document.id(), document.schema_id())
+                .await?;
+        }
+
+        self.documents
+            .lock()
+            .unwrap()
+            .insert(document.id().to_owned(), document.to_owned());
+        self.record(Write::InsertDocument(document.to_owned()));
+
+        Ok(())
+    }
+
+    /// Insert a document view into the store.
+    ///
+    /// Subscribers are notified when the view wasn't stored before.
+    async fn insert_document_view(
+        &self,
+        document_view: &DocumentView,
+        document_id: &DocumentId,
+        schema_id: &SchemaId,
+    ) -> Result<(), DocumentStorageError> {
+        let is_new = {
+            let mut document_views = self.document_views.lock().unwrap();
+
+            match document_views.entry(document_view.id().to_owned()) {
+                Entry::Occupied(_) => false,
+                Entry::Vacant(entry) => {
+                    entry.insert((
+                        document_id.to_owned(),
+                        schema_id.to_owned(),
+                        document_view.to_owned(),
+                    ));
+                    true
+                }
+            }
+        };
+
+        if is_new {
+            self.events.send(&StoreEvent::DocumentViewUpdated {
+                view_id: document_view.id().to_owned(),
+                schema_id: schema_id.to_owned(),
+                document_id: document_id.to_owned(),
+            });
+        }
+
+        self.record(Write::InsertDocumentView(
+            document_view.to_owned(),
+            document_id.to_owned(),
+            schema_id.to_owned(),
+        ));
+
+        Ok(())
+    }
+
+    /// Remove a stored document and all of it's stored views.
+    async fn remove_document(&self, document_id: &DocumentId) -> Result<(), DocumentStorageError> {
+        self.documents.lock().unwrap().remove(document_id);
+        self.document_views
+            .lock()
+            .unwrap()
+            .retain(|_, (id, _, _)| id != document_id);
+        self.record(Write::RemoveDocument(document_id.to_owned()));
+
+        Ok(())
+    }
+
+    /// Get all stored views of a document.
+    async fn get_document_views(
+        &self,
+        document_id: &DocumentId,
+    ) -> Result<Vec<DocumentView>, DocumentStorageError> {
+        let document_views = self.document_views.lock().unwrap();
+
+        Ok(document_views
+            .values()
+            .filter(|(id, _, _)| id == document_id)
+            .map(|(_, _, document_view)| document_view.to_owned())
+            .collect())
+    }
+}
+
+impl MemoryStore {
+    /// Returns the document at a stored view, `None` if this view or the document it belongs to
+    /// were not inserted.
+    fn get_stored_document_view(&self, id: &DocumentViewId) -> Option<Document> {
+        let document_views = self.document_views.lock().unwrap();
+        let (document_id, schema_id, document_view) = document_views.get(id)?;
+
+        // The author is only known from the stored document.
+        let documents = self.documents.lock().unwrap();
+        let document = documents.get(document_id)?;
+
+        Some(Document::new(
+            document_id,
+            Some(document_view.fields()),
+            schema_id,
+            id,
+            document.author(),
+        ))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use futures::{FutureExt, StreamExt, TryStreamExt};
     use rstest::rstest;
 
     use crate::document::traits::AsDocument;
-    use crate::document::DocumentId;
-    use crate::operation::{OperationAction, OperationBuilder, OperationId, OperationValue};
+    use crate::document::{
+        Document, DocumentId, DocumentViewFields, DocumentViewId, DocumentViewValue,
+    };
+    use crate::entry::traits::AsEncodedEntry;
+    use crate::entry::{LogId, SeqNum};
+    use crate::operation::{
+        OperationAction, OperationBuilder, OperationId, OperationValue, PinnedRelation, Relation,
+    };
     use crate::schema::SchemaId;
-    use crate::storage_provider::traits::{DocumentStore, OperationStore};
+    use crate::storage_provider::error::DocumentStorageError;
+    use crate::storage_provider::events::{EventFilter, StoreEvent};
+    use crate::storage_provider::query::{Direction, DocumentQueryBuilder, Filter, OrderBy};
+    use crate::storage_provider::traits::{
+        DocumentStore, EntryStore, OperationStore, SubscriptionStore, TransactionStore,
+    };
     use crate::test_utils::constants::{self, test_fields};
     use crate::test_utils::fixtures::{
         populate_store_config, random_document_id, random_operation_id, schema_id,
     };
     use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
     use crate::test_utils::memory_store::MemoryStore;
+    use crate::WithId;
 
     #[rstest]
     #[tokio::test]
@@ -200,4 +347,447 @@ mod tests {
 
         assert_eq!(schema_documents.len(), 0);
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn streams_documents_by_schema(
+        #[from(populate_store_config)]
+        #[with(10, 2, 1, false, constants::schema())]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        populate_store(&store, &config).await;
+
+        let schema_id = SchemaId::from_str(constants::SCHEMA_ID).unwrap();
+        let mut view_ids: Vec<DocumentViewId> = store
+            .stream_documents_by_schema(&schema_id)
+            .map_ok(|document| document.view_id().to_owned())
+            .try_collect()
+            .await
+            .unwrap();
+        view_ids.sort();
+
+        let mut expected_view_ids: Vec<DocumentViewId> = store
+            .get_documents_by_schema(&schema_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|document| document.view_id().to_owned())
+            .collect();
+        expected_view_ids.sort();
+
+        assert_eq!(view_ids.len(), 2);
+        assert_eq!(view_ids, expected_view_ids);
+
+        let schema_id = SchemaId::SchemaDefinition(1);
+        let mut stream = store.stream_documents_by_schema(&schema_id);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn inserts_and_gets_document_views(
+        #[from(populate_store_config)]
+        #[with(3, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+        let document_id = documents[0].clone();
+
+        assert!(store
+            .get_document_views(&document_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Materialise and insert the document at every view.
+        let mut view_ids = Vec::new();
+        for seq_num in 1..=3 {
+            let entry = store
+                .get_entry_at_seq_num(
+                    &public_key,
+                    &LogId::default(),
+                    &SeqNum::new(seq_num).unwrap(),
+                )
+                .await
+                .unwrap()
+                .unwrap();
+            let view_id = DocumentViewId::new(&[entry.hash().into()]);
+            let document = store
+                .get_document_by_view_id(&view_id)
+                .await
+                .unwrap()
+                .unwrap();
+
+            store
+                .insert_document_view(
+                    &document.view().unwrap(),
+                    &document_id,
+                    document.schema_id(),
+                )
+                .await
+                .unwrap();
+
+            // Inserting the same view again has no effect.
+            store
+                .insert_document_view(
+                    &document.view().unwrap(),
+                    &document_id,
+                    document.schema_id(),
+                )
+                .await
+                .unwrap();
+
+            view_ids.push(view_id);
+        }
+
+        let mut stored_view_ids: Vec<DocumentViewId> = store
+            .get_document_views(&document_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|view| view.id().to_owned())
+            .collect();
+        stored_view_ids.sort();
+        view_ids.sort();
+
+        assert_eq!(stored_view_ids, view_ids);
+
+        // Stored documents can't change their schema.
+        let document = store.get_document(&document_id).await.unwrap().unwrap();
+        store.insert_document(&document).await.unwrap();
+        let other_schema_document = Document::new(
+            document.id(),
+            document.fields(),
+            &SchemaId::SchemaDefinition(1),
+            document.view_id(),
+            document.author(),
+        );
+        assert!(matches!(
+            store.insert_document(&other_schema_document).await,
+            Err(DocumentStorageError::DocumentInsertionError(_))
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn returns_stored_document_while_up_to_date(
+        schema_id: SchemaId,
+        #[from(random_operation_id)] operation_id: OperationId,
+        #[from(populate_store_config)]
+        #[with(1, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let document_id = documents[0].clone();
+        let mut document = store.get_document(&document_id).await.unwrap().unwrap();
+
+        // Change the fields of the materialised document before storing it, like this we can tell
+        // if the stored document is returned or if it was materialised again.
+        let view_id = document.view_id().to_owned();
+        let mut fields = DocumentViewFields::new();
+        fields.insert(
+            "age",
+            DocumentViewValue::new(&operation_id, &OperationValue::Integer(100)),
+        );
+        document.update_view(&view_id, Some(&fields));
+        store.insert_document(&document).await.unwrap();
+
+        let stored_document = store.get_document(&document_id).await.unwrap().unwrap();
+        assert_eq!(
+            stored_document.get("age"),
+            Some(&OperationValue::Integer(100))
+        );
+
+        let stored_document = store
+            .get_document_by_view_id(&view_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored_document.get("age"),
+            Some(&OperationValue::Integer(100))
+        );
+        assert_eq!(stored_document.author(), document.author());
+
+        // After a new operation was added the document is materialised again.
+        let update_operation = OperationBuilder::new(&schema_id)
+            .action(OperationAction::Update)
+            .previous(&view_id)
+            .fields(&[("age", OperationValue::Integer(29))])
+            .build()
+            .unwrap();
+        store
+            .insert_operation(
+                &operation_id,
+                &key_pairs[0].public_key(),
+                &update_operation,
+                &document_id,
+            )
+            .await
+            .unwrap();
+
+        let document = store.get_document(&document_id).await.unwrap().unwrap();
+        assert_eq!(document.get("age"), Some(&OperationValue::Integer(29)));
+        assert_eq!(document.view_id(), &DocumentViewId::new(&[operation_id]));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn queries_documents(
+        #[from(random_operation_id)] operation_id: OperationId,
+        #[from(populate_store_config)]
+        #[with(1, 3, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, documents) = populate_store(&store, &config).await;
+        let schema = constants::schema();
+
+        // Make one of the documents older than the others.
+        let document = store.get_document(&documents[1]).await.unwrap().unwrap();
+        let update_operation = OperationBuilder::new(schema.id())
+            .action(OperationAction::Update)
+            .previous(document.view_id())
+            .fields(&[("age", OperationValue::Integer(99))])
+            .build()
+            .unwrap();
+        store
+            .insert_operation(
+                &operation_id,
+                &key_pairs[0].public_key(),
+                &update_operation,
+                &documents[1],
+            )
+            .await
+            .unwrap();
+
+        let query = DocumentQueryBuilder::new(&schema)
+            .filter("age", Filter::GreaterThan(OperationValue::Integer(28)))
+            .build()
+            .unwrap();
+        let page = store.query_documents(&query).await.unwrap();
+        assert_eq!(page.documents.len(), 1);
+        assert_eq!(page.documents[0].id(), &documents[1]);
+
+        let query = DocumentQueryBuilder::new(&schema)
+            .order_by(OrderBy::Field("age".into()), Direction::Descending)
+            .limit(2)
+            .build()
+            .unwrap();
+        let page = store.query_documents(&query).await.unwrap();
+        assert_eq!(page.documents.len(), 2);
+        assert_eq!(page.documents[0].id(), &documents[1]);
+        assert!(page.next_cursor.is_some());
+
+        let query = DocumentQueryBuilder::new(&schema)
+            .order_by(OrderBy::Field("age".into()), Direction::Descending)
+            .limit(2)
+            .after(&page.next_cursor.unwrap())
+            .build()
+            .unwrap();
+        let page = store.query_documents(&query).await.unwrap();
+        assert_eq!(page.documents.len(), 1);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn gets_referencing_documents(
+        schema_id: SchemaId,
+        #[from(random_operation_id)] create_operation_id: OperationId,
+        #[from(random_operation_id)] update_operation_id: OperationId,
+        #[from(random_document_id)] target_document_id: DocumentId,
+        #[from(random_document_id)] other_document_id: DocumentId,
+        #[from(random_operation_id)] target_view_operation_id: OperationId,
+        #[from(populate_store_config)]
+        #[with(1, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+        let target_view_id = DocumentViewId::new(&[target_view_operation_id]);
+
+        // Create a document pointing at the target document and a view of it.
+        let mut fields = test_fields();
+        for (name, value) in fields.iter_mut() {
+            match *name {
+                "profile_picture" => {
+                    *value = OperationValue::Relation(Relation::new(target_document_id.clone()))
+                }
+                "past_event" => {
+                    *value =
+                        OperationValue::PinnedRelation(PinnedRelation::new(target_view_id.clone()))
+                }
+                _ => (),
+            }
+        }
+        let create_operation = OperationBuilder::new(&schema_id)
+            .fields(&fields)
+            .build()
+            .unwrap();
+        let document_id = DocumentId::new(&create_operation_id);
+        store
+            .insert_operation(
+                &create_operation_id,
+                &public_key,
+                &create_operation,
+                &document_id,
+            )
+            .await
+            .unwrap();
+
+        let operations = store
+            .get_operations_referencing_document(&target_document_id)
+            .await
+            .unwrap();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(
+            WithId::<OperationId>::id(&operations[0]),
+            &create_operation_id
+        );
+
+        let documents = store
+            .get_documents_referencing_document(&target_document_id)
+            .await
+            .unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].id(), &document_id);
+
+        let documents = store
+            .get_documents_referencing_view(&target_view_id)
+            .await
+            .unwrap();
+        assert_eq!(documents.len(), 1);
+
+        // Nothing points at unrelated documents.
+        assert!(store
+            .get_documents_referencing_document(&other_document_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // After the relation was changed the document doesn't point at the target anymore, the
+        // operation which introduced the relation is still returned.
+        let update_operation = OperationBuilder::new(&schema_id)
+            .action(OperationAction::Update)
+            .previous(&DocumentViewId::new(&[create_operation_id]))
+            .fields(&[(
+                "profile_picture",
+                OperationValue::Relation(Relation::new(other_document_id.clone())),
+            )])
+            .build()
+            .unwrap();
+        store
+            .insert_operation(
+                &update_operation_id,
+                &public_key,
+                &update_operation,
+                &document_id,
+            )
+            .await
+            .unwrap();
+
+        assert!(store
+            .get_documents_referencing_document(&target_document_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store
+                .get_operations_referencing_document(&target_document_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_documents_referencing_document(&other_document_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_documents_referencing_view(&target_view_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn notifies_about_inserted_document_views(
+        #[from(populate_store_config)]
+        #[with(2, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (_, documents) = populate_store(&store, &config).await;
+        let document = store.get_document(&documents[0]).await.unwrap().unwrap();
+
+        let mut events = store.subscribe(EventFilter::new().document_id(document.id()));
+        store.insert_document(&document).await.unwrap();
+
+        assert_eq!(
+            events.next().await,
+            Some(StoreEvent::DocumentViewUpdated {
+                view_id: document.view_id().to_owned(),
+                schema_id: document.schema_id().to_owned(),
+                document_id: document.id().to_owned(),
+            })
+        );
+
+        // Inserting a view which is already stored doesn't notify again.
+        store
+            .insert_document_view(
+                &document.view().unwrap(),
+                document.id(),
+                document.schema_id(),
+            )
+            .await
+            .unwrap();
+        assert!(events.next().now_or_never().is_none());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn notifies_about_document_views_after_commit(
+        #[from(populate_store_config)]
+        #[with(2, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (_, documents) = populate_store(&store, &config).await;
+        let document = store.get_document(&documents[0]).await.unwrap().unwrap();
+        let mut events = store.subscribe(EventFilter::new().document_id(document.id()));
+
+        // Views inserted through a transaction which was rolled back are never announced.
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction.insert_document(&document).await.unwrap();
+        store.rollback_transaction(transaction).await.unwrap();
+        assert!(events.next().now_or_never().is_none());
+
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction.insert_document(&document).await.unwrap();
+        assert!(events.next().now_or_never().is_none());
+
+        store.commit_transaction(transaction).await.unwrap();
+        assert_eq!(
+            events.next().await,
+            Some(StoreEvent::DocumentViewUpdated {
+                view_id: document.view_id().to_owned(),
+                schema_id: document.schema_id().to_owned(),
+                document_id: document.id().to_owned(),
+            })
+        );
+    }
 }
@@ -10,6 +10,7 @@ use crate::identity::PublicKey;
 use crate::operation::EncodedOperation;
 use crate::storage_provider::error::EntryStorageError;
 use crate::storage_provider::traits::EntryStore;
+use crate::test_utils::memory_store::stores::Write;
 use crate::test_utils::memory_store::{MemoryStore, StorageEntry};
 
 /// Implement `EntryStore` trait on `MemoryStore`
@@ -34,10 +35,109 @@ impl EntryStore for MemoryStore {
         };
 
         let mut entries = self.entries.lock().unwrap();
+
+        let is_duplicate = entries.values().any(|stored| {
+            stored.hash() == encoded_entry.hash()
+                || (stored.public_key() == entry.public_key()
+                    && stored.log_id() == entry.log_id()
+                    && stored.seq_num() == entry.seq_num())
+        });
+
+        if is_duplicate {
+            return Err(EntryStorageError::Custom(format!(
+                "Entry {} or another entry at seq num {} of log {} already exists",
+                encoded_entry.hash(),
+                entry.seq_num().as_u64(),
+                entry.log_id().as_u64()
+            )));
+        }
+
         entries.insert(encoded_entry.hash(), storage_entry);
+
+        if let Some(operation) = operation {
+            *self
+                .payload_sizes
+                .lock()
+                .unwrap()
+                .entry(*entry.public_key())
+                .or_default() += operation.size();
+        }
+
+        self.record(Write::InsertEntry(
+            entry.to_owned(),
+            encoded_entry.to_owned(),
+            operation.cloned(),
+        ));
+
         Ok(())
     }
 
+    /// Remove the payload of an `Entry` while keeping the `Entry` itself.
+    async fn remove_payload(&self, hash: &Hash) -> Result<(), EntryStorageError> {
+        debug!("Removing payload of entry: {} from store", hash);
+
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(hash) {
+            if let Some(payload) = entry.payload.take() {
+                self.subtract_payload_size(entry.public_key(), &payload);
+            }
+        }
+
+        self.record(Write::RemovePayload(hash.to_owned()));
+        Ok(())
+    }
+
+    /// Remove an `Entry` together with it's payload.
+    async fn remove_entry(&self, hash: &Hash) -> Result<(), EntryStorageError> {
+        debug!("Removing entry: {} from store", hash);
+
+        if let Some(entry) = self.entries.lock().unwrap().remove(hash) {
+            if let Some(payload) = &entry.payload {
+                self.subtract_payload_size(entry.public_key(), payload);
+            }
+        }
+        self.record(Write::RemoveEntry(hash.to_owned()));
+        Ok(())
+    }
+
+    /// Record that a `PublicKey`'s log was pruned below the passed sequence number.
+    async fn insert_pruned_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        seq_num: &SeqNum,
+    ) -> Result<(), EntryStorageError> {
+        let public_key_log_id_str = public_key.to_string() + &log_id.as_u64().to_string();
+        let mut pruned_seq_nums = self.pruned_seq_nums.lock().unwrap();
+        pruned_seq_nums.insert(public_key_log_id_str, seq_num.to_owned());
+        self.record(Write::InsertPrunedSeqNum(
+            public_key.to_owned(),
+            log_id.to_owned(),
+            seq_num.to_owned(),
+        ));
+        Ok(())
+    }
+
+    /// Get the sequence number below which a `PublicKey`'s log was pruned.
+    async fn get_pruned_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+    ) -> Result<Option<SeqNum>, EntryStorageError> {
+        let public_key_log_id_str = public_key.to_string() + &log_id.as_u64().to_string();
+        let pruned_seq_nums = self.pruned_seq_nums.lock().unwrap();
+        Ok(pruned_seq_nums.get(&public_key_log_id_str).cloned())
+    }
+
+    /// Get the total size of all payloads stored in the logs of a `PublicKey`, in bytes.
+    async fn get_payload_size_by_public_key(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<u64, EntryStorageError> {
+        let payload_sizes = self.payload_sizes.lock().unwrap();
+        Ok(payload_sizes.get(public_key).copied().unwrap_or_default())
+    }
+
     /// Get an `Entry` by it's `Hash`.
     async fn get_entry(&self, hash: &Hash) -> Result<Option<StorageEntry>, EntryStorageError> {
         let entries = self.entries.lock().unwrap();
@@ -78,6 +178,41 @@ impl EntryStore for MemoryStore {
 
         Ok(latest_entry.map(|(_, entry)| entry).cloned())
     }
+
+    /// Get up to `limit` entries of `PublicKey`'s log, starting at `from` sequence number.
+    async fn get_entries_from(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        from: &SeqNum,
+        limit: usize,
+    ) -> Result<Vec<StorageEntry>, EntryStorageError> {
+        let entries = self.entries.lock().unwrap();
+
+        let mut log_entries: Vec<StorageEntry> = entries
+            .values()
+            .filter(|entry| {
+                entry.public_key() == public_key
+                    && entry.log_id() == log_id
+                    && entry.seq_num().as_u64() >= from.as_u64()
+            })
+            .cloned()
+            .collect();
+
+        log_entries.sort_by_key(|entry| entry.seq_num().as_u64());
+        log_entries.truncate(limit);
+
+        Ok(log_entries)
+    }
+}
+
+impl MemoryStore {
+    /// Removes the size of a payload from the running total of it's public key.
+    fn subtract_payload_size(&self, public_key: &PublicKey, payload: &EncodedOperation) {
+        if let Some(size) = self.payload_sizes.lock().unwrap().get_mut(public_key) {
+            *size = size.saturating_sub(payload.size());
+        }
+    }
 }
 
 #[cfg(test)]
@@ -86,10 +221,13 @@ mod tests {
 
     use crate::entry::decode::decode_entry;
     use crate::entry::traits::{AsEncodedEntry, AsEntry};
-    use crate::entry::EncodedEntry;
+    use crate::entry::{EncodedEntry, LogId, SeqNum};
+    use crate::storage_provider::error::EntryStorageError;
     use crate::storage_provider::traits::EntryStore;
     use crate::test_utils::fixtures::{encoded_entry, populate_store_config};
-    use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
+    use crate::test_utils::memory_store::helpers::{
+        populate_store, remove_entries, PopulateStoreConfig,
+    };
     use crate::test_utils::memory_store::MemoryStore;
 
     #[rstest]
@@ -186,4 +324,160 @@ mod tests {
             store.get_entry(&entry_three.hash()).await.unwrap().unwrap()
         );
     }
+
+    #[rstest]
+    #[case::from_start(1, 5, vec![1, 2, 3, 4, 5])]
+    #[case::from_middle(9, 3, vec![9, 10, 11])]
+    #[case::limit_exceeds_log(11, 10, vec![11, 12, 13])]
+    #[case::from_after_end(14, 10, vec![])]
+    #[case::zero_limit(1, 0, vec![])]
+    #[tokio::test]
+    async fn get_entries_from(
+        #[case] from: u64,
+        #[case] limit: usize,
+        #[case] expected_seq_nums: Vec<u64>,
+        #[from(populate_store_config)]
+        #[with(13, 2, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        let entries = store
+            .get_entries_from(
+                &public_key,
+                &LogId::new(1),
+                &SeqNum::new(from).unwrap(),
+                limit,
+            )
+            .await
+            .unwrap();
+
+        let seq_nums: Vec<u64> = entries
+            .iter()
+            .map(|entry| entry.seq_num().as_u64())
+            .collect();
+        assert_eq!(seq_nums, expected_seq_nums);
+        assert!(entries.iter().all(|entry| entry.log_id() == &LogId::new(1)));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_paginated_entries(
+        #[from(populate_store_config)]
+        #[with(13, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        // Read the whole log in pages of five entries.
+        let mut pages = Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let page = store
+                .get_paginated_entries(&public_key, &LogId::default(), cursor.as_ref(), 5)
+                .await
+                .unwrap();
+
+            pages.push(
+                page.entries
+                    .iter()
+                    .map(|entry| entry.seq_num().as_u64())
+                    .collect::<Vec<u64>>(),
+            );
+
+            match page.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            pages,
+            vec![vec![1, 2, 3, 4, 5], vec![6, 7, 8, 9, 10], vec![11, 12, 13]]
+        );
+
+        // A log which fits exactly into one page doesn't return a cursor.
+        let page = store
+            .get_paginated_entries(&public_key, &LogId::default(), None, 13)
+            .await
+            .unwrap();
+        assert_eq!(page.entries.len(), 13);
+        assert!(page.next_cursor.is_none());
+
+        // An unknown log returns an empty page.
+        let page = store
+            .get_paginated_entries(&public_key, &LogId::new(1), None, 5)
+            .await
+            .unwrap();
+        assert!(page.entries.is_empty());
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[rstest]
+    #[case::first_entry(1, vec![])]
+    #[case::only_backlink(2, vec![1])]
+    #[case::skiplink_to_first(4, vec![1])]
+    #[case::backlink_then_skiplink(5, vec![1, 4])]
+    #[case::two_skiplinks(17, vec![1, 4, 13])]
+    #[case::backlinks_and_skiplinks(20, vec![1, 4, 13, 17, 18, 19])]
+    #[tokio::test]
+    async fn get_certificate_pool(
+        #[case] seq_num: u64,
+        #[case] expected_seq_nums: Vec<u64>,
+        #[from(populate_store_config)]
+        #[with(20, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        let certificate_pool = store
+            .get_certificate_pool(
+                &public_key,
+                &LogId::default(),
+                &SeqNum::new(seq_num).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let seq_nums: Vec<u64> = certificate_pool
+            .iter()
+            .map(|entry| entry.seq_num().as_u64())
+            .collect();
+        assert_eq!(seq_nums, expected_seq_nums);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn certificate_pool_entry_missing(
+        #[from(populate_store_config)]
+        #[with(20, 1, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (key_pairs, _) = populate_store(&store, &config).await;
+        let public_key = key_pairs[0].public_key();
+
+        remove_entries(&store, &public_key, &[(0, 4)]);
+
+        let result = store
+            .get_certificate_pool(&public_key, &LogId::default(), &SeqNum::new(17).unwrap())
+            .await;
+        assert!(matches!(
+            result,
+            Err(EntryStorageError::CertPoolEntryMissing(4))
+        ));
+
+        // Entries which don't need the removed entry can still be verified.
+        assert!(store
+            .get_certificate_pool(&public_key, &LogId::default(), &SeqNum::new(3).unwrap())
+            .await
+            .is_ok());
+    }
 }
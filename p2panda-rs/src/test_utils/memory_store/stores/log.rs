@@ -9,6 +9,7 @@ use crate::identity::PublicKey;
 use crate::schema::SchemaId;
 use crate::storage_provider::error::LogStorageError;
 use crate::storage_provider::traits::LogStore;
+use crate::test_utils::memory_store::stores::Write;
 use crate::test_utils::memory_store::MemoryStore;
 
 /// Implement the `LogStore` trait on MemoryStore
@@ -30,10 +31,25 @@ impl LogStore for MemoryStore {
 
         let public_key_log_id_str = public_key.to_string() + &log_id.as_u64().to_string();
         let mut logs = self.logs.lock().unwrap();
+
+        if logs.contains_key(&public_key_log_id_str) {
+            return Err(LogStorageError::Custom(format!(
+                "Log {} of {} already exists",
+                log_id.as_u64(),
+                public_key
+            )));
+        }
+
         logs.insert(
             public_key_log_id_str,
             (*public_key, *log_id, schema.to_owned(), document.to_owned()),
         );
+        self.record(Write::InsertLog(
+            *log_id,
+            *public_key,
+            schema.to_owned(),
+            document.to_owned(),
+        ));
         Ok(true)
     }
 
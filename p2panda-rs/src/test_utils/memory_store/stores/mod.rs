@@ -4,3 +4,7 @@ mod document;
 mod entry;
 mod log;
 pub mod operation;
+mod subscription;
+mod transaction;
+
+pub(crate) use transaction::{MemoryTransaction, Write};
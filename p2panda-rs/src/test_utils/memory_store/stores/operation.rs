@@ -3,13 +3,15 @@
 use async_trait::async_trait;
 use log::debug;
 
-use crate::document::DocumentId;
+use crate::document::{DocumentId, DocumentViewId};
 use crate::identity::PublicKey;
 use crate::operation::traits::AsOperation;
 use crate::operation::{Operation, OperationId};
 use crate::schema::SchemaId;
 use crate::storage_provider::error::OperationStorageError;
 use crate::storage_provider::traits::OperationStore;
+use crate::storage_provider::utils::{referenced_document_views, referenced_documents};
+use crate::test_utils::memory_store::stores::Write;
 use crate::test_utils::memory_store::{MemoryStore, PublishedOperation};
 use crate::WithId;
 
@@ -39,13 +41,72 @@ impl OperationStore for MemoryStore {
             return Err(OperationStorageError::InsertionError(id.clone()));
         }
 
-        let operation = PublishedOperation(
+        // Index all relations of this operation to find it when querying for referencing
+        // operations.
+        let mut document_relations = self.document_relations.lock().unwrap();
+        for target in referenced_documents(operation) {
+            document_relations
+                .entry(target)
+                .or_default()
+                .push(id.clone());
+        }
+
+        let mut view_relations = self.view_relations.lock().unwrap();
+        for target in referenced_document_views(operation) {
+            view_relations.entry(target).or_default().push(id.clone());
+        }
+
+        if operation.is_create() {
+            *self
+                .document_counts
+                .lock()
+                .unwrap()
+                .entry(operation.schema_id())
+                .or_default() += 1;
+        }
+
+        operations.insert(
+            id.clone(),
+            PublishedOperation(
+                id.clone(),
+                operation.clone(),
+                *public_key,
+                document_id.clone(),
+            ),
+        );
+        self.record(Write::InsertOperation(
             id.clone(),
-            operation.clone(),
             *public_key,
+            operation.clone(),
             document_id.clone(),
-        );
-        operations.insert(id.clone(), operation);
+        ));
+
+        Ok(())
+    }
+
+    /// Remove all `Operations` of a single `Document`.
+    async fn remove_operations_by_document_id(
+        &self,
+        id: &DocumentId,
+    ) -> Result<(), OperationStorageError> {
+        debug!("Removing operations of document: {} from store", id);
+
+        let mut operations = self.operations.lock().unwrap();
+        let mut document_counts = self.document_counts.lock().unwrap();
+        operations.retain(|_, operation| {
+            if WithId::<DocumentId>::id(operation) != id {
+                return true;
+            }
+
+            if operation.is_create() {
+                if let Some(count) = document_counts.get_mut(&operation.schema_id()) {
+                    *count = count.saturating_sub(1);
+                }
+            }
+
+            false
+        });
+        self.record(Write::RemoveOperationsByDocumentId(id.to_owned()));
 
         Ok(())
     }
@@ -97,10 +158,55 @@ impl OperationStore for MemoryStore {
             .map(Clone::clone)
             .collect())
     }
+
+    /// Get the number of `Documents` created with a certain `Schema`.
+    async fn count_documents_by_schema_id(
+        &self,
+        id: &SchemaId,
+    ) -> Result<usize, OperationStorageError> {
+        let document_counts = self.document_counts.lock().unwrap();
+        Ok(document_counts.get(id).copied().unwrap_or_default())
+    }
+
+    /// Get all `Operations` which point at a `Document` with a relation or relation list field.
+    async fn get_operations_referencing_document(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<PublishedOperation>, OperationStorageError> {
+        let operations = self.operations.lock().unwrap();
+        let document_relations = self.document_relations.lock().unwrap();
+
+        // Operations might have been removed from the store without updating the index.
+        Ok(document_relations
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|operation_id| operations.get(operation_id).cloned())
+            .collect())
+    }
+
+    /// Get all `Operations` which point at a `DocumentView` with a pinned relation or pinned
+    /// relation list field.
+    async fn get_operations_referencing_view(
+        &self,
+        id: &DocumentViewId,
+    ) -> Result<Vec<PublishedOperation>, OperationStorageError> {
+        let operations = self.operations.lock().unwrap();
+        let view_relations = self.view_relations.lock().unwrap();
+
+        // Operations might have been removed from the store without updating the index.
+        Ok(view_relations
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|operation_id| operations.get(operation_id).cloned())
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use futures::{StreamExt, TryStreamExt};
     use rstest::rstest;
 
     use crate::document::DocumentId;
@@ -113,10 +219,11 @@ mod tests {
     use crate::test_utils::constants;
     use crate::test_utils::fixtures::{
         create_operation, delete_operation, document_id, operation_id, populate_store_config,
-        public_key, random_operation_id, random_previous_operations, update_operation,
+        public_key, random_document_id, random_operation_id, random_previous_operations,
+        update_operation,
     };
     use crate::test_utils::memory_store::helpers::{populate_store, PopulateStoreConfig};
-    use crate::test_utils::memory_store::MemoryStore;
+    use crate::test_utils::memory_store::{MemoryStore, PublishedOperation};
     use crate::WithId;
 
     use super::OperationStore;
@@ -262,4 +369,47 @@ mod tests {
 
         assert_eq!(operations_by_document_id.len(), 5)
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn streams_operations(
+        #[from(populate_store_config)]
+        #[with(5, 2, 1)]
+        config: PopulateStoreConfig,
+    ) {
+        let store = MemoryStore::default();
+        let (_, document_ids) = populate_store(&store, &config).await;
+
+        let streamed_operations: Vec<PublishedOperation> = store
+            .stream_operations_by_document_id(&document_ids[0])
+            .try_collect()
+            .await
+            .unwrap();
+        let operations = store
+            .get_operations_by_document_id(&document_ids[0])
+            .await
+            .unwrap();
+        assert_eq!(streamed_operations.len(), 5);
+        for (streamed_operation, operation) in streamed_operations.iter().zip(operations.iter()) {
+            assert_eq!(
+                WithId::<OperationId>::id(streamed_operation),
+                WithId::<OperationId>::id(operation)
+            );
+        }
+
+        let streamed_operations: Vec<PublishedOperation> = store
+            .stream_operations_by_schema_id(config.schema.id())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(streamed_operations.len(), 10);
+
+        // Nothing is streamed for an unknown document.
+        let mut stream = store.stream_operations_by_document_id(&document_ids[1]);
+        assert!(stream.next().await.is_some());
+
+        let document_id = random_document_id();
+        let mut stream = store.stream_operations_by_document_id(&document_id);
+        assert!(stream.next().await.is_none());
+    }
 }
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use futures::stream::BoxStream;
+
+use crate::storage_provider::events::{EventFilter, StoreEvent};
+use crate::storage_provider::traits::SubscriptionStore;
+use crate::test_utils::memory_store::MemoryStore;
+
+impl SubscriptionStore for MemoryStore {
+    /// Subscribe to events matching the filter.
+    fn subscribe(&self, filter: EventFilter) -> BoxStream<'static, StoreEvent> {
+        self.events.subscribe(filter)
+    }
+
+    /// Send an event to all matching subscribers.
+    fn notify(&self, event: StoreEvent) {
+        self.events.send(&event)
+    }
+}
@@ -0,0 +1,343 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use log::debug;
+
+use crate::document::{Document, DocumentId, DocumentView};
+use crate::entry::{EncodedEntry, Entry, LogId, SeqNum};
+use crate::hash::Hash;
+use crate::identity::PublicKey;
+use crate::operation::{EncodedOperation, Operation, OperationId};
+use crate::schema::SchemaId;
+use crate::storage_provider::error::TransactionError;
+use crate::storage_provider::traits::{
+    DocumentStore, EntryStore, LogStore, OperationStore, TransactionStore,
+};
+use crate::test_utils::memory_store::MemoryStore;
+
+/// Write performed through a transaction handle of a `MemoryStore`.
+#[derive(Debug, Clone)]
+pub(crate) enum Write {
+    InsertLog(LogId, PublicKey, SchemaId, DocumentId),
+    InsertEntry(Entry, EncodedEntry, Option<EncodedOperation>),
+    RemovePayload(Hash),
+    RemoveEntry(Hash),
+    InsertPrunedSeqNum(PublicKey, LogId, SeqNum),
+    InsertOperation(OperationId, PublicKey, Operation, DocumentId),
+    RemoveOperationsByDocumentId(DocumentId),
+    InsertDocument(Document),
+    InsertDocumentView(DocumentView, DocumentId, SchemaId),
+    RemoveDocument(DocumentId),
+}
+
+impl Write {
+    /// Performs this write on the passed store.
+    async fn apply(&self, store: &MemoryStore) -> Result<(), String> {
+        match self {
+            Write::InsertLog(log_id, public_key, schema_id, document_id) => store
+                .insert_log(log_id, public_key, schema_id, document_id)
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string()),
+            Write::InsertEntry(entry, encoded_entry, operation) => store
+                .insert_entry(entry, encoded_entry, operation.as_ref())
+                .await
+                .map_err(|err| err.to_string()),
+            Write::RemovePayload(hash) => store
+                .remove_payload(hash)
+                .await
+                .map_err(|err| err.to_string()),
+            Write::RemoveEntry(hash) => store
+                .remove_entry(hash)
+                .await
+                .map_err(|err| err.to_string()),
+            Write::InsertPrunedSeqNum(public_key, log_id, seq_num) => store
+                .insert_pruned_seq_num(public_key, log_id, seq_num)
+                .await
+                .map_err(|err| err.to_string()),
+            Write::InsertOperation(id, public_key, operation, document_id) => store
+                .insert_operation(id, public_key, operation, document_id)
+                .await
+                .map_err(|err| err.to_string()),
+            Write::RemoveOperationsByDocumentId(document_id) => store
+                .remove_operations_by_document_id(document_id)
+                .await
+                .map_err(|err| err.to_string()),
+            Write::InsertDocument(document) => store
+                .insert_document(document)
+                .await
+                .map_err(|err| err.to_string()),
+            Write::InsertDocumentView(document_view, document_id, schema_id) => store
+                .insert_document_view(document_view, document_id, schema_id)
+                .await
+                .map_err(|err| err.to_string()),
+            Write::RemoveDocument(document_id) => store
+                .remove_document(document_id)
+                .await
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// State of a transaction in progress on a `MemoryStore`.
+#[derive(Debug)]
+pub(crate) struct MemoryTransaction {
+    /// Store the transaction was started on.
+    origin: MemoryStore,
+
+    /// Writes performed through the transaction handle, in their order.
+    writes: Mutex<Vec<Write>>,
+}
+
+impl MemoryTransaction {
+    /// Adds a write to the transaction.
+    pub(crate) fn record(&self, write: Write) {
+        self.writes.lock().unwrap().push(write);
+    }
+}
+
+/// Transaction handles are copies of the store taken when the transaction began, all writes
+/// performed through them are recorded. On commit these writes are repeated on the original
+/// store, first on a copy of it to make sure all of them succeed. Copies don't have subscribers,
+/// events about inserted document views are only sent when the writes are repeated on commit.
+///
+/// This is simple but copies the whole store, it is only intended for use in test environments.
+#[async_trait]
+impl TransactionStore for MemoryStore {
+    type Transaction = MemoryStore;
+
+    /// Start a new transaction by taking a copy of the store.
+    async fn begin_transaction(&self) -> Result<MemoryStore, TransactionError> {
+        if self.transaction.is_some() {
+            return Err(TransactionError::NestedTransaction);
+        }
+
+        debug!("Beginning transaction");
+
+        let transaction = MemoryTransaction {
+            origin: self.clone(),
+            writes: Mutex::new(Vec::new()),
+        };
+
+        Ok(MemoryStore {
+            transaction: Some(Arc::new(transaction)),
+            ..MemoryStore::from_snapshot(self.snapshot())
+        })
+    }
+
+    /// Commit a transaction by repeating it's writes on the store it was started on.
+    async fn commit_transaction(&self, transaction: MemoryStore) -> Result<(), TransactionError> {
+        let transaction = transaction
+            .transaction
+            .ok_or(TransactionError::NoTransaction)?;
+        let writes = std::mem::take(&mut *transaction.writes.lock().unwrap());
+        let origin = &transaction.origin;
+
+        debug!("Committing transaction");
+
+        let _lock = origin.commit_lock.lock().await;
+
+        // Writes of other transactions might have been committed in the meantime, check if all
+        // writes still succeed before touching the store.
+        let copy = MemoryStore::from_snapshot(origin.snapshot());
+        for write in &writes {
+            write.apply(&copy).await.map_err(TransactionError::Custom)?;
+        }
+
+        for write in &writes {
+            write
+                .apply(origin)
+                .await
+                .map_err(TransactionError::Custom)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rollback a transaction by discarding it's copy of the store.
+    async fn rollback_transaction(&self, transaction: MemoryStore) -> Result<(), TransactionError> {
+        if transaction.transaction.is_none() {
+            return Err(TransactionError::NoTransaction);
+        }
+
+        debug!("Rolling back transaction");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::document::DocumentId;
+    use crate::entry::decode::decode_entry;
+    use crate::entry::traits::AsEncodedEntry;
+    use crate::entry::{EncodedEntry, LogId};
+    use crate::identity::KeyPair;
+    use crate::schema::SchemaId;
+    use crate::storage_provider::error::TransactionError;
+    use crate::storage_provider::traits::{EntryStore, LogStore, TransactionStore};
+    use crate::test_utils::fixtures::{
+        document_id, encoded_entry, key_pair, random_document_id, schema_id,
+    };
+    use crate::test_utils::memory_store::MemoryStore;
+
+    #[rstest]
+    #[tokio::test]
+    async fn commit_persists_writes(
+        encoded_entry: EncodedEntry,
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        document_id: DocumentId,
+    ) {
+        let store = MemoryStore::default();
+        let entry = decode_entry(&encoded_entry).unwrap();
+
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction
+            .insert_log(
+                &LogId::default(),
+                &key_pair.public_key(),
+                &schema_id,
+                &document_id,
+            )
+            .await
+            .unwrap();
+        transaction
+            .insert_entry(&entry, &encoded_entry, None)
+            .await
+            .unwrap();
+
+        // Writes are not visible outside of the transaction before it was committed.
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_none());
+
+        store.commit_transaction(transaction).await.unwrap();
+
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            store
+                .get_log_id(&key_pair.public_key(), &document_id)
+                .await
+                .unwrap(),
+            Some(LogId::default())
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn concurrent_transactions(key_pair: KeyPair, schema_id: SchemaId) {
+        let store = MemoryStore::default();
+        let public_key = key_pair.public_key();
+        let document_ids = [random_document_id(), random_document_id()];
+
+        let first = store.begin_transaction().await.unwrap();
+        let second = store.begin_transaction().await.unwrap();
+        let third = store.begin_transaction().await.unwrap();
+
+        first
+            .insert_log(&LogId::new(0), &public_key, &schema_id, &document_ids[0])
+            .await
+            .unwrap();
+        second
+            .insert_log(&LogId::new(1), &public_key, &schema_id, &document_ids[1])
+            .await
+            .unwrap();
+        third
+            .insert_log(&LogId::new(0), &public_key, &schema_id, &document_ids[1])
+            .await
+            .unwrap();
+
+        // Committing one transaction keeps the writes of another one.
+        store.commit_transaction(first).await.unwrap();
+        store.commit_transaction(second).await.unwrap();
+
+        // Transactions whose writes conflict with committed ones fail as a whole.
+        assert!(matches!(
+            store.commit_transaction(third).await,
+            Err(TransactionError::Custom(_))
+        ));
+
+        for (log_id, document_id) in document_ids.iter().enumerate() {
+            assert_eq!(
+                store.get_log_id(&public_key, document_id).await.unwrap(),
+                Some(LogId::new(log_id as u64))
+            );
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn rollback_keeps_other_writes(
+        encoded_entry: EncodedEntry,
+        key_pair: KeyPair,
+        schema_id: SchemaId,
+        document_id: DocumentId,
+    ) {
+        let store = MemoryStore::default();
+        let entry = decode_entry(&encoded_entry).unwrap();
+
+        let transaction = store.begin_transaction().await.unwrap();
+        transaction
+            .insert_entry(&entry, &encoded_entry, None)
+            .await
+            .unwrap();
+
+        // Writes performed directly on the store while the transaction is in progress.
+        store
+            .insert_log(
+                &LogId::default(),
+                &key_pair.public_key(),
+                &schema_id,
+                &document_id,
+            )
+            .await
+            .unwrap();
+
+        store.rollback_transaction(transaction).await.unwrap();
+
+        assert!(store
+            .get_entry(&encoded_entry.hash())
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            store
+                .get_log_id(&key_pair.public_key(), &document_id)
+                .await
+                .unwrap(),
+            Some(LogId::default())
+        );
+    }
+
+    #[tokio::test]
+    async fn transaction_handles() {
+        let store = MemoryStore::default();
+
+        assert!(matches!(
+            store.commit_transaction(store.clone()).await,
+            Err(TransactionError::NoTransaction)
+        ));
+        assert!(matches!(
+            store.rollback_transaction(store.clone()).await,
+            Err(TransactionError::NoTransaction)
+        ));
+
+        let transaction = store.begin_transaction().await.unwrap();
+        assert!(matches!(
+            transaction.begin_transaction().await,
+            Err(TransactionError::NestedTransaction)
+        ));
+        store.commit_transaction(transaction).await.unwrap();
+    }
+}
@@ -6,6 +6,7 @@ use crate::entry::{EncodedEntry, Entry, LogId, SeqNum};
 use crate::hash::Hash;
 use crate::identity::PublicKey;
 use crate::operation::EncodedOperation;
+use crate::storage_provider::traits::EntryWithOperation;
 
 /// A struct which represents an entry and operation pair in storage as a concatenated string.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -36,6 +37,12 @@ impl StorageEntry {
     }
 }
 
+impl EntryWithOperation for StorageEntry {
+    fn payload(&self) -> Option<&EncodedOperation> {
+        self.payload.as_ref()
+    }
+}
+
 impl AsEntry for StorageEntry {
     fn backlink(&self) -> Option<&Hash> {
         self.entry.backlink()
@@ -4,6 +4,7 @@
 //!
 //! It includes fixtures and templates which can be injected into tests, mock node and client
 //! implementations.
+pub mod conformance;
 pub mod constants;
 pub mod fixtures;
 pub mod memory_store;
@@ -16,7 +16,7 @@ use crate::operation::traits::{Actionable, Schematic};
 use crate::operation::validate::validate_operation_format;
 use crate::operation::{
     EncodedOperation, OperationAction, OperationId, OperationValue, OperationVersion,
-    PinnedRelation, PinnedRelationList, Relation, RelationList,
+    PinnedRelation, PinnedRelationList, Relation, RelationList, TextDelta,
 };
 use crate::schema::SchemaId;
 use crate::wasm::error::jserr;
@@ -37,6 +37,7 @@ fn operation_to_js_value(operation_value: &OperationValue) -> Result<JsValue, Js
         OperationValue::RelationList(value) => Ok(jserr!(serialize_to_js(value))),
         OperationValue::PinnedRelation(value) => Ok(jserr!(serialize_to_js(value))),
         OperationValue::PinnedRelationList(value) => Ok(jserr!(serialize_to_js(value))),
+        OperationValue::Text(value) => Ok(jserr!(serialize_to_js(value))),
     }
 }
 
@@ -52,6 +53,7 @@ fn plain_to_js_value(plain_value: &PlainValue) -> Result<JsValue, JsValue> {
         PlainValue::String(value) => Ok(JsValue::from_str(value)),
         PlainValue::AmbiguousRelation(value) => Ok(jserr!(serialize_to_js(value))),
         PlainValue::PinnedRelationList(value) => Ok(jserr!(serialize_to_js(value))),
+        PlainValue::TextDelta(value) => Ok(jserr!(serialize_to_js(value))),
     }
 }
 
@@ -138,6 +140,7 @@ impl OperationFields {
     ///     of hex-encoded operation ids)
     /// - "pinned_relation_list" (array of document view ids, represented as an array
     ///     of arrays of hex-encoded operation ids)
+    /// - "text" (array of text edits, represented as `[position, delete, insert]` arrays)
     ///
     /// This method will throw an error when the field was already set, an invalid type value got
     /// passed or when the value does not reflect the given type.
@@ -227,6 +230,14 @@ impl OperationFields {
                     .insert(name, OperationValue::PinnedRelationList(relations)));
                 Ok(())
             }
+            "text" => {
+                let text_delta: TextDelta = jserr!(
+                    deserialize_from_js(value),
+                    "Expected an array of text edits for field of type text"
+                );
+                jserr!(self.0.insert(name, OperationValue::Text(text_delta)));
+                Ok(())
+            }
             _ => Err(js_sys::Error::new("Unknown value type").into()),
         }
     }